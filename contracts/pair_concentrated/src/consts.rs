@@ -24,6 +24,8 @@ pub const HALFPOW_TOL: Decimal256 = Decimal256::raw(100000000);
 pub const TWO: Decimal256 = Decimal256::raw(2000000000000000000);
 /// Iterations limit for Newton's method
 pub const MAX_ITER: usize = 64;
+/// Iterations limit for the price scale search during force repeg
+pub const FORCE_REPEG_ITERATIONS: usize = 32;
 /// TWAP constant for external oracle prices
 pub const TWAP_PRECISION_DEC: Decimal256 = Decimal256::raw((1e6 * 1e18) as u128);
 
@@ -62,3 +64,11 @@ pub const GAMMA_MAX: Decimal = Decimal::raw(20000000000000000);
 pub const MIN_AMP_CHANGING_TIME: u64 = 86400;
 /// The maximum allowed change of Amplifier or Gamma (10%).
 pub const MAX_CHANGE: Decimal = Decimal::raw(1e17 as u128);
+
+/// The minimum time interval for scheduled pool parameters update
+pub const MIN_PARAMS_CHANGING_TIME: u64 = 86400;
+/// The maximum allowed change of any pool parameter within one schedule (50%).
+pub const MAX_PARAMS_CHANGE: Decimal = Decimal::raw(5e17 as u128);
+
+/// The maximum allowed xcp loss during force repeg (10%).
+pub const MAX_FORCE_REPEG_LOSS: Decimal = Decimal::raw(1e17 as u128);
@@ -26,12 +26,13 @@ use astroport::token::InstantiateMsg as TokenInstantiateMsg;
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 
+use crate::consts::MAX_FORCE_REPEG_LOSS;
 use crate::error::ContractError;
 use crate::math::{calc_d, get_xcp};
 use crate::migration::{migrate_config, migrate_config_from_v140};
 use crate::state::{
-    store_precisions, AmpGamma, Config, PoolParams, PoolState, Precisions, PriceState, BALANCES,
-    CONFIG, OWNERSHIP_PROPOSAL,
+    store_precisions, validate_param, AmpGamma, Config, PoolParams, PoolState, Precisions,
+    PriceState, BALANCES, CONFIG, OWNERSHIP_PROPOSAL,
 };
 use crate::utils::{
     accumulate_prices, assert_max_spread, assert_slippage_tolerance, before_swap_check,
@@ -131,6 +132,7 @@ pub fn instantiate(
         pool_state,
         owner: None,
         track_asset_balances: params.track_asset_balances.unwrap_or_default(),
+        params_schedule: None,
     };
 
     if config.track_asset_balances {
@@ -516,7 +518,8 @@ pub fn provide_liquidity(
         let mut ideposits = deposits;
         ideposits[1] *= config.pool_state.price_state.price_scale;
 
-        share * (Decimal256::one() - calc_provide_fee(&ideposits, &new_xp, &config.pool_params))
+        let pool_params = config.get_pool_params(&env);
+        share * (Decimal256::one() - calc_provide_fee(&ideposits, &new_xp, &pool_params))
     };
 
     // calculate accrued share
@@ -542,12 +545,17 @@ pub fn provide_liquidity(
 
         assert_slippage_tolerance(old_price, new_price, slippage_tolerance)?;
 
+        // Price scale follows the schedule on swaps only while the regular repegging is paused
+        let price_scale_target = config
+            .scheduled_price_scale(&env)
+            .map(|_| config.pool_state.price_state.price_scale);
         config.pool_state.update_price(
-            &config.pool_params,
+            &config.get_pool_params(&env),
             &env,
             total_share + share,
             &new_xp,
             last_price,
+            price_scale_target,
         )?;
     }
 
@@ -786,9 +794,16 @@ fn swap(
 
     // update_price() works only with internal representation
     xs[1] *= config.pool_state.price_state.price_scale;
-    config
-        .pool_state
-        .update_price(&config.pool_params, &env, total_share, &xs, last_price)?;
+    let pool_params = config.get_pool_params(&env);
+    let price_scale_target = config.take_scheduled_price_scale(&env);
+    config.pool_state.update_price(
+        &pool_params,
+        &env,
+        total_share,
+        &xs,
+        last_price,
+        price_scale_target,
+    )?;
 
     let receiver = to.unwrap_or_else(|| sender.clone());
 
@@ -864,9 +879,10 @@ fn update_config(
         return Err(ContractError::Unauthorized {});
     }
 
+    let mut attrs = vec![];
     let action = match from_binary::<ConcentratedPoolUpdateParams>(&params)? {
         ConcentratedPoolUpdateParams::Update(update_params) => {
-            config.pool_params.update_params(update_params)?;
+            config.update_params(&env, update_params)?;
             "update_params"
         }
         ConcentratedPoolUpdateParams::Promote(promote_params) => {
@@ -877,6 +893,45 @@ fn update_config(
             config.pool_state.stop_promotion(&env);
             "stop_changing_amp_gamma"
         }
+        ConcentratedPoolUpdateParams::ScheduleParams(schedule_params) => {
+            config.schedule_params(&env, schedule_params)?;
+            "schedule_params"
+        }
+        ConcentratedPoolUpdateParams::StopParamsSchedule {} => {
+            config.stop_params_schedule(&env);
+            "stop_params_schedule"
+        }
+        ConcentratedPoolUpdateParams::ForceRepeg {
+            target_price,
+            max_loss,
+        } => {
+            if target_price.is_zero() {
+                return Err(StdError::generic_err("Target price can not be zero").into());
+            }
+            if max_loss.is_zero() {
+                return Err(StdError::generic_err("Max loss can not be zero").into());
+            }
+            validate_param("max_loss", max_loss, Decimal::zero(), MAX_FORCE_REPEG_LOSS)?;
+
+            let precisions = Precisions::new(deps.storage)?;
+            let mut xs = query_pools(deps.querier, &env.contract.address, &config, &precisions)?
+                .into_iter()
+                .map(|asset| asset.amount)
+                .collect_vec();
+            if xs.iter().any(|amount| amount.is_zero()) {
+                return Err(StdError::generic_err("One of the pools is empty").into());
+            }
+
+            // force_repeg() works only with internal representation
+            xs[1] *= config.pool_state.price_state.price_scale;
+            let price_scale =
+                config
+                    .pool_state
+                    .force_repeg(&env, &xs, target_price.into(), max_loss.into())?;
+            attrs.push(attr("price_scale", price_scale.to_string()));
+
+            "force_repeg"
+        }
         ConcentratedPoolUpdateParams::EnableAssetBalancesTracking {} => {
             if config.track_asset_balances {
                 return Err(ContractError::AssetBalancesTrackingIsAlreadyEnabled {});
@@ -896,7 +951,9 @@ fn update_config(
     };
     CONFIG.save(deps.storage, &config)?;
 
-    Ok(Response::new().add_attribute("action", action))
+    Ok(Response::new()
+        .add_attribute("action", action)
+        .add_attributes(attrs))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -1,4 +1,4 @@
-use crate::consts::MIN_AMP_CHANGING_TIME;
+use crate::consts::{MIN_AMP_CHANGING_TIME, MIN_PARAMS_CHANGING_TIME};
 use astroport::asset::MINIMUM_LIQUIDITY_AMOUNT;
 use cosmwasm_std::{ConversionOverflowError, Decimal, OverflowError, StdError};
 use thiserror::Error;
@@ -38,6 +38,20 @@ pub enum ContractError {
     )]
     MinChangingTimeAssertion {},
 
+    #[error(
+        "{0} error: The difference between the current and scheduled values must not exceed {1} percent"
+    )]
+    MaxParamsChangeAssertion(String, Decimal),
+
+    #[error(
+        "Pool parameters cannot be scheduled more often than once per {} seconds",
+        MIN_PARAMS_CHANGING_TIME
+    )]
+    MinParamsChangingTimeAssertion {},
+
+    #[error("Pool parameters are being changed gradually. Stop the schedule first")]
+    ParamsScheduleInProgress {},
+
     #[error("Initial provide can not be one-sided")]
     InvalidZeroAmount {},
 
@@ -71,6 +71,7 @@ pub(crate) fn migrate_config(storage: &mut dyn Storage) -> Result<(), StdError>
         pool_state: old_config.pool_state,
         owner: old_config.owner,
         track_asset_balances: false,
+        params_schedule: None,
     };
 
     CONFIG.save(storage, &new_config)?;
@@ -112,6 +113,7 @@ pub(crate) fn migrate_config_from_v140(storage: &mut dyn Storage) -> Result<(),
         pool_state: old_config.pool_state,
         owner: old_config.owner,
         track_asset_balances: false,
+        params_schedule: None,
     };
 
     CONFIG.save(storage, &new_config)?;
@@ -244,6 +244,7 @@ pub fn query_lp_price(deps: Deps) -> StdResult<Decimal256> {
 pub fn query_config(deps: Deps, env: Env) -> StdResult<ConfigResponse> {
     let config = CONFIG.load(deps.storage)?;
     let amp_gamma = config.pool_state.get_amp_gamma(&env);
+    let pool_params = config.get_pool_params(&env);
     let dec256_price_scale = config.pool_state.price_state.price_scale;
     let price_scale = Decimal::from_atomics(
         Uint128::try_from(dec256_price_scale.atomics())?,
@@ -258,14 +259,15 @@ pub fn query_config(deps: Deps, env: Env) -> StdResult<ConfigResponse> {
         params: Some(to_binary(&ConcentratedPoolConfig {
             amp: amp_gamma.amp,
             gamma: amp_gamma.gamma,
-            mid_fee: config.pool_params.mid_fee,
-            out_fee: config.pool_params.out_fee,
-            fee_gamma: config.pool_params.fee_gamma,
-            repeg_profit_threshold: config.pool_params.repeg_profit_threshold,
-            min_price_scale_delta: config.pool_params.min_price_scale_delta,
+            mid_fee: pool_params.mid_fee,
+            out_fee: pool_params.out_fee,
+            fee_gamma: pool_params.fee_gamma,
+            repeg_profit_threshold: pool_params.repeg_profit_threshold,
+            min_price_scale_delta: pool_params.min_price_scale_delta,
             price_scale,
-            ma_half_time: config.pool_params.ma_half_time,
+            ma_half_time: pool_params.ma_half_time,
             track_asset_balances: config.track_asset_balances,
+            params_schedule: config.params_schedule_response(&env)?,
         })?),
         owner: config.owner.unwrap_or(factory_config.owner),
         factory_addr: config.factory_addr,
@@ -8,13 +8,16 @@ use cw_storage_plus::{Item, Map, SnapshotMap};
 
 use astroport::asset::{AssetInfo, PairInfo};
 use astroport::common::OwnershipProposal;
-use astroport::cosmwasm_ext::{AbsDiff, IntegerToDecimal};
-use astroport::pair_concentrated::{PromoteParams, UpdatePoolParams};
+use astroport::cosmwasm_ext::{AbsDiff, ConvertInto, IntegerToDecimal};
+use astroport::pair_concentrated::{
+    ParamsScheduleResponse, PromoteParams, ScheduleParams, UpdatePoolParams,
+};
 
 use crate::consts::{
-    AMP_MAX, AMP_MIN, FEE_GAMMA_MAX, FEE_GAMMA_MIN, FEE_TOL, GAMMA_MAX, GAMMA_MIN, MAX_CHANGE,
-    MAX_FEE, MA_HALF_TIME_LIMITS, MIN_AMP_CHANGING_TIME, MIN_FEE, N_POW2, PRICE_SCALE_DELTA_MAX,
-    PRICE_SCALE_DELTA_MIN, REPEG_PROFIT_THRESHOLD_MAX, REPEG_PROFIT_THRESHOLD_MIN, TWO,
+    AMP_MAX, AMP_MIN, FEE_GAMMA_MAX, FEE_GAMMA_MIN, FEE_TOL, FORCE_REPEG_ITERATIONS, GAMMA_MAX,
+    GAMMA_MIN, MAX_CHANGE, MAX_FEE, MAX_PARAMS_CHANGE, MA_HALF_TIME_LIMITS, MIN_AMP_CHANGING_TIME,
+    MIN_FEE, MIN_PARAMS_CHANGING_TIME, N_POW2, PRICE_SCALE_DELTA_MAX, PRICE_SCALE_DELTA_MIN,
+    REPEG_PROFIT_THRESHOLD_MAX, REPEG_PROFIT_THRESHOLD_MIN, TWO,
 };
 use crate::error::ContractError;
 use crate::math::{calc_d, get_xcp, half_float_pow};
@@ -30,7 +33,7 @@ pub struct Config {
     pub block_time_last: u64,
     /// The vector contains cumulative prices for each pair of assets in the pool
     pub cumulative_prices: Vec<(AssetInfo, AssetInfo, Uint128)>,
-    /// Pool parameters
+    /// Pool parameters. If pool parameters are being changed gradually then this field holds target values.
     pub pool_params: PoolParams,
     /// Pool state
     pub pool_state: PoolState,
@@ -38,6 +41,235 @@ pub struct Config {
     pub owner: Option<Addr>,
     /// Whether asset balances are tracked over blocks or not.
     pub track_asset_balances: bool,
+    /// The last pool parameters schedule
+    #[serde(default)]
+    pub params_schedule: Option<ParamsSchedule>,
+}
+
+impl Config {
+    /// Calculates current pool parameters.
+    /// If block time >= schedule future_time then it returns self.pool_params.
+    pub fn get_pool_params(&self, env: &Env) -> PoolParams {
+        match &self.params_schedule {
+            Some(schedule) if env.block.time.seconds() < schedule.future_time => {
+                schedule.get_params(env, &self.pool_params)
+            }
+            _ => self.pool_params.clone(),
+        }
+    }
+
+    /// Updates pool parameters instantly. Fails if pool parameters are being changed gradually.
+    /// Every parameter can't be changed by more than [`MAX_PARAMS_CHANGE`] at once.
+    pub fn update_params(
+        &mut self,
+        env: &Env,
+        update_params: UpdatePoolParams,
+    ) -> Result<(), ContractError> {
+        if self.is_schedule_active(env) {
+            return Err(ContractError::ParamsScheduleInProgress {});
+        }
+
+        let mut next_params = self.pool_params.clone();
+        next_params.update_params(update_params)?;
+        validate_params_change(&self.pool_params, &next_params)?;
+        self.pool_params = next_params;
+
+        Ok(())
+    }
+
+    /// Validates pool parameters schedule.
+    /// Saves current values in schedule initial params and setups self.pool_params as target values.
+    /// If pool parameters are being changed then current values will be used as initial values.
+    pub fn schedule_params(
+        &mut self,
+        env: &Env,
+        params: ScheduleParams,
+    ) -> Result<(), ContractError> {
+        let block_time = env.block.time.seconds();
+        let last_initial_time = self
+            .params_schedule
+            .as_ref()
+            .map(|schedule| schedule.initial_time)
+            .unwrap_or_default();
+
+        // Validate time interval
+        if block_time < last_initial_time + MIN_PARAMS_CHANGING_TIME
+            || params.future_time < block_time + MIN_PARAMS_CHANGING_TIME
+        {
+            return Err(ContractError::MinParamsChangingTimeAssertion {});
+        }
+
+        // Calculate current pool parameters
+        let cur_params = self.get_pool_params(env);
+
+        // Validate target values
+        let mut next_params = cur_params.clone();
+        next_params.update_params(params.params)?;
+
+        // Validate pool parameters are being changed by <= 50%
+        validate_params_change(&cur_params, &next_params)?;
+
+        let price_scale = match params.price_scale {
+            Some(future) => {
+                let initial = self.pool_state.price_state.price_scale;
+                let future = Decimal256::from(future);
+                if (future / initial).diff(Decimal256::one()) > Decimal256::from(MAX_PARAMS_CHANGE)
+                {
+                    return Err(ContractError::MaxParamsChangeAssertion(
+                        "price_scale".to_string(),
+                        MAX_PARAMS_CHANGE,
+                    ));
+                }
+
+                Some(PriceScaleSchedule { initial, future })
+            }
+            None => None,
+        };
+
+        self.params_schedule = Some(ParamsSchedule {
+            initial: cur_params,
+            initial_time: block_time,
+            future_time: params.future_time,
+            price_scale,
+        });
+        self.pool_params = next_params;
+
+        Ok(())
+    }
+
+    /// Stops pool parameters schedule. Saves current values in self.pool_params.
+    /// Price scale keeps the value which was set by the last swap.
+    pub fn stop_params_schedule(&mut self, env: &Env) {
+        self.pool_params = self.get_pool_params(env);
+        if let Some(schedule) = &mut self.params_schedule {
+            schedule.future_time = schedule.future_time.min(env.block.time.seconds());
+            schedule.price_scale = None;
+        }
+    }
+
+    /// Returns price scale which should be set according to the schedule if any.
+    pub fn scheduled_price_scale(&self, env: &Env) -> Option<Decimal256> {
+        self.params_schedule
+            .as_ref()
+            .and_then(|schedule| schedule.get_price_scale(env))
+    }
+
+    /// Same as [`Config::scheduled_price_scale`] but removes the price scale schedule once it is finished.
+    /// Thus the target price scale is applied only once and the regular repegging is resumed.
+    pub fn take_scheduled_price_scale(&mut self, env: &Env) -> Option<Decimal256> {
+        let price_scale = self.scheduled_price_scale(env);
+        if !self.is_schedule_active(env) {
+            if let Some(schedule) = &mut self.params_schedule {
+                schedule.price_scale = None;
+            }
+        }
+
+        price_scale
+    }
+
+    /// Returns active pool parameters schedule if any.
+    pub fn params_schedule_response(&self, env: &Env) -> StdResult<Option<ParamsScheduleResponse>> {
+        self.params_schedule
+            .as_ref()
+            .filter(|_| self.is_schedule_active(env))
+            .map(|schedule| {
+                let (initial_price_scale, future_price_scale) = match &schedule.price_scale {
+                    Some(price_scale) => (
+                        Some(price_scale.initial.conv()?),
+                        Some(price_scale.future.conv()?),
+                    ),
+                    None => (None, None),
+                };
+
+                Ok(ParamsScheduleResponse {
+                    initial_params: schedule.initial.clone().into(),
+                    future_params: self.pool_params.clone().into(),
+                    initial_price_scale,
+                    future_price_scale,
+                    initial_time: schedule.initial_time,
+                    future_time: schedule.future_time,
+                })
+            })
+            .transpose()
+    }
+
+    fn is_schedule_active(&self, env: &Env) -> bool {
+        matches!(&self.params_schedule, Some(schedule) if env.block.time.seconds() < schedule.future_time)
+    }
+}
+
+/// Internal structure which stores the pool parameters schedule.
+/// Target values are stored in [`Config`] pool_params.
+#[cw_serde]
+pub struct ParamsSchedule {
+    /// Pool parameters at the moment when the schedule was started
+    pub initial: PoolParams,
+    /// Timestamp when the schedule was started
+    pub initial_time: u64,
+    /// Timestamp when pool parameters should become equal to the target values
+    pub future_time: u64,
+    /// Price scale schedule. It is removed once the target price scale is applied
+    #[serde(default)]
+    pub price_scale: Option<PriceScaleSchedule>,
+}
+
+/// Internal structure which stores initial and target price scale values of the schedule.
+#[cw_serde]
+pub struct PriceScaleSchedule {
+    /// Price scale at the moment when the schedule was started
+    pub initial: Decimal256,
+    /// Target price scale
+    pub future: Decimal256,
+}
+
+impl ParamsSchedule {
+    /// Calculates current pool parameters using linear interpolation
+    /// between self.initial and `future` values.
+    pub fn get_params(&self, env: &Env, future: &PoolParams) -> PoolParams {
+        let block_time = env.block.time.seconds();
+        let total = self.future_time - self.initial_time;
+        let passed = block_time - self.initial_time;
+        let left = total - passed;
+
+        // P1 = ( P0 * (t_end - block_time) + P1 * (block_time - t_init) ) / (t_end - t_init)
+        let interpolate = |initial: Decimal, future: Decimal| {
+            (initial * left.to_decimal() + future * passed.to_decimal()) / total.to_decimal()
+        };
+        let ma_half_time = (self.initial.ma_half_time as u128 * left as u128
+            + future.ma_half_time as u128 * passed as u128)
+            / total as u128;
+
+        PoolParams {
+            mid_fee: interpolate(self.initial.mid_fee, future.mid_fee),
+            out_fee: interpolate(self.initial.out_fee, future.out_fee),
+            fee_gamma: interpolate(self.initial.fee_gamma, future.fee_gamma),
+            repeg_profit_threshold: interpolate(
+                self.initial.repeg_profit_threshold,
+                future.repeg_profit_threshold,
+            ),
+            min_price_scale_delta: interpolate(
+                self.initial.min_price_scale_delta,
+                future.min_price_scale_delta,
+            ),
+            ma_half_time: ma_half_time as u64,
+        }
+    }
+
+    /// Calculates current price scale using linear interpolation between initial and target values.
+    /// Returns None if price scale is not scheduled.
+    pub fn get_price_scale(&self, env: &Env) -> Option<Decimal256> {
+        let price_scale = self.price_scale.as_ref()?;
+        let block_time = env.block.time.seconds();
+        if block_time >= self.future_time {
+            return Some(price_scale.future);
+        }
+
+        let total = Decimal256::from_ratio(self.future_time - self.initial_time, 1u8);
+        let passed = Decimal256::from_ratio(block_time - self.initial_time, 1u8);
+        let left = total - passed;
+
+        Some((price_scale.initial * left + price_scale.future * passed) / total)
+    }
 }
 
 /// This structure stores the pool parameters which may be adjusted via the `update_pool_params`.
@@ -60,7 +292,7 @@ pub struct PoolParams {
 }
 
 /// Validates input value against its limits.
-fn validate_param<T>(name: &str, val: T, min: T, max: T) -> Result<(), ContractError>
+pub(crate) fn validate_param<T>(name: &str, val: T, min: T, max: T) -> Result<(), ContractError>
 where
     T: PartialOrd + Display,
 {
@@ -75,6 +307,53 @@ where
     }
 }
 
+/// Validates that every pool parameter is changed by no more than [`MAX_PARAMS_CHANGE`].
+fn validate_params_change(cur: &PoolParams, next: &PoolParams) -> Result<(), ContractError> {
+    validate_change("mid_fee", cur.mid_fee, next.mid_fee)?;
+    validate_change("out_fee", cur.out_fee, next.out_fee)?;
+    validate_change("fee_gamma", cur.fee_gamma, next.fee_gamma)?;
+    validate_change(
+        "repeg_profit_threshold",
+        cur.repeg_profit_threshold,
+        next.repeg_profit_threshold,
+    )?;
+    validate_change(
+        "min_price_scale_delta",
+        cur.min_price_scale_delta,
+        next.min_price_scale_delta,
+    )?;
+    validate_change(
+        "ma_half_time",
+        cur.ma_half_time.to_decimal(),
+        next.ma_half_time.to_decimal(),
+    )
+}
+
+/// Validates the difference between current and next values of a pool parameter.
+fn validate_change(name: &str, cur: Decimal, next: Decimal) -> Result<(), ContractError> {
+    if !cur.is_zero() && (next / cur).diff(Decimal::one()) > MAX_PARAMS_CHANGE {
+        Err(ContractError::MaxParamsChangeAssertion(
+            name.to_string(),
+            MAX_PARAMS_CHANGE,
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+impl From<PoolParams> for UpdatePoolParams {
+    fn from(params: PoolParams) -> Self {
+        Self {
+            mid_fee: Some(params.mid_fee),
+            out_fee: Some(params.out_fee),
+            fee_gamma: Some(params.fee_gamma),
+            repeg_profit_threshold: Some(params.repeg_profit_threshold),
+            min_price_scale_delta: Some(params.min_price_scale_delta),
+            ma_half_time: Some(params.ma_half_time),
+        }
+    }
+}
+
 impl PoolParams {
     /// Intended to update current pool parameters. Performs validation of the new parameters.
     ///
@@ -288,6 +567,8 @@ impl PoolState {
     /// * **total_lp** total LP tokens were minted
     /// * **cur_xs** - internal representation of pool volumes
     /// * **cur_price** - last price happened in the previous action (swap, provide or withdraw)
+    /// * **price_scale_target** - scheduled price scale. If set then price scale is moved to this value
+    /// instead of the regular repegging
     pub fn update_price(
         &mut self,
         pool_params: &PoolParams,
@@ -295,6 +576,7 @@ impl PoolState {
         total_lp: Decimal256,
         cur_xs: &[Decimal256],
         cur_price: Decimal256,
+        price_scale_target: Option<Decimal256>,
     ) -> StdResult<()> {
         let amp_gamma = self.get_amp_gamma(env);
         let block_time = env.block.time.seconds();
@@ -330,6 +612,20 @@ impl PoolState {
 
         price_state.xcp = xcp;
 
+        if let Some(price_scale_new) = price_scale_target {
+            if price_scale_new != price_state.price_scale {
+                let new_xcp =
+                    calc_repeg_xcp(cur_xs, price_state.price_scale, price_scale_new, &amp_gamma)?;
+                // Same as force repeg, the scheduled one must never increase xcp_profit
+                price_state.xcp_profit =
+                    (price_state.xcp_profit * new_xcp / xcp).min(price_state.xcp_profit);
+                price_state.xcp = new_xcp;
+                price_state.price_scale = price_scale_new;
+            }
+
+            return Ok(());
+        }
+
         let xcp_profit = price_state.xcp_profit;
 
         let norm = (price_state.oracle_price / price_state.price_scale).diff(Decimal256::one());
@@ -345,13 +641,8 @@ impl PoolState {
                 + scale_delta * price_state.oracle_price;
             let price_scale_new = numerator / norm;
 
-            let xs = [
-                cur_xs[0],
-                cur_xs[1] * price_scale_new / price_state.price_scale,
-            ];
-            let new_d = calc_d(&xs, &amp_gamma)?;
-
-            let new_xcp = get_xcp(new_d, price_scale_new);
+            let new_xcp =
+                calc_repeg_xcp(cur_xs, price_state.price_scale, price_scale_new, &amp_gamma)?;
             let new_virtual_price = new_xcp / total_lp;
 
             if TWO * new_virtual_price > xcp_profit + Decimal256::one() {
@@ -362,6 +653,72 @@ impl PoolState {
 
         Ok(())
     }
+
+    /// Forcibly moves price_scale toward the target price.
+    /// If the pool's xcp drops by more than max_loss at the target price then
+    /// price_scale is moved only partially (as far as the loss limit allows).
+    /// xcp_profit is decreased in the same proportion as xcp and never increased. Returns new price_scale.
+    ///
+    /// * **cur_xs** - internal representation of pool volumes
+    pub fn force_repeg(
+        &mut self,
+        env: &Env,
+        cur_xs: &[Decimal256],
+        target_price: Decimal256,
+        max_loss: Decimal256,
+    ) -> StdResult<Decimal256> {
+        let amp_gamma = self.get_amp_gamma(env);
+        let price_state = &mut self.price_state;
+
+        let cur_d = calc_d(cur_xs, &amp_gamma)?;
+        let xcp = get_xcp(cur_d, price_state.price_scale);
+        let min_xcp = xcp * (Decimal256::one() - max_loss);
+
+        let mut price_scale_new = target_price;
+        let mut new_xcp =
+            calc_repeg_xcp(cur_xs, price_state.price_scale, price_scale_new, &amp_gamma)?;
+
+        if new_xcp < min_xcp {
+            // Searching for the furthest price_scale which satisfies the loss limit
+            let (mut acceptable, mut unacceptable) = (price_state.price_scale, target_price);
+            price_scale_new = price_state.price_scale;
+            new_xcp = xcp;
+            for _ in 0..FORCE_REPEG_ITERATIONS {
+                let mid = (acceptable + unacceptable) / TWO;
+                let mid_xcp = calc_repeg_xcp(cur_xs, price_state.price_scale, mid, &amp_gamma)?;
+                if mid_xcp >= min_xcp {
+                    acceptable = mid;
+                    price_scale_new = mid;
+                    new_xcp = mid_xcp;
+                } else {
+                    unacceptable = mid;
+                }
+            }
+        }
+
+        // Repeg must never increase xcp_profit otherwise it would unlock profit which wasn't earned
+        price_state.xcp_profit =
+            (price_state.xcp_profit * new_xcp / xcp).min(price_state.xcp_profit);
+        price_state.xcp = new_xcp;
+        price_state.price_scale = price_scale_new;
+
+        Ok(price_scale_new)
+    }
+}
+
+/// Calculates xcp value if price scale was changed to the new value.
+///
+/// * **cur_xs** - internal representation of pool volumes
+fn calc_repeg_xcp(
+    cur_xs: &[Decimal256],
+    price_scale: Decimal256,
+    price_scale_new: Decimal256,
+    amp_gamma: &AmpGamma,
+) -> StdResult<Decimal256> {
+    let xs = [cur_xs[0], cur_xs[1] * price_scale_new / price_scale];
+    let new_d = calc_d(&xs, amp_gamma)?;
+
+    Ok(get_xcp(new_d, price_scale_new))
 }
 
 /// Store all token precisions.
@@ -679,6 +1036,46 @@ mod test {
         vec![xs[0], xs[1] * price_scale]
     }
 
+    #[test]
+    fn check_force_repeg_never_increases_xcp_profit() {
+        let env = mock_env();
+        let mut pool_state = PoolState {
+            initial: AmpGamma::default(),
+            future: AmpGamma {
+                amp: f64_to_dec(40f64),
+                gamma: f64_to_dec(0.000145),
+            },
+            future_time: 0,
+            initial_time: 0,
+            price_state: PriceState {
+                oracle_price: f64_to_dec256(2f64),
+                last_price: f64_to_dec256(2f64),
+                price_scale: f64_to_dec256(2f64),
+                last_price_update: env.block.time.seconds(),
+                xcp_profit: Decimal256::one(),
+                xcp: Decimal256::zero(),
+            },
+        };
+
+        // Moving price_scale toward 1.5 increases xcp for these pool volumes
+        let ext_xs = [f64_to_dec256(1_000_000f64), f64_to_dec256(400_000f64)];
+        let xs = to_internal_repr(&ext_xs, pool_state.price_state.price_scale);
+        let price_scale = pool_state
+            .force_repeg(&env, &xs, f64_to_dec256(1.5), f64_to_dec256(0.05))
+            .unwrap();
+
+        assert_eq!(price_scale, f64_to_dec256(1.5));
+        assert_eq!(pool_state.price_state.xcp_profit, Decimal256::one());
+
+        // Moving price_scale back decreases xcp thus xcp_profit is decreased as well
+        let xs = to_internal_repr(&ext_xs, pool_state.price_state.price_scale);
+        pool_state
+            .force_repeg(&env, &xs, f64_to_dec256(2f64), f64_to_dec256(0.05))
+            .unwrap();
+
+        assert!(pool_state.price_state.xcp_profit < Decimal256::one());
+    }
+
     #[test]
     fn check_repeg() {
         let (amp, gamma) = (40f64, 0.000145);
@@ -734,6 +1131,7 @@ mod test {
                 total_lp,
                 &to_internal_repr(&ext_xs, pool_state.price_state.price_scale),
                 price,
+                None,
             )
             .unwrap();
 
@@ -755,6 +1153,7 @@ mod test {
                 total_lp,
                 &to_internal_repr(&ext_xs, pool_state.price_state.price_scale),
                 price,
+                None,
             )
             .unwrap();
 
@@ -776,6 +1175,7 @@ mod test {
                 total_lp,
                 &to_internal_repr(&ext_xs, pool_state.price_state.price_scale),
                 price,
+                None,
             )
             .unwrap();
 
@@ -798,6 +1198,7 @@ mod test {
                 total_lp,
                 &to_internal_repr(&ext_xs, pool_state.price_state.price_scale),
                 price,
+                None,
             )
             .unwrap();
 
@@ -820,6 +1221,7 @@ mod test {
                 total_lp,
                 &to_internal_repr(&ext_xs, pool_state.price_state.price_scale),
                 price,
+                None,
             )
             .unwrap();
 
@@ -842,6 +1244,7 @@ mod test {
                 total_lp,
                 &to_internal_repr(&ext_xs, pool_state.price_state.price_scale),
                 price,
+                None,
             )
             .unwrap();
     }
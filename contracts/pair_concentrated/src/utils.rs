@@ -319,7 +319,7 @@ pub fn compute_swap(
     // Since price_scale moves slower than real price spread fee may become negative
     let spread_fee = (offer_amount * price).saturating_sub(dy);
 
-    let fee_rate = config.pool_params.fee(&ixs);
+    let fee_rate = config.get_pool_params(env).fee(&ixs);
     let total_fee = fee_rate * dy;
     dy -= total_fee;
 
@@ -353,7 +353,7 @@ pub fn compute_offer_amount(
 
     // It's hard to predict fee rate thus we use maximum possible fee rate
     let before_fee = want_amount
-        * (Decimal256::one() - Decimal256::from(config.pool_params.out_fee))
+        * (Decimal256::one() - Decimal256::from(config.get_pool_params(env).out_fee))
             .inv()
             .unwrap();
     let mut fee = before_fee - want_amount;
@@ -20,7 +20,7 @@ use astroport::pair::{
     SimulationResponse,
 };
 use astroport::pair_concentrated::{
-    ConcentratedPoolParams, ConcentratedPoolUpdateParams, QueryMsg,
+    ConcentratedPoolConfig, ConcentratedPoolParams, ConcentratedPoolUpdateParams, QueryMsg,
 };
 use astroport_pair_concentrated::contract::{execute, instantiate, reply};
 use astroport_pair_concentrated::queries::query;
@@ -484,16 +484,20 @@ impl Helper {
         )
     }
 
-    pub fn query_amp_gamma(&self) -> StdResult<AmpGammaResponse> {
+    pub fn query_pool_config(&self) -> StdResult<ConcentratedPoolConfig> {
         let config_resp: ConfigResponse = self
             .app
             .wrap()
             .query_wasm_smart(&self.pair_addr, &QueryMsg::Config {})?;
-        let params: ConcentratedPoolParams = from_slice(
+        from_slice(
             &config_resp
                 .params
                 .ok_or_else(|| StdError::generic_err("Params not found in config response!"))?,
-        )?;
+        )
+    }
+
+    pub fn query_amp_gamma(&self) -> StdResult<AmpGammaResponse> {
+        let params = self.query_pool_config()?;
         Ok(AmpGammaResponse {
             amp: params.amp,
             gamma: params.gamma,
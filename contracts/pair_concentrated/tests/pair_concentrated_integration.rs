@@ -9,9 +9,12 @@ use astroport::asset::{
 
 use astroport::pair::{ExecuteMsg, PoolResponse};
 use astroport::pair_concentrated::{
-    ConcentratedPoolParams, ConcentratedPoolUpdateParams, PromoteParams, QueryMsg, UpdatePoolParams,
+    ConcentratedPoolParams, ConcentratedPoolUpdateParams, PromoteParams, QueryMsg, ScheduleParams,
+    UpdatePoolParams,
+};
+use astroport_pair_concentrated::consts::{
+    AMP_MAX, AMP_MIN, MAX_FORCE_REPEG_LOSS, MAX_PARAMS_CHANGE, MA_HALF_TIME_LIMITS,
 };
-use astroport_pair_concentrated::consts::{AMP_MAX, AMP_MIN, MA_HALF_TIME_LIMITS};
 use astroport_pair_concentrated::error::ContractError;
 
 use crate::helper::{dec_to_f64, f64_to_dec, AppExtension, Helper, TestCoin};
@@ -813,6 +816,346 @@ fn check_amp_gamma_change() {
     assert_eq!(amp_gamma.future_time, last_change_time);
 }
 
+#[test]
+fn check_params_schedule() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::cw20("USDC")];
+
+    let params = ConcentratedPoolParams {
+        amp: f64_to_dec(40f64),
+        gamma: f64_to_dec(0.0001),
+        mid_fee: f64_to_dec(0.0026),
+        out_fee: f64_to_dec(0.0045),
+        fee_gamma: f64_to_dec(0.00023),
+        repeg_profit_threshold: f64_to_dec(0.000002),
+        min_price_scale_delta: f64_to_dec(0.000146),
+        price_scale: Decimal::one(),
+        ma_half_time: 600,
+        track_asset_balances: None,
+    };
+    let mut helper = Helper::new(&owner, test_coins.clone(), params).unwrap();
+
+    helper.app.next_block(86400);
+
+    let schedule = |mid_fee: f64, ma_half_time: u64, future_time: u64| {
+        ConcentratedPoolUpdateParams::ScheduleParams(ScheduleParams {
+            params: UpdatePoolParams {
+                mid_fee: Some(f64_to_dec(mid_fee)),
+                out_fee: None,
+                fee_gamma: None,
+                repeg_profit_threshold: None,
+                min_price_scale_delta: None,
+                ma_half_time: Some(ma_half_time),
+            },
+            price_scale: None,
+            future_time,
+        })
+    };
+
+    let random_user = Addr::unchecked("random");
+    let future_time = helper.app.block_info().time.seconds() + 100_000;
+    let err = helper
+        .update_config(&random_user, &schedule(0.003, 800, future_time))
+        .unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+    let err = helper
+        .update_config(
+            &owner,
+            &schedule(0.003, 800, helper.app.block_info().time.seconds() + 100),
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::MinParamsChangingTimeAssertion {},
+        err.downcast().unwrap()
+    );
+
+    let err = helper
+        .update_config(&owner, &schedule(0.003, 1200, future_time))
+        .unwrap_err();
+    assert_eq!(
+        ContractError::MaxParamsChangeAssertion("ma_half_time".to_string(), MAX_PARAMS_CHANGE),
+        err.downcast().unwrap()
+    );
+
+    helper
+        .update_config(&owner, &schedule(0.003, 800, future_time))
+        .unwrap();
+
+    let config = helper.query_pool_config().unwrap();
+    assert_eq!(dec_to_f64(config.mid_fee), 0.0026);
+    assert_eq!(config.ma_half_time, 600);
+    let schedule_resp = config.params_schedule.unwrap();
+    assert_eq!(schedule_resp.future_time, future_time);
+    assert_eq!(schedule_resp.future_params.mid_fee, Some(f64_to_dec(0.003)));
+    assert_eq!(schedule_resp.future_params.ma_half_time, Some(800));
+
+    helper.app.next_block(50_000);
+
+    let config = helper.query_pool_config().unwrap();
+    assert_eq!(dec_to_f64(config.mid_fee), 0.0028);
+    assert_eq!(config.ma_half_time, 700);
+    assert_eq!(dec_to_f64(config.out_fee), 0.0045);
+
+    // Instant update is forbidden while parameters are being changed
+    let action = ConcentratedPoolUpdateParams::Update(UpdatePoolParams {
+        mid_fee: None,
+        out_fee: None,
+        fee_gamma: Some(f64_to_dec(0.0003)),
+        repeg_profit_threshold: None,
+        min_price_scale_delta: None,
+        ma_half_time: None,
+    });
+    let err = helper.update_config(&owner, &action).unwrap_err();
+    assert_eq!(
+        ContractError::ParamsScheduleInProgress {},
+        err.downcast().unwrap()
+    );
+
+    // Swaps work in the middle of the schedule
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+    let user = Addr::unchecked("user");
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(100_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, None).unwrap();
+
+    // stop changing parameters thus fixing current values
+    let action = ConcentratedPoolUpdateParams::StopParamsSchedule {};
+    helper.update_config(&owner, &action).unwrap();
+
+    helper.app.next_block(50_000);
+
+    let config = helper.query_pool_config().unwrap();
+    assert_eq!(dec_to_f64(config.mid_fee), 0.0028);
+    assert_eq!(config.ma_half_time, 700);
+    assert_eq!(config.params_schedule, None);
+
+    // Pool parameters can be updated instantly again
+    let action = ConcentratedPoolUpdateParams::Update(UpdatePoolParams {
+        mid_fee: None,
+        out_fee: None,
+        fee_gamma: Some(f64_to_dec(0.0003)),
+        repeg_profit_threshold: None,
+        min_price_scale_delta: None,
+        ma_half_time: None,
+    });
+    helper.update_config(&owner, &action).unwrap();
+
+    let config = helper.query_pool_config().unwrap();
+    assert_eq!(dec_to_f64(config.fee_gamma), 0.0003);
+    assert_eq!(dec_to_f64(config.mid_fee), 0.0028);
+
+    // Instant update is bounded by the same max change as schedules
+    let action = ConcentratedPoolUpdateParams::Update(UpdatePoolParams {
+        mid_fee: Some(f64_to_dec(0.001)),
+        out_fee: None,
+        fee_gamma: None,
+        repeg_profit_threshold: None,
+        min_price_scale_delta: None,
+        ma_half_time: None,
+    });
+    let err = helper.update_config(&owner, &action).unwrap_err();
+    assert_eq!(
+        ContractError::MaxParamsChangeAssertion("mid_fee".to_string(), MAX_PARAMS_CHANGE),
+        err.downcast().unwrap()
+    );
+}
+
+#[test]
+fn check_price_scale_schedule() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::cw20("USDC")];
+
+    let params = ConcentratedPoolParams {
+        amp: f64_to_dec(40f64),
+        gamma: f64_to_dec(0.000145),
+        mid_fee: f64_to_dec(0.0026),
+        out_fee: f64_to_dec(0.0045),
+        fee_gamma: f64_to_dec(0.00023),
+        repeg_profit_threshold: f64_to_dec(0.000002),
+        min_price_scale_delta: f64_to_dec(0.000146),
+        price_scale: Decimal::one(),
+        ma_half_time: 600,
+        track_asset_balances: None,
+    };
+    let mut helper = Helper::new(&owner, test_coins.clone(), params).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    helper.app.next_block(86400);
+
+    let schedule = |price_scale: f64, future_time: u64| {
+        ConcentratedPoolUpdateParams::ScheduleParams(ScheduleParams {
+            params: UpdatePoolParams {
+                mid_fee: None,
+                out_fee: None,
+                fee_gamma: None,
+                repeg_profit_threshold: None,
+                min_price_scale_delta: None,
+                ma_half_time: None,
+            },
+            price_scale: Some(f64_to_dec(price_scale)),
+            future_time,
+        })
+    };
+
+    let future_time = helper.app.block_info().time.seconds() + 100_000;
+    let err = helper
+        .update_config(&owner, &schedule(1.6, future_time))
+        .unwrap_err();
+    assert_eq!(
+        ContractError::MaxParamsChangeAssertion("price_scale".to_string(), MAX_PARAMS_CHANGE),
+        err.downcast().unwrap()
+    );
+
+    helper
+        .update_config(&owner, &schedule(1.2, future_time))
+        .unwrap();
+
+    let config = helper.query_pool_config().unwrap();
+    let schedule_resp = config.params_schedule.unwrap();
+    assert_eq!(schedule_resp.initial_price_scale, Some(Decimal::one()));
+    assert_eq!(schedule_resp.future_price_scale, Some(f64_to_dec(1.2)));
+
+    helper.app.next_block(50_000);
+
+    // Price scale is moved on swaps only
+    let config = helper.query_pool_config().unwrap();
+    assert_eq!(config.price_scale, Decimal::one());
+
+    let user = Addr::unchecked("user");
+    let offer_asset = helper.assets[&test_coins[1]].with_balance(100_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, None).unwrap();
+
+    let config = helper.query_pool_config().unwrap();
+    assert_eq!(dec_to_f64(config.price_scale), 1.1);
+
+    helper.app.next_block(60_000);
+
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, None).unwrap();
+
+    let config = helper.query_pool_config().unwrap();
+    assert_eq!(dec_to_f64(config.price_scale), 1.2);
+    assert_eq!(config.params_schedule, None);
+
+    // Regular repegging is resumed and the pool is still operational
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(100_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, None).unwrap();
+}
+
+#[test]
+fn check_force_repeg() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::cw20("USDC")];
+
+    let params = ConcentratedPoolParams {
+        amp: f64_to_dec(40f64),
+        gamma: f64_to_dec(0.000145),
+        mid_fee: f64_to_dec(0.0026),
+        out_fee: f64_to_dec(0.0045),
+        fee_gamma: f64_to_dec(0.00023),
+        repeg_profit_threshold: f64_to_dec(0.01),
+        min_price_scale_delta: f64_to_dec(0.000146),
+        price_scale: Decimal::one(),
+        ma_half_time: 600,
+        track_asset_balances: None,
+    };
+    let mut helper = Helper::new(&owner, test_coins.clone(), params).unwrap();
+
+    let force_repeg = |target_price: f64, max_loss: f64| ConcentratedPoolUpdateParams::ForceRepeg {
+        target_price: f64_to_dec(target_price),
+        max_loss: f64_to_dec(max_loss),
+    };
+
+    let random_user = Addr::unchecked("random");
+    let err = helper
+        .update_config(&random_user, &force_repeg(1.05, 0.01))
+        .unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+    let err = helper
+        .update_config(&owner, &force_repeg(1.05, 0.01))
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Generic error: One of the pools is empty"
+    );
+
+    let err = helper
+        .update_config(&owner, &force_repeg(0.0, 0.01))
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Generic error: Target price can not be zero"
+    );
+
+    let err = helper
+        .update_config(&owner, &force_repeg(1.05, 0.0))
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Generic error: Max loss can not be zero"
+    );
+
+    let err = helper
+        .update_config(&owner, &force_repeg(1.05, 0.5))
+        .unwrap_err();
+    assert_eq!(
+        ContractError::IncorrectPoolParam(
+            "max_loss".to_string(),
+            "0".to_string(),
+            MAX_FORCE_REPEG_LOSS.to_string()
+        ),
+        err.downcast().unwrap()
+    );
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    // Market moves away while high repeg_profit_threshold blocks regular repeg
+    let user = Addr::unchecked("user");
+    let offer_asset = helper.assets[&test_coins[1]].with_balance(10_000_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, None).unwrap();
+    helper.app.next_block(1000);
+
+    let config = helper.query_pool_config().unwrap();
+    assert_eq!(config.price_scale, Decimal::one());
+
+    let target_price = 1.05;
+    helper
+        .update_config(&owner, &force_repeg(target_price, 0.05))
+        .unwrap();
+
+    let price_scale = dec_to_f64(helper.query_pool_config().unwrap().price_scale);
+    assert!(
+        price_scale > 1.0 && price_scale <= target_price,
+        "price_scale {price_scale} must be moved toward {target_price}"
+    );
+
+    // Pool is still operational
+    let offer_asset = helper.assets[&test_coins[1]].with_balance(100_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, None).unwrap();
+}
+
 #[test]
 fn check_prices() {
     let owner = Addr::unchecked("owner");
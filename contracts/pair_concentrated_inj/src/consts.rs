@@ -28,6 +28,12 @@ pub const MAX_ITER: usize = 64;
 pub const TWAP_PRECISION_DEC: Decimal256 = Decimal256::raw((1e6 * 1e18) as u128);
 /// Circular buffer size which stores observations
 pub const OBSERVATIONS_SIZE: u32 = 3000;
+/// Circular buffer size which stores orderbook reconciliation history
+pub const OB_HISTORY_SIZE: u32 = 1000;
+/// Default number of orderbook history records returned by query
+pub const OB_HISTORY_DEFAULT_LIMIT: u32 = 10;
+/// Maximum number of orderbook history records returned by query
+pub const OB_HISTORY_MAX_LIMIT: u32 = 100;
 
 /// ## Validation constants
 /// 0.001
@@ -8,10 +8,10 @@ use cosmwasm_std::{
 use cw2::set_contract_version;
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
 use cw_utils::parse_instantiate_response_data;
-use injective_cosmwasm::{InjectiveMsgWrapper, InjectiveQuerier, InjectiveQueryWrapper};
+use injective_cosmwasm::{InjectiveMsgWrapper, InjectiveQueryWrapper};
 use itertools::Itertools;
 
-use crate::consts::OBSERVATIONS_SIZE;
+use crate::consts::{OBSERVATIONS_SIZE, OB_HISTORY_SIZE};
 use astroport::asset::{
     addr_opt_validate, format_lp_token_name, Asset, AssetInfo, AssetInfoExt, CoinsExt,
     Decimal256Ext, PairInfo, MINIMUM_LIQUIDITY_AMOUNT,
@@ -22,7 +22,7 @@ use astroport::factory::PairType;
 use astroport::pair::{Cw20HookMsg, InstantiateMsg};
 use astroport::pair_concentrated::UpdatePoolParams;
 use astroport::pair_concentrated_inj::{
-    ConcentratedInjObParams, ConcentratedObPoolUpdateParams, ExecuteMsg,
+    ConcentratedInjObParams, ConcentratedObPoolUpdateParams, ExecuteMsg, UpdateMaxLiquidityShare,
 };
 use astroport::querier::{query_factory_config, query_fee_info, query_supply};
 use astroport::token::InstantiateMsg as TokenInstantiateMsg;
@@ -30,10 +30,10 @@ use astroport_circular_buffer::BufferManager;
 
 use crate::error::ContractError;
 use crate::math::{calc_d, get_xcp};
-use crate::orderbook::state::OrderbookState;
-use crate::orderbook::utils::{
-    get_subaccount_balances, is_contract_active, leave_orderbook, process_cumulative_trade,
-};
+use crate::orderbook::exchange::ExchangeModule;
+use crate::orderbook::injective::InjectiveExchange;
+use crate::orderbook::state::{record_reconciliation, OrderbookState, OB_HISTORY};
+use crate::orderbook::utils::{get_subaccount_balances, leave_orderbook, process_cumulative_trade};
 use crate::state::{
     store_precisions, AmpGamma, Config, PoolParams, PoolState, Precisions, PriceState, CONFIG,
     OBSERVATIONS, OWNERSHIP_PROPOSAL,
@@ -86,16 +86,15 @@ pub fn instantiate(
     store_precisions(deps.branch(), &msg.asset_infos, &factory_addr)?;
 
     let ob_state = OrderbookState::new(
-        deps.querier,
+        &InjectiveExchange::new(&deps.querier),
         &env,
-        &orderbook_params.orderbook_config.market_id,
-        orderbook_params.orderbook_config.orders_number,
-        orderbook_params.orderbook_config.min_trades_to_avg,
+        &orderbook_params.orderbook_config,
         &msg.asset_infos,
     )?;
     ob_state.save(deps.storage)?;
 
     BufferManager::init(deps.storage, OBSERVATIONS, OBSERVATIONS_SIZE)?;
+    BufferManager::init(deps.storage, OB_HISTORY, OB_HISTORY_SIZE)?;
 
     let mut pool_params = PoolParams::default();
     pool_params.update_params(UpdatePoolParams {
@@ -397,6 +396,7 @@ where
     let precisions = Precisions::new(deps.storage)?;
     let mut pools = query_pools(
         deps.querier,
+        &InjectiveExchange::new(&deps.querier),
         &env.contract.address,
         &config,
         &ob_state,
@@ -438,14 +438,14 @@ where
     let mut messages = vec![];
     let subacc_balances = get_subaccount_balances(
         &config.pair_info.asset_infos,
-        &InjectiveQuerier::new(&deps.querier),
-        &ob_state.subaccount,
+        &InjectiveExchange::new(&deps.querier),
+        &ob_state.subaccount_as::<InjectiveExchange>()?,
     )?;
     // In case begin blocker logic wasn't executed, we need to update price and send maker fees
     if ob_state.last_balances != subacc_balances {
         let base_asset_precision = precisions.get_precision(&config.pair_info.asset_infos[0])?;
         let quote_asset_precision = precisions.get_precision(&config.pair_info.asset_infos[1])?;
-        let maker_fee_message = process_cumulative_trade(
+        let (maker_fee_message, maker_fee) = process_cumulative_trade(
             deps.querier,
             &env,
             &ob_state,
@@ -456,6 +456,13 @@ where
             quote_asset_precision,
         )
         .map_err(StdError::from)?;
+        record_reconciliation(
+            deps.storage,
+            &env,
+            &ob_state.last_balances,
+            &subacc_balances,
+            maker_fee,
+        )?;
 
         ob_state.last_balances = subacc_balances;
 
@@ -599,6 +606,7 @@ fn withdraw_liquidity(
     let ob_state = OrderbookState::load(deps.storage)?;
     let pools = query_pools(
         deps.querier,
+        &InjectiveExchange::new(&deps.querier),
         &config.pair_info.contract_addr,
         &config,
         &ob_state,
@@ -627,13 +635,14 @@ fn withdraw_liquidity(
     if refund_assets[0].amount > contract_balances[0].amount
         || refund_assets[1].amount > contract_balances[1].amount
     {
-        let querier = InjectiveQuerier::new(&deps.querier);
+        let exchange = InjectiveExchange::new(&deps.querier);
         let orderbook_balances = get_subaccount_balances(
             &config.pair_info.asset_infos,
-            &querier,
-            &ob_state.subaccount,
+            &exchange,
+            &ob_state.subaccount_as::<InjectiveExchange>()?,
         )?;
-        response = leave_orderbook(&ob_state, orderbook_balances, &env).map_err(StdError::from)?;
+        response = leave_orderbook(&exchange, &ob_state, orderbook_balances, &env)
+            .map_err(StdError::from)?;
     }
 
     // decrease XCP
@@ -719,6 +728,7 @@ where
 
     let mut pools = query_pools(
         deps.querier,
+        &InjectiveExchange::new(&deps.querier),
         &env.contract.address,
         &config,
         &ob_state,
@@ -754,14 +764,14 @@ where
 
     let subacc_balances = get_subaccount_balances(
         &config.pair_info.asset_infos,
-        &InjectiveQuerier::new(&deps.querier),
-        &ob_state.subaccount,
+        &InjectiveExchange::new(&deps.querier),
+        &ob_state.subaccount_as::<InjectiveExchange>()?,
     )?;
     // In case begin blocker logic wasn't executed, we need to update price and send maker fees
     if ob_state.last_balances != subacc_balances {
         let base_asset_precision = precisions.get_precision(&config.pair_info.asset_infos[0])?;
         let quote_asset_precision = precisions.get_precision(&config.pair_info.asset_infos[1])?;
-        let maker_fee_message = process_cumulative_trade(
+        let (maker_fee_message, maker_fee) = process_cumulative_trade(
             deps.querier,
             &env,
             &ob_state,
@@ -772,6 +782,13 @@ where
             quote_asset_precision,
         )
         .map_err(StdError::from)?;
+        record_reconciliation(
+            deps.storage,
+            &env,
+            &ob_state.last_balances,
+            &subacc_balances,
+            maker_fee,
+        )?;
 
         ob_state.last_balances = subacc_balances;
 
@@ -889,10 +906,28 @@ fn update_config<T>(
             config.pool_state.stop_promotion(&env);
             "stop_changing_amp_gamma"
         }
-        ConcentratedObPoolUpdateParams::UpdateOrderbookParams { orders_number } => {
+        ConcentratedObPoolUpdateParams::UpdateOrderbookParams {
+            orders_number,
+            liquidity_distribution,
+            max_liquidity_share,
+        } => {
             let mut ob_config = OrderbookState::load(deps.storage)?;
-            ob_config.orders_number = orders_number;
-            ob_config.save(deps.storage)?;
+            if let Some(orders_number) = orders_number {
+                ob_config.orders_number = orders_number;
+            }
+            if let Some(liquidity_distribution) = liquidity_distribution {
+                ob_config.liquidity_distribution = liquidity_distribution;
+            }
+            match max_liquidity_share {
+                Some(UpdateMaxLiquidityShare::Set(share)) => {
+                    ob_config.max_liquidity_share = Some(share)
+                }
+                Some(UpdateMaxLiquidityShare::Remove {}) => ob_config.max_liquidity_share = None,
+                None => {}
+            }
+            ob_config.validate_params()?;
+            // Orders must be rebuilt according to the new params
+            ob_config.reconcile(deps.storage)?;
             "update_orderbook_params"
         }
     };
@@ -907,10 +942,10 @@ pub fn orderbook_emergency_withdraw(
     deps: DepsMut<InjectiveQueryWrapper>,
     env: Env,
 ) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
-    let querier = InjectiveQuerier::new(&deps.querier);
+    let exchange = InjectiveExchange::new(&deps.querier);
 
     // Ask chain whether the pair contract is still active in begin blocker
-    if is_contract_active(&querier, &env.contract.address)? {
+    if exchange.is_contract_active(&env.contract.address)? {
         return Err(StdError::generic_err(
             "Failed to withdraw liquidity from orderbook: contract is active",
         )
@@ -918,10 +953,14 @@ pub fn orderbook_emergency_withdraw(
     }
 
     let ob_state = OrderbookState::load(deps.storage)?;
-    let balances = get_subaccount_balances(&ob_state.asset_infos, &querier, &ob_state.subaccount)?;
+    let balances = get_subaccount_balances(
+        &ob_state.asset_infos,
+        &exchange,
+        &ob_state.subaccount_as::<InjectiveExchange>()?,
+    )?;
 
     let mut response = if !(balances[0].amount + balances[1].amount).is_zero() {
-        leave_orderbook(&ob_state, balances.clone(), &env).map_err(StdError::from)?
+        leave_orderbook(&exchange, &ob_state, balances.clone(), &env).map_err(StdError::from)?
     } else {
         Response::new()
     };
@@ -931,6 +970,7 @@ pub fn orderbook_emergency_withdraw(
         let precisions = Precisions::new(deps.storage)?;
         let mut pools = query_pools(
             deps.querier,
+            &exchange,
             &env.contract.address,
             &config,
             &ob_state,
@@ -942,7 +982,7 @@ pub fn orderbook_emergency_withdraw(
         .collect_vec();
         let base_asset_precision = precisions.get_precision(&config.pair_info.asset_infos[0])?;
         let quote_asset_precision = precisions.get_precision(&config.pair_info.asset_infos[1])?;
-        let maker_fee_message = process_cumulative_trade(
+        let (maker_fee_message, maker_fee) = process_cumulative_trade(
             deps.querier,
             &env,
             &ob_state,
@@ -954,6 +994,13 @@ pub fn orderbook_emergency_withdraw(
         )
         .map_err(StdError::from)?;
         CONFIG.save(deps.storage, &config)?;
+        record_reconciliation(
+            deps.storage,
+            &env,
+            &ob_state.last_balances,
+            &balances,
+            maker_fee,
+        )?;
 
         response = response.add_messages(maker_fee_message);
     }
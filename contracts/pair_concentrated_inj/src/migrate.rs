@@ -5,9 +5,10 @@ use cw2::{set_contract_version, CONTRACT};
 use cw_storage_plus::Item;
 use injective_cosmwasm::{InjectiveMsgWrapper, InjectiveQueryWrapper};
 
-use crate::consts::OBSERVATIONS_SIZE;
+use crate::consts::{OBSERVATIONS_SIZE, OB_HISTORY_SIZE};
 use crate::contract::{CONTRACT_NAME, CONTRACT_VERSION};
-use crate::orderbook::state::OrderbookState;
+use crate::orderbook::injective::InjectiveExchange;
+use crate::orderbook::state::{OrderbookState, OB_HISTORY};
 use astroport::pair_concentrated_inj::MigrateMsg;
 use astroport_circular_buffer::BufferManager;
 use astroport_pair_concentrated::state::Config as CLConfig;
@@ -37,14 +38,13 @@ pub fn migrate(
                 )));
             }
             BufferManager::init(deps.storage, OBSERVATIONS, OBSERVATIONS_SIZE)?;
+            BufferManager::init(deps.storage, OB_HISTORY, OB_HISTORY_SIZE)?;
 
             let config: CLConfig = Item::new("config").load(deps.storage)?;
             let ob_state = OrderbookState::new(
-                deps.querier,
+                &InjectiveExchange::new(&deps.querier),
                 &env,
-                &params.market_id,
-                params.orders_number,
-                params.min_trades_to_avg,
+                &params,
                 &config.pair_info.asset_infos,
             )?;
             CONFIG.save(deps.storage, &config.into())?;
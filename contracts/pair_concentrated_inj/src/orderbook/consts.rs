@@ -1,4 +1,5 @@
 use crate::consts::OBSERVATIONS_SIZE;
+use cosmwasm_std::Decimal;
 use std::ops::RangeInclusive;
 
 /// Validation limits for order size.
@@ -8,6 +9,10 @@ pub const ORDER_SIZE_LIMITS: RangeInclusive<u8> = 1..=30;
 /// why we need such exotic limits.
 pub const MIN_TRADES_TO_AVG_LIMITS: RangeInclusive<u32> = 1..=(OBSERVATIONS_SIZE - 1);
 
-/// Starting from v1.10 injective uses default subaccount (nonce = 0) to automatically transfer
-/// funds from bank module when creating an order. We need to avoid it.
-pub const SUBACC_NONCE: u32 = 1;
+/// Maximum step of [`astroport::pair_concentrated_inj::LiquidityDistribution::Linear`] distribution (10.0).
+pub const LINEAR_STEP_MAX: Decimal = Decimal::raw(1e19 as u128);
+
+/// Validation limits for ratio of [`astroport::pair_concentrated_inj::LiquidityDistribution::Geometric`]
+/// distribution (0.1 - 10.0).
+pub const GEOMETRIC_RATIO_LIMITS: RangeInclusive<Decimal> =
+    Decimal::raw(1e17 as u128)..=Decimal::raw(1e19 as u128);
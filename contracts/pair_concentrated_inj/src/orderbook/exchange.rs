@@ -0,0 +1,218 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Coin, CosmosMsg, CustomMsg, Decimal256, StdResult, Uint128};
+
+use crate::orderbook::error::OrderbookError;
+use crate::orderbook::state::OrderbookState;
+
+/// Exchange-agnostic representation of a limit order.
+/// Price and quantity are already adjusted to the market tick sizes and asset precisions
+/// thus exchange module implementations only need to convert them into their native format.
+#[cw_serde]
+pub struct Order {
+    pub price: Decimal256,
+    pub quantity: Decimal256,
+    pub is_buy: bool,
+}
+
+/// Exchange-specific identifier (market or subaccount). The orderbook state keeps identifiers
+/// as plain strings thus every exchange module must be able to convert them back and forth.
+pub trait ExchangeId: Sized {
+    /// Parses and validates the identifier format.
+    fn from_id(id: &str) -> StdResult<Self>;
+
+    /// Returns string representation of the identifier.
+    fn as_id(&self) -> &str;
+}
+
+/// This trait describes the minimal interface of a chain exchange module which is required
+/// by the orderbook integration. All orderbook logic (orders generation, cumulative trade processing
+/// and reconciliation) works only through this trait.
+pub trait ExchangeModule {
+    /// Custom message type used by the chain to interact with the exchange module.
+    type Msg: CustomMsg;
+    /// Market identifier used by the exchange module.
+    type MarketId: ExchangeId;
+    /// Subaccount identifier used by the exchange module.
+    type SubaccountId: ExchangeId;
+
+    /// Returns the subaccount which the contract uses to place orders.
+    fn subaccount(&self, contract_addr: &Addr) -> Self::SubaccountId;
+
+    /// Ensures the market exists and is a spot market.
+    fn validate_market(&self, market_id: &Self::MarketId) -> StdResult<()>;
+
+    /// Returns (min_price_tick_size, min_quantity_tick_size) of the market.
+    fn query_ticks(
+        &self,
+        market_id: &Self::MarketId,
+    ) -> Result<(Decimal256, Decimal256), OrderbookError>;
+
+    /// Returns total subaccount deposit for the specified denom.
+    fn query_deposit(&self, subaccount: &Self::SubaccountId, denom: &str) -> StdResult<Uint128>;
+
+    /// Whether the contract is still registered to be called on every begin blocker.
+    fn is_contract_active(&self, contract_addr: &Addr) -> StdResult<bool>;
+
+    /// Creates a message which places the batch of post-only orders.
+    fn create_orders_msg(
+        &self,
+        sender: &Addr,
+        ob_state: &OrderbookState,
+        orders: Vec<Order>,
+        fee_receiver: &Addr,
+    ) -> StdResult<CosmosMsg<Self::Msg>>;
+
+    /// Creates a message which cancels all contract orders on the market.
+    fn cancel_all_orders_msg(
+        &self,
+        sender: &Addr,
+        ob_state: &OrderbookState,
+    ) -> CosmosMsg<Self::Msg>;
+
+    /// Creates a message which moves funds from the contract balance to the subaccount.
+    fn deposit_msg(
+        &self,
+        sender: &Addr,
+        subaccount: &Self::SubaccountId,
+        coin: Coin,
+    ) -> CosmosMsg<Self::Msg>;
+
+    /// Creates a message which moves funds from the subaccount back to the contract balance.
+    fn withdraw_msg(
+        &self,
+        sender: &Addr,
+        subaccount: &Self::SubaccountId,
+        coin: Coin,
+    ) -> CosmosMsg<Self::Msg>;
+}
+
+#[cfg(test)]
+pub(crate) mod mock {
+    use std::collections::HashMap;
+
+    use cosmwasm_std::StdError;
+
+    use super::*;
+
+    #[cw_serde]
+    pub enum MockExchangeMsg {
+        CreateOrders {
+            market_id: String,
+            subaccount: String,
+            orders: Vec<Order>,
+        },
+        CancelAll {
+            market_id: String,
+        },
+        Deposit {
+            subaccount: String,
+            coin: Coin,
+        },
+        Withdraw {
+            subaccount: String,
+            coin: Coin,
+        },
+    }
+
+    impl CustomMsg for MockExchangeMsg {}
+
+    impl ExchangeId for String {
+        fn from_id(id: &str) -> StdResult<Self> {
+            Ok(id.to_string())
+        }
+
+        fn as_id(&self) -> &str {
+            self
+        }
+    }
+
+    /// In-memory exchange module which is used to test orderbook logic without Injective chain.
+    #[derive(Default)]
+    pub struct MockExchange {
+        pub deposits: HashMap<String, Uint128>,
+        pub active: bool,
+    }
+
+    impl ExchangeModule for MockExchange {
+        type Msg = MockExchangeMsg;
+        type MarketId = String;
+        type SubaccountId = String;
+
+        fn subaccount(&self, contract_addr: &Addr) -> String {
+            format!("{contract_addr}-1")
+        }
+
+        fn validate_market(&self, market_id: &String) -> StdResult<()> {
+            if market_id.is_empty() {
+                return Err(StdError::generic_err("Empty market id"));
+            }
+            Ok(())
+        }
+
+        fn query_ticks(
+            &self,
+            _market_id: &String,
+        ) -> Result<(Decimal256, Decimal256), OrderbookError> {
+            Ok((
+                Decimal256::from_ratio(1u8, 1000u16),
+                Decimal256::from_ratio(1u8, 1000u16),
+            ))
+        }
+
+        fn query_deposit(&self, _subaccount: &String, denom: &str) -> StdResult<Uint128> {
+            Ok(self.deposits.get(denom).copied().unwrap_or_default())
+        }
+
+        fn is_contract_active(&self, _contract_addr: &Addr) -> StdResult<bool> {
+            Ok(self.active)
+        }
+
+        fn create_orders_msg(
+            &self,
+            _sender: &Addr,
+            ob_state: &OrderbookState,
+            orders: Vec<Order>,
+            _fee_receiver: &Addr,
+        ) -> StdResult<CosmosMsg<Self::Msg>> {
+            Ok(CosmosMsg::Custom(MockExchangeMsg::CreateOrders {
+                market_id: ob_state.market_id.clone(),
+                subaccount: ob_state.subaccount.clone(),
+                orders,
+            }))
+        }
+
+        fn cancel_all_orders_msg(
+            &self,
+            _sender: &Addr,
+            ob_state: &OrderbookState,
+        ) -> CosmosMsg<Self::Msg> {
+            CosmosMsg::Custom(MockExchangeMsg::CancelAll {
+                market_id: ob_state.market_id.clone(),
+            })
+        }
+
+        fn deposit_msg(
+            &self,
+            _sender: &Addr,
+            subaccount: &String,
+            coin: Coin,
+        ) -> CosmosMsg<Self::Msg> {
+            CosmosMsg::Custom(MockExchangeMsg::Deposit {
+                subaccount: subaccount.clone(),
+                coin,
+            })
+        }
+
+        fn withdraw_msg(
+            &self,
+            _sender: &Addr,
+            subaccount: &String,
+            coin: Coin,
+        ) -> CosmosMsg<Self::Msg> {
+            CosmosMsg::Custom(MockExchangeMsg::Withdraw {
+                subaccount: subaccount.clone(),
+                coin,
+            })
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use cosmwasm_std::{
+    entry_point, Addr, Coin, CosmosMsg, Decimal256, DepsMut, Env, QuerierWrapper, Response,
+    StdResult, Uint128,
+};
+use injective_cosmwasm::{
+    checked_address_to_subaccount_id, create_batch_update_orders_msg, create_deposit_msg,
+    create_withdraw_msg, InjectiveMsgWrapper, InjectiveQuerier, InjectiveQueryWrapper, MarketId,
+    MarketType, OrderType, SpotOrder, SubaccountId,
+};
+
+use astroport::cosmwasm_ext::ConvertInto;
+
+use crate::orderbook::error::OrderbookError;
+use crate::orderbook::exchange::{ExchangeId, ExchangeModule, Order};
+use crate::orderbook::msg::SudoMsg;
+use crate::orderbook::state::OrderbookState;
+use crate::orderbook::sudo::{begin_blocker, deactivate_orderbook};
+
+/// Starting from v1.10 injective uses default subaccount (nonce = 0) to automatically transfer
+/// funds from bank module when creating an order. We need to avoid it.
+pub const SUBACC_NONCE: u32 = 1;
+
+/// A thin wrapper to get subaccount we are working with.
+#[inline]
+pub fn get_subaccount(addr: &Addr) -> SubaccountId {
+    checked_address_to_subaccount_id(addr, SUBACC_NONCE)
+}
+
+/// Injective chain calls this entry point on every begin blocker and when the contract is deactivated.
+/// The orderbook logic is executed with the Injective exchange module backend.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn sudo(
+    deps: DepsMut<InjectiveQueryWrapper>,
+    env: Env,
+    msg: SudoMsg,
+) -> Result<Response<InjectiveMsgWrapper>, OrderbookError> {
+    let exchange = InjectiveExchange::new(&deps.querier);
+
+    match msg {
+        SudoMsg::BeginBlocker {} => begin_blocker(deps.storage, deps.querier, &exchange, env),
+        SudoMsg::Deactivate {} => deactivate_orderbook(deps.api, deps.storage, &exchange, env),
+    }
+}
+
+/// Injective exchange module backend for the orderbook integration.
+pub struct InjectiveExchange<'a> {
+    querier: InjectiveQuerier<'a>,
+}
+
+impl<'a> InjectiveExchange<'a> {
+    pub fn new(querier: &'a QuerierWrapper<'a, InjectiveQueryWrapper>) -> Self {
+        Self {
+            querier: InjectiveQuerier::new(querier),
+        }
+    }
+}
+
+impl ExchangeId for MarketId {
+    fn from_id(id: &str) -> StdResult<Self> {
+        MarketId::new(id)
+    }
+
+    fn as_id(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl ExchangeId for SubaccountId {
+    fn from_id(id: &str) -> StdResult<Self> {
+        SubaccountId::new(id)
+    }
+
+    fn as_id(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl ExchangeModule for InjectiveExchange<'_> {
+    type Msg = InjectiveMsgWrapper;
+    type MarketId = MarketId;
+    type SubaccountId = SubaccountId;
+
+    fn subaccount(&self, contract_addr: &Addr) -> SubaccountId {
+        get_subaccount(contract_addr)
+    }
+
+    fn validate_market(&self, market_id: &MarketId) -> StdResult<()> {
+        market_id
+            .clone()
+            .validate(&self.querier, MarketType::Spot)
+            .map(|_| ())
+    }
+
+    /// Querying exchange module and converting tick sizes into [`Decimal256`].
+    fn query_ticks(
+        &self,
+        market_id: &MarketId,
+    ) -> Result<(Decimal256, Decimal256), OrderbookError> {
+        let market_info = self
+            .querier
+            .query_spot_market(market_id)?
+            .market
+            .ok_or_else(|| OrderbookError::MarketNotFound(market_id.clone().into()))?;
+
+        Ok((
+            market_info.min_price_tick_size.conv()?,
+            market_info.min_quantity_tick_size.conv()?,
+        ))
+    }
+
+    fn query_deposit(&self, subaccount: &SubaccountId, denom: &str) -> StdResult<Uint128> {
+        let resp = self.querier.query_subaccount_deposit(subaccount, denom)?;
+        Ok(resp.deposits.total_balance.into())
+    }
+
+    /// Ask chain module whether contract is registered for begin blocker or not.
+    fn is_contract_active(&self, contract_addr: &Addr) -> StdResult<bool> {
+        let reg_info = self
+            .querier
+            .query_contract_registration_info(contract_addr)?;
+        let active = reg_info
+            .contract
+            .map(|reg| reg.is_executable)
+            .unwrap_or(false);
+
+        Ok(active)
+    }
+
+    fn create_orders_msg(
+        &self,
+        sender: &Addr,
+        ob_state: &OrderbookState,
+        orders: Vec<Order>,
+        fee_receiver: &Addr,
+    ) -> StdResult<CosmosMsg<Self::Msg>> {
+        let market_id = ob_state.market_id_as::<Self>()?;
+        let subaccount = ob_state.subaccount_as::<Self>()?;
+
+        let spot_orders = orders
+            .into_iter()
+            .map(|order| {
+                Ok(SpotOrder::new(
+                    order.price.conv()?,
+                    order.quantity.conv()?,
+                    if order.is_buy {
+                        OrderType::BuyPo
+                    } else {
+                        OrderType::SellPo
+                    },
+                    &market_id,
+                    subaccount.clone(),
+                    Some(fee_receiver.clone()),
+                ))
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(create_batch_update_orders_msg(
+            sender.clone(),
+            None,
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+            spot_orders,
+            vec![],
+        ))
+    }
+
+    fn cancel_all_orders_msg(
+        &self,
+        sender: &Addr,
+        ob_state: &OrderbookState,
+    ) -> CosmosMsg<Self::Msg> {
+        create_batch_update_orders_msg(
+            sender.clone(),
+            // Identifiers were validated when the orderbook state was created
+            Some(SubaccountId::unchecked(&ob_state.subaccount)),
+            vec![MarketId::unchecked(&ob_state.market_id)],
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+        )
+    }
+
+    fn deposit_msg(
+        &self,
+        sender: &Addr,
+        subaccount: &SubaccountId,
+        coin: Coin,
+    ) -> CosmosMsg<Self::Msg> {
+        create_deposit_msg(sender.clone(), subaccount.clone(), coin)
+    }
+
+    fn withdraw_msg(
+        &self,
+        sender: &Addr,
+        subaccount: &SubaccountId,
+        coin: Coin,
+    ) -> CosmosMsg<Self::Msg> {
+        create_withdraw_msg(sender.clone(), subaccount.clone(), coin)
+    }
+}
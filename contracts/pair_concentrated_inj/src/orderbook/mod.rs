@@ -1,5 +1,7 @@
 pub mod consts;
 pub mod error;
+pub mod exchange;
+pub mod injective;
 pub mod msg;
 pub mod state;
 pub mod sudo;
@@ -1,17 +1,20 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Decimal256, Env, QuerierWrapper, StdError, StdResult, Storage};
+use cosmwasm_std::{Decimal, Decimal256, Env, StdError, StdResult, Storage};
 use cw_storage_plus::Item;
-use injective_cosmwasm::{
-    InjectiveQuerier, InjectiveQueryWrapper, MarketId, MarketType, SubaccountId,
-};
 
 use astroport::asset::{Asset, AssetInfo, AssetInfoExt};
-use astroport::cosmwasm_ext::ConvertInto;
-use astroport::pair_concentrated_inj::OrderbookStateResponse;
+use astroport::pair_concentrated_inj::{
+    LiquidityDistribution, OrderbookConfig, OrderbookReconciliation, OrderbookStateResponse,
+};
+use astroport_circular_buffer::error::BufferResult;
+use astroport_circular_buffer::{BufferManager, CircularBuffer};
 
-use crate::orderbook::consts::{MIN_TRADES_TO_AVG_LIMITS, ORDER_SIZE_LIMITS};
-use crate::orderbook::error::OrderbookError;
-use crate::orderbook::utils::{calc_market_ids, get_subaccount};
+use crate::consts::{OB_HISTORY_DEFAULT_LIMIT, OB_HISTORY_MAX_LIMIT, OB_HISTORY_SIZE};
+use crate::orderbook::consts::{
+    GEOMETRIC_RATIO_LIMITS, LINEAR_STEP_MAX, MIN_TRADES_TO_AVG_LIMITS, ORDER_SIZE_LIMITS,
+};
+use crate::orderbook::exchange::{ExchangeId, ExchangeModule};
+use crate::orderbook::utils::{calc_market_ids, cumulative_trade};
 
 macro_rules! validate_param {
     ($name:ident, $val:expr, $min:expr, $max:expr) => {
@@ -30,9 +33,9 @@ macro_rules! validate_param {
 #[cw_serde]
 pub struct OrderbookState {
     /// Market which is being used to deploy liquidity to
-    pub market_id: MarketId,
+    pub market_id: String,
     /// Subaccount used for the orderbook
-    pub subaccount: SubaccountId,
+    pub subaccount: String,
     /// Stores asset infos. We duplicate it in OB state to decrease noop gas usage on begin blocker.
     pub asset_infos: Vec<AssetInfo>,
     /// Minimum allowed price tick size in the orderbook
@@ -52,32 +55,96 @@ pub struct OrderbookState {
     pub min_trades_to_avg: u32,
     /// Whether the pool is ready to integrate with the orderbook (MIN_TRADES_TO_AVG is reached)
     pub ready: bool,
+    /// Shape of liquidity distribution over orderbook levels
+    #[serde(default)]
+    pub liquidity_distribution: LiquidityDistribution,
+    /// Maximum share of pool liquidity which can be placed in the orderbook on each side
+    pub max_liquidity_share: Option<Decimal>,
 }
 
 const OB_CONFIG: Item<OrderbookState> = Item::new("orderbook_config");
 
+/// Stores the history of orderbook reconciliations
+pub const OB_HISTORY: CircularBuffer<OrderbookReconciliation> =
+    CircularBuffer::new("ob_history_state", "ob_history_buffer");
+
+/// Saves orderbook reconciliation record. Initializes the history buffer if needed
+/// (e.g. the pair was created before the history was introduced).
+pub fn save_reconciliation(
+    storage: &mut dyn Storage,
+    record: &OrderbookReconciliation,
+) -> BufferResult<()> {
+    if OB_HISTORY.state().may_load(storage)?.is_none() {
+        BufferManager::init(storage, OB_HISTORY, OB_HISTORY_SIZE)?;
+    }
+
+    BufferManager::new(storage, OB_HISTORY)?.instant_push(storage, record)
+}
+
+/// Records subaccount balances change since the last reconciliation along with the maker fee.
+/// Used by the begin blocker as well as by endpoints which process orderbook trades
+/// when the begin blocker wasn't executed.
+pub fn record_reconciliation(
+    storage: &mut dyn Storage,
+    env: &Env,
+    last_balances: &[Asset],
+    balances: &[Asset],
+    maker_fee: Option<Asset>,
+) -> BufferResult<()> {
+    save_reconciliation(
+        storage,
+        &OrderbookReconciliation {
+            timestamp: env.block.time.seconds(),
+            trade: cumulative_trade(last_balances, balances),
+            last_balances: last_balances.to_vec(),
+            balances: balances.to_vec(),
+            maker_fee,
+        },
+    )
+}
+
+/// Reads the latest orderbook reconciliations starting from the most recent one.
+pub fn read_reconciliations(
+    storage: &dyn Storage,
+    limit: Option<u32>,
+) -> BufferResult<Vec<OrderbookReconciliation>> {
+    if OB_HISTORY.state().may_load(storage)?.is_none() {
+        return Ok(vec![]);
+    }
+
+    let buffer = BufferManager::new(storage, OB_HISTORY)?;
+    let limit = limit
+        .unwrap_or(OB_HISTORY_DEFAULT_LIMIT)
+        .min(OB_HISTORY_MAX_LIMIT)
+        .min(buffer.capacity());
+
+    let mut records = vec![];
+    for i in 1..=limit {
+        let index = (buffer.capacity() + buffer.head() - i) % buffer.capacity();
+        match buffer.read_single(storage, index)? {
+            Some(record) => records.push(record),
+            None => break,
+        }
+    }
+
+    Ok(records)
+}
+
 impl OrderbookState {
-    pub fn new(
-        querier: QuerierWrapper<InjectiveQueryWrapper>,
+    pub fn new<E: ExchangeModule>(
+        exchange: &E,
         env: &Env,
-        market_id: &str,
-        orders_number: u8,
-        min_trades_to_avg: u32,
+        ob_config: &OrderbookConfig,
         asset_infos: &[AssetInfo],
     ) -> StdResult<Self> {
-        let market_id = MarketId::new(market_id)?;
-
-        Self::validate(
-            querier,
-            asset_infos,
-            &market_id,
-            orders_number,
-            min_trades_to_avg,
-        )?;
+        let market_id = E::MarketId::from_id(&ob_config.market_id)?;
 
         let mut state = Self {
-            market_id,
-            subaccount: get_subaccount(&env.contract.address),
+            market_id: market_id.as_id().to_string(),
+            subaccount: exchange
+                .subaccount(&env.contract.address)
+                .as_id()
+                .to_string(),
             asset_infos: asset_infos.to_vec(),
             min_price_tick_size: Default::default(),
             min_quantity_tick_size: Default::default(),
@@ -86,12 +153,16 @@ impl OrderbookState {
                 asset_infos[0].with_balance(0u8),
                 asset_infos[1].with_balance(0u8),
             ],
-            orders_number,
-            min_trades_to_avg,
+            orders_number: ob_config.orders_number,
+            min_trades_to_avg: ob_config.min_trades_to_avg,
             ready: false,
+            liquidity_distribution: ob_config.liquidity_distribution.clone().unwrap_or_default(),
+            max_liquidity_share: ob_config.max_liquidity_share,
         };
 
-        state.set_ticks(querier)?;
+        state.validate_params()?;
+        Self::validate(exchange, asset_infos, &market_id)?;
+        state.set_ticks(exchange)?;
 
         Ok(state)
     }
@@ -104,31 +175,15 @@ impl OrderbookState {
         OB_CONFIG.save(storage, self)
     }
 
-    /// Validates orderbook params
-    fn validate(
-        querier: QuerierWrapper<InjectiveQueryWrapper>,
+    /// Validates market id
+    fn validate<E: ExchangeModule>(
+        exchange: &E,
         asset_infos: &[AssetInfo],
-        market_id: &MarketId,
-        orders_number: u8,
-        min_trades_to_avg: u32,
+        market_id: &E::MarketId,
     ) -> StdResult<()> {
-        validate_param!(
-            orders_number,
-            orders_number,
-            *ORDER_SIZE_LIMITS.start(),
-            *ORDER_SIZE_LIMITS.end()
-        );
-
-        validate_param!(
-            min_trades_to_avg,
-            min_trades_to_avg,
-            *MIN_TRADES_TO_AVG_LIMITS.start(),
-            *MIN_TRADES_TO_AVG_LIMITS.end()
-        );
-
         let market_ids = calc_market_ids(asset_infos)?;
 
-        if market_id.as_str() == market_ids[1] {
+        if market_id.as_id() == market_ids[1] {
             // If we call this from instantiate context, we could just swap asset_infos to have correct order.
             // However, in that case we'll need to invert initial price scale which is bad UX.
             // We want to avoid implicit actions thus we prohibit pair creation for market id with wrong order.
@@ -136,35 +191,86 @@ impl OrderbookState {
                     "Pair asset infos have different order than market: {first}-{second} while market has {second}-{first}",
                     first = asset_infos[0], second = asset_infos[1]
                 )));
-        } else if market_id.as_str() != market_ids[0] {
+        } else if market_id.as_id() != market_ids[0] {
             return Err(StdError::generic_err(format!(
                 "Invalid market id. Must be: {}",
                 market_ids[0]
             )));
         }
 
-        market_id
-            .clone()
-            .validate(&InjectiveQuerier::new(&querier), MarketType::Spot)?;
+        exchange.validate_market(market_id)
+    }
+
+    /// Validates orderbook params
+    pub fn validate_params(&self) -> StdResult<()> {
+        validate_param!(
+            orders_number,
+            self.orders_number,
+            *ORDER_SIZE_LIMITS.start(),
+            *ORDER_SIZE_LIMITS.end()
+        );
+
+        validate_param!(
+            min_trades_to_avg,
+            self.min_trades_to_avg,
+            *MIN_TRADES_TO_AVG_LIMITS.start(),
+            *MIN_TRADES_TO_AVG_LIMITS.end()
+        );
+
+        match self.liquidity_distribution {
+            LiquidityDistribution::AvgTradeSize {} => {}
+            LiquidityDistribution::Linear { step } => {
+                validate_param!(step, step, Decimal::zero(), LINEAR_STEP_MAX);
+            }
+            LiquidityDistribution::Geometric { ratio } => {
+                validate_param!(
+                    ratio,
+                    ratio,
+                    *GEOMETRIC_RATIO_LIMITS.start(),
+                    *GEOMETRIC_RATIO_LIMITS.end()
+                );
+            }
+        }
+
+        if let Some(max_liquidity_share) = self.max_liquidity_share {
+            if max_liquidity_share.is_zero() {
+                return Err(StdError::generic_err(
+                    "Incorrect orderbook params: max_liquidity_share can not be zero",
+                ));
+            }
+            validate_param!(
+                max_liquidity_share,
+                max_liquidity_share,
+                Decimal::zero(),
+                Decimal::one()
+            );
+        }
 
         Ok(())
     }
 
     /// Querying exchange module, converting into [`Decimal256`] and caching tick sizes.
     /// Cashed values help to save gas on begin blocker iterations.
-    fn set_ticks(&mut self, querier: QuerierWrapper<InjectiveQueryWrapper>) -> StdResult<()> {
-        let querier = InjectiveQuerier::new(&querier);
-        let market_info = querier
-            .query_spot_market(&self.market_id)?
-            .market
-            .ok_or_else(|| OrderbookError::MarketNotFound(self.market_id.clone().into()))?;
+    fn set_ticks<E: ExchangeModule>(&mut self, exchange: &E) -> StdResult<()> {
+        let (min_price_tick_size, min_quantity_tick_size) =
+            exchange.query_ticks(&self.market_id_as::<E>()?)?;
 
-        self.min_price_tick_size = market_info.min_price_tick_size.conv()?;
-        self.min_quantity_tick_size = market_info.min_quantity_tick_size.conv()?;
+        self.min_price_tick_size = min_price_tick_size;
+        self.min_quantity_tick_size = min_quantity_tick_size;
 
         Ok(())
     }
 
+    /// Converts stored market id into the exchange module representation.
+    pub fn market_id_as<E: ExchangeModule>(&self) -> StdResult<E::MarketId> {
+        E::MarketId::from_id(&self.market_id)
+    }
+
+    /// Converts stored subaccount into the exchange module representation.
+    pub fn subaccount_as<E: ExchangeModule>(&self) -> StdResult<E::SubaccountId> {
+        E::SubaccountId::from_id(&self.subaccount)
+    }
+
     /// Set flag to trigger reconciliation on next begin blocker
     pub fn reconcile(self, storage: &mut dyn Storage) -> StdResult<()> {
         OB_CONFIG.save(
@@ -201,8 +307,8 @@ impl OrderbookState {
 impl From<OrderbookState> for OrderbookStateResponse {
     fn from(value: OrderbookState) -> Self {
         Self {
-            market_id: value.market_id.as_str().to_string(),
-            subaccount: value.subaccount.as_str().to_string(),
+            market_id: value.market_id,
+            subaccount: value.subaccount,
             min_price_tick_size: value.min_price_tick_size,
             min_quantity_tick_size: value.min_quantity_tick_size,
             need_reconcile: value.need_reconcile,
@@ -210,6 +316,8 @@ impl From<OrderbookState> for OrderbookStateResponse {
             orders_number: value.orders_number,
             min_trades_to_avg: value.min_trades_to_avg,
             ready: value.ready,
+            liquidity_distribution: value.liquidity_distribution,
+            max_liquidity_share: value.max_liquidity_share,
         }
     }
 }
@@ -1,57 +1,51 @@
-use cosmwasm_std::{entry_point, Decimal256, DepsMut, Env, Response, StdResult};
-use injective_cosmwasm::{
-    create_deposit_msg, create_withdraw_msg, InjectiveMsgWrapper, InjectiveQuerier,
-    InjectiveQueryWrapper,
+use cosmwasm_std::{
+    Api, CustomQuery, Decimal256, Env, QuerierWrapper, Response, StdResult, Storage,
 };
 use itertools::Itertools;
 use std::cmp::Ordering;
 
-use astroport::asset::AssetInfoExt;
+use astroport::asset::{Asset, AssetInfoExt};
 use astroport::cosmwasm_ext::IntegerToDecimal;
 use astroport_circular_buffer::BufferManager;
 
 use crate::math::calc_d;
 use crate::orderbook::error::OrderbookError;
-use crate::orderbook::msg::SudoMsg;
-use crate::orderbook::state::OrderbookState;
+use crate::orderbook::exchange::ExchangeModule;
+use crate::orderbook::state::{record_reconciliation, OrderbookState};
 use crate::orderbook::utils::{
-    cancel_all_orders, compute_swap, get_subaccount_balances, leave_orderbook,
-    process_cumulative_trade, update_spot_orders, SpotOrdersFactory,
+    compute_swap, get_subaccount_balances, leave_orderbook, level_weight, process_cumulative_trade,
+    SpotOrdersFactory,
 };
 use crate::state::{Precisions, CONFIG, OBSERVATIONS};
 use crate::utils::query_pools;
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn sudo(
-    deps: DepsMut<InjectiveQueryWrapper>,
+pub(crate) fn begin_blocker<C, E>(
+    storage: &mut dyn Storage,
+    querier: QuerierWrapper<C>,
+    exchange: &E,
     env: Env,
-    msg: SudoMsg,
-) -> Result<Response<InjectiveMsgWrapper>, OrderbookError> {
-    match msg {
-        SudoMsg::BeginBlocker {} => begin_blocker(deps, env),
-        SudoMsg::Deactivate {} => deactivate_orderbook(deps, env),
-    }
-}
-
-fn begin_blocker(
-    deps: DepsMut<InjectiveQueryWrapper>,
-    env: Env,
-) -> Result<Response<InjectiveMsgWrapper>, OrderbookError> {
-    let ob_state = OrderbookState::load(deps.storage)?;
+) -> Result<Response<E::Msg>, OrderbookError>
+where
+    C: CustomQuery,
+    E: ExchangeModule,
+{
+    let ob_state = OrderbookState::load(storage)?;
     if !ob_state.ready {
         return Ok(Response::new());
     }
-    let querier = InjectiveQuerier::new(&deps.querier);
 
-    let balances = get_subaccount_balances(&ob_state.asset_infos, &querier, &ob_state.subaccount)?;
+    let balances = get_subaccount_balances(
+        &ob_state.asset_infos,
+        exchange,
+        &ob_state.subaccount_as::<E>()?,
+    )?;
 
     if ob_state.need_reconcile || ob_state.last_balances != balances {
-        let mut messages = vec![];
-
-        let mut config = CONFIG.load(deps.storage)?;
-        let precisions = Precisions::new(deps.storage)?;
-        let mut pools = query_pools(
-            deps.querier,
+        let config = CONFIG.load(storage)?;
+        let precisions = Precisions::new(storage)?;
+        let pools = query_pools(
+            querier,
+            exchange,
             &env.contract.address,
             &config,
             &ob_state,
@@ -62,147 +56,204 @@ fn begin_blocker(
         .map(|asset| asset.amount)
         .collect_vec();
 
-        let base_asset_precision = precisions.get_precision(&config.pair_info.asset_infos[0])?;
-        let quote_asset_precision = precisions.get_precision(&config.pair_info.asset_infos[1])?;
-
-        // If subaccount balances have changed, then trades have occurred
-        // and we need to repeg and reconcile orderbook
-        if ob_state.last_balances != balances {
-            let maker_fee_message = process_cumulative_trade(
-                deps.querier,
-                &env,
-                &ob_state,
-                &mut config,
-                &mut pools,
-                &balances,
-                base_asset_precision,
-                quote_asset_precision,
-            )?;
-            messages.extend(maker_fee_message);
-
-            CONFIG.save(deps.storage, &config)?;
-        }
+        reconcile_orderbook(storage, querier, exchange, &env, ob_state, balances, pools)
+    } else {
+        Ok(Response::default())
+    }
+}
 
-        let last_observation_opt =
-            BufferManager::new(deps.storage, OBSERVATIONS)?.read_last(deps.storage)?;
-
-        let (avg_base_trade_size, avg_quote_trade_size) = last_observation_opt
-            .map(|last_observation| -> StdResult<_> {
-                let converted_base = last_observation
-                    .base_sma
-                    .to_decimal256(base_asset_precision)?;
-                let converted_quote = last_observation
-                    .quote_sma
-                    .to_decimal256(quote_asset_precision)?;
-                Ok((converted_base, converted_quote))
-            })
-            .transpose()?
-            .ok_or(OrderbookError::NoObservationFound {})?;
-        // This shouldn't happen since we wait until MIN_TRADES_TO_AVG is reached. However, we keep this check just for safety.
-
-        let mut orders_factory = SpotOrdersFactory::new(
-            &ob_state.market_id,
-            &ob_state.subaccount,
-            ob_state.min_price_tick_size,
+/// Processes trades which happened since the last reconciliation, rebuilds orders according to
+/// the current pool state and adjusts subaccount balances. This function is exchange-agnostic:
+/// all interactions with the exchange module go through the [`ExchangeModule`] trait.
+///
+/// * **balances** current subaccount balances.
+///
+/// * **pools** current pool balances (including subaccount deposits) in decimal representation.
+pub fn reconcile_orderbook<C, E>(
+    storage: &mut dyn Storage,
+    querier: QuerierWrapper<C>,
+    exchange: &E,
+    env: &Env,
+    ob_state: OrderbookState,
+    balances: Vec<Asset>,
+    mut pools: Vec<Decimal256>,
+) -> Result<Response<E::Msg>, OrderbookError>
+where
+    C: CustomQuery,
+    E: ExchangeModule,
+{
+    let mut messages = vec![];
+
+    let mut config = CONFIG.load(storage)?;
+    let precisions = Precisions::new(storage)?;
+
+    let base_asset_precision = precisions.get_precision(&config.pair_info.asset_infos[0])?;
+    let quote_asset_precision = precisions.get_precision(&config.pair_info.asset_infos[1])?;
+
+    let mut maker_fee = None;
+    // If subaccount balances have changed, then trades have occurred
+    // and we need to repeg and reconcile orderbook
+    if ob_state.last_balances != balances {
+        let (maker_fee_message, maker_fee_asset) = process_cumulative_trade(
+            querier,
+            env,
+            &ob_state,
+            &mut config,
+            &mut pools,
+            &balances,
             base_asset_precision,
             quote_asset_precision,
-        );
+        )?;
+        messages.extend(maker_fee_message);
+        maker_fee = maker_fee_asset;
 
-        // Adjusting to min quantity tick size on Injective market
-        let avg_base_trade_size = (avg_base_trade_size / ob_state.min_quantity_tick_size).floor()
-            * ob_state.min_quantity_tick_size;
+        CONFIG.save(storage, &config)?;
+    }
 
-        // If adjusted avg_trade_size is zero we cancel all orders and withdraw liquidity.
-        if avg_base_trade_size.is_zero() {
-            return leave_orderbook(&ob_state, balances, &env);
-        }
+    record_reconciliation(storage, env, &ob_state.last_balances, &balances, maker_fee)?;
+
+    let last_observation_opt = BufferManager::new(storage, OBSERVATIONS)?.read_last(storage)?;
+
+    let (avg_base_trade_size, avg_quote_trade_size) = last_observation_opt
+        .map(|last_observation| -> StdResult<_> {
+            let converted_base = last_observation
+                .base_sma
+                .to_decimal256(base_asset_precision)?;
+            let converted_quote = last_observation
+                .quote_sma
+                .to_decimal256(quote_asset_precision)?;
+            Ok((converted_base, converted_quote))
+        })
+        .transpose()?
+        .ok_or(OrderbookError::NoObservationFound {})?;
+    // This shouldn't happen since we wait until MIN_TRADES_TO_AVG is reached. However, we keep this check just for safety.
+
+    let mut orders_factory = SpotOrdersFactory::new(
+        ob_state.min_price_tick_size,
+        base_asset_precision,
+        quote_asset_precision,
+    );
+
+    // Adjusting to min quantity tick size on the market
+    let avg_base_trade_size = (avg_base_trade_size / ob_state.min_quantity_tick_size).floor()
+        * ob_state.min_quantity_tick_size;
+
+    // If adjusted avg_trade_size is zero we cancel all orders and withdraw liquidity.
+    if avg_base_trade_size.is_zero() {
+        return leave_orderbook(exchange, &ob_state, balances, env);
+    }
 
-        let amp_gamma = config.pool_state.get_amp_gamma(&env);
-        let mut ixs = pools.to_vec();
-        ixs[1] *= config.pool_state.price_state.price_scale;
-        let d = calc_d(&ixs, &amp_gamma)?;
-
-        // Equal heights algorithm
-        for i in 1..=ob_state.orders_number {
-            let quote_sell_amount = avg_quote_trade_size * Decimal256::from_ratio(i, 1u8);
-            let base_sell_amount = compute_swap(&ixs, quote_sell_amount, 0, &config, amp_gamma, d)?;
-            let sell_amount = (base_sell_amount * Decimal256::from_ratio(1u8, i)
-                / ob_state.min_quantity_tick_size)
+    let amp_gamma = config.pool_state.get_amp_gamma(env);
+    let mut ixs = pools.to_vec();
+    ixs[1] *= config.pool_state.price_state.price_scale;
+    let d = calc_d(&ixs, &amp_gamma)?;
+
+    let max_liquidity_share = ob_state.max_liquidity_share.map(Decimal256::from);
+    let mut cumulative_weight = Decimal256::zero();
+
+    // Equal heights algorithm. Order sizes are scaled by level weights according to the distribution shape.
+    for i in 1..=ob_state.orders_number {
+        let weight = level_weight(&ob_state.liquidity_distribution, i)?;
+        cumulative_weight += weight;
+
+        let quote_sell_amount = avg_quote_trade_size * cumulative_weight;
+        let base_sell_amount = compute_swap(&ixs, quote_sell_amount, 0, &config, amp_gamma, d)?;
+        let sell_amount =
+            (base_sell_amount * weight / cumulative_weight / ob_state.min_quantity_tick_size)
                 .floor()
                 * ob_state.min_quantity_tick_size;
 
-            let sell_price = if i > 1 {
-                (quote_sell_amount - orders_factory.orderbook_one_side_liquidity(false))
-                    / sell_amount
-            } else {
-                quote_sell_amount / sell_amount
-            };
-
-            let buy_amount = avg_base_trade_size;
-            let base_buy_amount = buy_amount * Decimal256::from_ratio(i, 1u8);
-            let quote_buy_amount = compute_swap(&ixs, base_buy_amount, 1, &config, amp_gamma, d)?;
-            let buy_price = if i > 1 {
-                (quote_buy_amount - orders_factory.orderbook_one_side_liquidity(true)) / buy_amount
-            } else {
-                quote_buy_amount / base_buy_amount
-            };
-
-            // If price is zero we cancel all orders and withdraw liquidity.
-            if sell_price.is_zero() || buy_price.is_zero() {
-                return leave_orderbook(&ob_state, balances, &env);
-            }
+        let buy_amount = (avg_base_trade_size * weight / ob_state.min_quantity_tick_size).floor()
+            * ob_state.min_quantity_tick_size;
 
-            orders_factory.sell(sell_price, sell_amount);
-            orders_factory.buy(buy_price, buy_amount);
+        // Orders became too small to be placed on the market
+        if sell_amount.is_zero() || buy_amount.is_zero() {
+            break;
         }
 
-        let total_deposits =
-            orders_factory.total_deposit(&config.pair_info.asset_infos, &precisions)?;
+        let sell_price = if i > 1 {
+            (quote_sell_amount - orders_factory.orderbook_one_side_liquidity(false)) / sell_amount
+        } else {
+            quote_sell_amount / sell_amount
+        };
+
+        let base_buy_amount = avg_base_trade_size * cumulative_weight;
+        let quote_buy_amount = compute_swap(&ixs, base_buy_amount, 1, &config, amp_gamma, d)?;
+        let buy_price = if i > 1 {
+            (quote_buy_amount - orders_factory.orderbook_one_side_liquidity(true)) / buy_amount
+        } else {
+            quote_buy_amount / base_buy_amount
+        };
+
+        // If price is zero we cancel all orders and withdraw liquidity.
+        if sell_price.is_zero() || buy_price.is_zero() {
+            return leave_orderbook(exchange, &ob_state, balances, env);
+        }
 
-        // Cancel all orders first
-        messages.push(cancel_all_orders(
-            &env.contract.address,
-            &ob_state.subaccount,
-            &ob_state.market_id,
-        ));
+        // Stop placing orders if the next level exceeds the max share of pool liquidity
+        if let Some(max_share) = max_liquidity_share {
+            let total_sell = orders_factory.orderbook_one_side_amount(false) + sell_amount;
+            let total_buy =
+                orders_factory.orderbook_one_side_liquidity(true) + buy_price * buy_amount;
+            if total_sell > pools[0] * max_share || total_buy > pools[1] * max_share {
+                break;
+            }
+        }
 
-        // Adjust subaccount balances
-        total_deposits
-            .iter()
-            .zip(balances.iter())
-            .try_for_each::<_, StdResult<_>>(|(need, current)| {
-                match need.amount.cmp(&current.amount) {
-                    Ordering::Greater => messages.push(create_deposit_msg(
-                        env.contract.address.clone(),
-                        ob_state.subaccount.clone(),
+        orders_factory.sell(sell_price, sell_amount);
+        orders_factory.buy(buy_price, buy_amount);
+    }
+
+    let total_deposits =
+        orders_factory.total_deposit(&config.pair_info.asset_infos, &precisions)?;
+
+    // Cancel all orders first
+    messages.push(exchange.cancel_all_orders_msg(&env.contract.address, &ob_state));
+
+    // Adjust subaccount balances
+    let subaccount = ob_state.subaccount_as::<E>()?;
+    total_deposits
+        .iter()
+        .zip(balances.iter())
+        .try_for_each::<_, StdResult<_>>(|(need, current)| {
+            match need.amount.cmp(&current.amount) {
+                Ordering::Greater => messages.push(
+                    exchange.deposit_msg(
+                        &env.contract.address,
+                        &subaccount,
                         need.info
                             .with_balance(need.amount - current.amount)
                             .as_coin()?,
-                    )),
-                    Ordering::Less => {
-                        messages.push(create_withdraw_msg(
-                            env.contract.address.clone(),
-                            ob_state.subaccount.clone(),
+                    ),
+                ),
+                Ordering::Less => {
+                    messages.push(
+                        exchange.withdraw_msg(
+                            &env.contract.address,
+                            &subaccount,
                             need.info
                                 .with_balance(current.amount - need.amount)
                                 .as_coin()?,
-                        ));
-                    }
-                    Ordering::Equal => {}
+                        ),
+                    );
                 }
+                Ordering::Equal => {}
+            }
 
-                Ok(())
-            })?;
+            Ok(())
+        })?;
 
-        let new_orders = orders_factory.collect_orders(&env.contract.address)?;
-        messages.push(update_spot_orders(&env.contract.address, new_orders));
+    let new_orders = orders_factory.collect_orders();
+    messages.push(exchange.create_orders_msg(
+        &env.contract.address,
+        &ob_state,
+        new_orders,
+        &env.contract.address,
+    )?);
 
-        ob_state.reconciliation_done(deps.storage, total_deposits)?;
+    ob_state.reconciliation_done(storage, total_deposits)?;
 
-        Ok(Response::new().add_messages(messages))
-    } else {
-        Ok(Response::default())
-    }
+    Ok(Response::new().add_messages(messages))
 }
 
 /// This function is called when chain for some reason wants to remove our contract from begin blocker.
@@ -213,21 +264,241 @@ fn begin_blocker(
 /// In that case we disable orderbook integration, cancel all orders and withdraw all subaccount balances.
 /// This function may fail due to out of gas error thus for safety reasons we have permissionless endpoint
 /// [`astroport::pair_concentrated_inj::ExecuteMsg::WithdrawFromOrderbook`] to perform the same action.
-fn deactivate_orderbook(
-    deps: DepsMut<InjectiveQueryWrapper>,
+pub(crate) fn deactivate_orderbook<E: ExchangeModule>(
+    api: &dyn Api,
+    storage: &dyn Storage,
+    exchange: &E,
     env: Env,
-) -> Result<Response<InjectiveMsgWrapper>, OrderbookError> {
-    deps.api.debug(&format!(
+) -> Result<Response<E::Msg>, OrderbookError> {
+    api.debug(&format!(
         "Deactivating Astroport pair {} orderbook integration",
         &env.contract.address
     ));
 
-    let ob_state = OrderbookState::load(deps.storage)?;
+    let ob_state = OrderbookState::load(storage)?;
 
-    let querier = InjectiveQuerier::new(&deps.querier);
-    let balances = get_subaccount_balances(&ob_state.asset_infos, &querier, &ob_state.subaccount)?;
+    let balances = get_subaccount_balances(
+        &ob_state.asset_infos,
+        exchange,
+        &ob_state.subaccount_as::<E>()?,
+    )?;
 
-    Ok(leave_orderbook(&ob_state, balances, &env)?
+    Ok(leave_orderbook(exchange, &ob_state, balances, &env)?
         .add_attribute("action", "deactivate")
         .add_attribute("pair", &env.contract.address))
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, MockQuerier};
+    use cosmwasm_std::{coin, Addr, CosmosMsg, Decimal, Empty, Uint128};
+
+    use astroport::asset::{native_asset_info, PairInfo};
+    use astroport::factory::PairType;
+
+    use crate::orderbook::exchange::mock::{MockExchange, MockExchangeMsg};
+    use crate::orderbook::exchange::Order;
+    use crate::orderbook::state::read_reconciliations;
+    use crate::state::{
+        AmpGamma, Config, Observation, PoolParams, PoolState, PriceState, PRECISIONS,
+    };
+
+    use super::*;
+
+    /// Stores pool config, precisions and one observation with average trade size of 10 tokens.
+    /// Returns orderbook state with 3 orders on each side.
+    fn init_pool(
+        storage: &mut dyn Storage,
+        env: &Env,
+        max_liquidity_share: Option<Decimal>,
+    ) -> OrderbookState {
+        let asset_infos = vec![
+            native_asset_info("uusd".to_string()),
+            native_asset_info("uatom".to_string()),
+        ];
+
+        for asset_info in &asset_infos {
+            PRECISIONS
+                .save(storage, asset_info.to_string(), &6)
+                .unwrap();
+        }
+
+        let amp_gamma = AmpGamma {
+            amp: Decimal::from_str("40").unwrap(),
+            gamma: Decimal::from_str("0.000145").unwrap(),
+        };
+        let config = Config {
+            pair_info: PairInfo {
+                asset_infos: asset_infos.clone(),
+                contract_addr: env.contract.address.clone(),
+                liquidity_token: Addr::unchecked("lp_token"),
+                pair_type: PairType::Custom("concentrated_inj_orderbook".to_string()),
+            },
+            factory_addr: Addr::unchecked("factory"),
+            block_time_last: 0,
+            pool_params: PoolParams {
+                mid_fee: Decimal::from_str("0.0026").unwrap(),
+                out_fee: Decimal::from_str("0.0045").unwrap(),
+                fee_gamma: Decimal::from_str("0.00023").unwrap(),
+                repeg_profit_threshold: Decimal::from_str("0.000002").unwrap(),
+                min_price_scale_delta: Decimal::from_str("0.000146").unwrap(),
+                ma_half_time: 600,
+            },
+            pool_state: PoolState {
+                initial: amp_gamma,
+                future: amp_gamma,
+                future_time: 0,
+                initial_time: 0,
+                price_state: PriceState {
+                    oracle_price: Decimal256::one(),
+                    last_price: Decimal256::one(),
+                    price_scale: Decimal256::one(),
+                    last_price_update: env.block.time.seconds(),
+                    xcp_profit: Decimal256::one(),
+                    xcp: Decimal256::zero(),
+                },
+            },
+            owner: None,
+        };
+        CONFIG.save(storage, &config).unwrap();
+
+        BufferManager::init(storage, OBSERVATIONS, 10).unwrap();
+        let mut buffer = BufferManager::new(storage, OBSERVATIONS).unwrap();
+        buffer.push(&Observation {
+            timestamp: env.block.time.seconds(),
+            base_sma: Uint128::new(10_000000),
+            base_amount: Uint128::new(10_000000),
+            quote_sma: Uint128::new(10_000000),
+            quote_amount: Uint128::new(10_000000),
+        });
+        buffer.commit(storage).unwrap();
+
+        let ob_state = OrderbookState {
+            market_id: "test".to_string(),
+            subaccount: MockExchange::default().subaccount(&env.contract.address),
+            asset_infos: asset_infos.clone(),
+            min_price_tick_size: Decimal256::from_ratio(1u8, 1000u16),
+            min_quantity_tick_size: Decimal256::from_ratio(1u8, 1000u16),
+            need_reconcile: true,
+            last_balances: vec![
+                asset_infos[0].with_balance(0u8),
+                asset_infos[1].with_balance(0u8),
+            ],
+            orders_number: 3,
+            min_trades_to_avg: 1,
+            ready: true,
+            liquidity_distribution: Default::default(),
+            max_liquidity_share,
+        };
+        ob_state.save(storage).unwrap();
+
+        ob_state
+    }
+
+    /// Runs reconciliation against 1000/1000 pool and returns emitted exchange messages.
+    fn reconcile(
+        storage: &mut dyn Storage,
+        env: &Env,
+        ob_state: &OrderbookState,
+    ) -> Vec<CosmosMsg<MockExchangeMsg>> {
+        let pools = vec![
+            Decimal256::from_ratio(1000u16, 1u8),
+            Decimal256::from_ratio(1000u16, 1u8),
+        ];
+        let mock_querier: MockQuerier = MockQuerier::new(&[]);
+
+        reconcile_orderbook(
+            storage,
+            QuerierWrapper::<Empty>::new(&mock_querier),
+            &MockExchange::default(),
+            env,
+            ob_state.clone(),
+            ob_state.last_balances.clone(),
+            pools,
+        )
+        .unwrap()
+        .messages
+        .into_iter()
+        .map(|sub_msg| sub_msg.msg)
+        .collect()
+    }
+
+    fn placed_orders(messages: &[CosmosMsg<MockExchangeMsg>]) -> Vec<Order> {
+        match messages.last() {
+            Some(CosmosMsg::Custom(MockExchangeMsg::CreateOrders {
+                market_id, orders, ..
+            })) => {
+                assert_eq!(market_id, "test");
+                orders.clone()
+            }
+            msg => panic!("Unexpected message: {msg:?}"),
+        }
+    }
+
+    #[test]
+    fn test_reconcile_orderbook_with_mock_exchange() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let ob_state = init_pool(&mut deps.storage, &env, None);
+
+        let messages = reconcile(&mut deps.storage, &env, &ob_state);
+
+        // Balances haven't changed thus no trade is recorded
+        let history = read_reconciliations(&deps.storage, None).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].trade, None);
+        assert_eq!(history[0].maker_fee, None);
+
+        // New deposits are saved as the last balances
+        let new_state = OrderbookState::load(&deps.storage).unwrap();
+        assert!(!new_state.need_reconcile);
+        let expected_deposits = new_state
+            .last_balances
+            .iter()
+            .map(|asset| {
+                CosmosMsg::Custom(MockExchangeMsg::Deposit {
+                    subaccount: ob_state.subaccount.clone(),
+                    coin: coin(asset.amount.u128(), asset.info.to_string()),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(messages.len(), 4);
+        assert_eq!(
+            messages[0],
+            CosmosMsg::Custom(MockExchangeMsg::CancelAll {
+                market_id: "test".to_string()
+            })
+        );
+        assert_eq!(messages[1..3], expected_deposits);
+
+        let orders = placed_orders(&messages);
+        assert_eq!(orders.iter().filter(|order| order.is_buy).count(), 3);
+        assert_eq!(orders.iter().filter(|order| !order.is_buy).count(), 3);
+    }
+
+    #[test]
+    fn test_reconcile_orderbook_max_liquidity_share() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        // 2.5% of 1000 tokens allows only two levels of 10 tokens on each side
+        let max_share = Decimal::from_str("0.025").unwrap();
+        let ob_state = init_pool(&mut deps.storage, &env, Some(max_share));
+
+        let messages = reconcile(&mut deps.storage, &env, &ob_state);
+
+        let orders = placed_orders(&messages);
+        assert_eq!(orders.iter().filter(|order| order.is_buy).count(), 2);
+        assert_eq!(orders.iter().filter(|order| !order.is_buy).count(), 2);
+
+        // Liquidity placed on each side doesn't exceed the max share of the pool
+        let new_state = OrderbookState::load(&deps.storage).unwrap();
+        let limit = Uint128::new(1000_000000) * max_share;
+        for deposit in &new_state.last_balances {
+            assert!(!deposit.amount.is_zero());
+            assert!(deposit.amount <= limit, "{deposit} exceeds {limit}");
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use astroport::asset::{Asset, AssetInfo, AssetInfoExt, DecimalAsset};
 use cosmwasm_std::{
-    Addr, CosmosMsg, CustomMsg, CustomQuery, Decimal, Decimal256, Env, QuerierWrapper, Response,
+    CosmosMsg, CustomMsg, CustomQuery, Decimal, Decimal256, Env, QuerierWrapper, Response,
     StdError, StdResult,
 };
 use std::cmp::Ordering;
@@ -11,16 +11,13 @@ use tiny_keccak::Hasher;
 use crate::contract::LP_TOKEN_PRECISION;
 use crate::error::ContractError;
 use crate::math::calc_y;
-use crate::orderbook::consts::SUBACC_NONCE;
 use crate::orderbook::error::OrderbookError;
+use crate::orderbook::exchange::{ExchangeModule, Order};
 use crate::orderbook::state::OrderbookState;
 use crate::state::{AmpGamma, Config, Precisions};
 use astroport::cosmwasm_ext::{AbsDiff, ConvertInto, IntegerToDecimal};
+use astroport::pair_concentrated_inj::{LiquidityDistribution, OrderbookTrade};
 use astroport::querier::{query_fee_info, query_supply};
-use injective_cosmwasm::{
-    checked_address_to_subaccount_id, create_batch_update_orders_msg, create_withdraw_msg,
-    InjectiveMsgWrapper, InjectiveQuerier, MarketId, OrderType, SpotOrder, SubaccountId,
-};
 
 /// Calculate hash from two binary slices.
 pub fn calc_hash(a1: &[u8], a2: &[u8]) -> String {
@@ -57,59 +54,14 @@ pub fn calc_market_ids(asset_infos: &[AssetInfo]) -> StdResult<[String; 2]> {
     ])
 }
 
-/// A thin wrapper to get subaccount we are working with.
-#[inline]
-pub fn get_subaccount(addr: &Addr) -> SubaccountId {
-    // Starting from v1.10 injective uses default subaccount (nonce = 0) to automatically transfer
-    // funds from bank module when creating an order. We need to avoid it.
-    checked_address_to_subaccount_id(addr, SUBACC_NONCE)
-}
-
-/// A thin wrapper to create new batch of orders.
-#[inline]
-pub fn update_spot_orders(
-    sender: &Addr,
-    new_spot_orders: Vec<SpotOrder>,
-) -> CosmosMsg<InjectiveMsgWrapper> {
-    create_batch_update_orders_msg(
-        sender.clone(),
-        None,
-        vec![],
-        vec![],
-        vec![],
-        vec![],
-        new_spot_orders,
-        vec![],
-    )
-}
-
-/// A thin wrapper to cancel all orders.
-#[inline]
-pub fn cancel_all_orders(
-    sender: &Addr,
-    subaccount: &SubaccountId,
-    market_id: &MarketId,
-) -> CosmosMsg<InjectiveMsgWrapper> {
-    create_batch_update_orders_msg(
-        sender.clone(),
-        Some(subaccount.clone()),
-        vec![market_id.clone()],
-        vec![],
-        vec![],
-        vec![],
-        vec![],
-        vec![],
-    )
-}
-
 /// Fetches subaccount balances in decimal representation.
-pub(crate) fn get_subaccount_balances_dec(
+pub(crate) fn get_subaccount_balances_dec<E: ExchangeModule>(
     asset_infos: &[AssetInfo],
     precisions: &Precisions,
-    querier: &InjectiveQuerier,
-    subaccount: &SubaccountId,
+    exchange: &E,
+    subaccount: &E::SubaccountId,
 ) -> Result<Vec<DecimalAsset>, ContractError> {
-    get_subaccount_balances(asset_infos, querier, subaccount)?
+    get_subaccount_balances(asset_infos, exchange, subaccount)?
         .into_iter()
         .map(|asset| {
             let dec_asset = DecimalAsset {
@@ -124,22 +76,18 @@ pub(crate) fn get_subaccount_balances_dec(
 }
 
 /// Fetches subaccount balances in integer representation.
-pub fn get_subaccount_balances(
+pub fn get_subaccount_balances<E: ExchangeModule>(
     asset_infos: &[AssetInfo],
-    querier: &InjectiveQuerier,
-    subaccount: &SubaccountId,
+    exchange: &E,
+    subaccount: &E::SubaccountId,
 ) -> Result<Vec<Asset>, ContractError> {
     asset_infos
         .iter()
         .map(|asset_info| match asset_info {
-            AssetInfo::NativeToken { denom } => {
-                let resp = querier.query_subaccount_deposit(subaccount, denom)?;
-                let dec_asset = Asset {
-                    info: asset_info.clone(),
-                    amount: resp.deposits.total_balance.into(),
-                };
-                Ok(dec_asset)
-            }
+            AssetInfo::NativeToken { denom } => Ok(Asset {
+                info: asset_info.clone(),
+                amount: exchange.query_deposit(subaccount, denom)?,
+            }),
             AssetInfo::Token { .. } => {
                 Err(StdError::generic_err("CW20 tokens are not supported").into())
             }
@@ -148,28 +96,22 @@ pub fn get_subaccount_balances(
 }
 
 /// Cancels all orders and withdraws all balances from the orderbook.
-pub fn leave_orderbook(
+pub fn leave_orderbook<E: ExchangeModule>(
+    exchange: &E,
     ob_state: &OrderbookState,
     balances: Vec<Asset>,
     env: &Env,
-) -> Result<Response<InjectiveMsgWrapper>, OrderbookError> {
+) -> Result<Response<E::Msg>, OrderbookError> {
     // Cancel all orders first
-    let cancel_orders_msg = cancel_all_orders(
-        &env.contract.address,
-        &ob_state.subaccount,
-        &ob_state.market_id,
-    );
+    let cancel_orders_msg = exchange.cancel_all_orders_msg(&env.contract.address, ob_state);
 
     // Withdraw all balances
+    let subaccount = ob_state.subaccount_as::<E>()?;
     let withdraw_messages = balances
         .into_iter()
         .filter(|asset| !asset.amount.is_zero())
         .map(|asset| {
-            let msg = create_withdraw_msg(
-                env.contract.address.clone(),
-                ob_state.subaccount.clone(),
-                asset.as_coin()?,
-            );
+            let msg = exchange.withdraw_msg(&env.contract.address, &subaccount, asset.as_coin()?);
             Ok(msg)
         })
         .collect::<StdResult<Vec<_>>>()?;
@@ -179,17 +121,6 @@ pub fn leave_orderbook(
         .add_messages(withdraw_messages))
 }
 
-/// Ask chain module whether contract is registered for begin blocker or not.
-pub fn is_contract_active(inj_querier: &InjectiveQuerier, contract_addr: &Addr) -> StdResult<bool> {
-    let reg_info = inj_querier.query_contract_registration_info(contract_addr)?;
-    let active = reg_info
-        .contract
-        .map(|reg| reg.is_executable)
-        .unwrap_or(false);
-
-    Ok(active)
-}
-
 /// Calculate swap result using cached D.
 pub fn compute_swap(
     ixs: &[Decimal256],
@@ -233,30 +164,20 @@ struct AstroSpotOrder {
 }
 
 /// Internal structure to handle spot orders.
-pub struct SpotOrdersFactory<'a> {
-    market_id: &'a MarketId,
-    subaccount: &'a SubaccountId,
+pub struct SpotOrdersFactory {
     orders: Vec<AstroSpotOrder>,
     min_price_tick_size: Decimal256,
     precisions_ratio: Decimal256,
     base_precision: Decimal256,
 }
 
-impl<'a> SpotOrdersFactory<'a> {
-    pub fn new(
-        market_id: &'a MarketId,
-        subaccount: &'a SubaccountId,
-        min_price_tick_size: Decimal256,
-        base_precision: u8,
-        quote_precision: u8,
-    ) -> Self {
+impl SpotOrdersFactory {
+    pub fn new(min_price_tick_size: Decimal256, base_precision: u8, quote_precision: u8) -> Self {
         let quote_precision = Decimal256::from_ratio(10u64.pow(quote_precision as u32), 1u8);
         let base_precision = Decimal256::from_ratio(10u64.pow(base_precision as u32), 1u8);
         let precisions_ratio = quote_precision / base_precision;
 
         Self {
-            market_id,
-            subaccount,
             orders: vec![],
             min_price_tick_size,
             precisions_ratio,
@@ -294,6 +215,14 @@ impl<'a> SpotOrdersFactory<'a> {
             })
     }
 
+    /// Calculate total sell/buy amount measured in base asset.
+    pub fn orderbook_one_side_amount(&self, is_buy: bool) -> Decimal256 {
+        self.orders
+            .iter()
+            .filter(|order| order.is_buy == is_buy)
+            .fold(Decimal256::zero(), |acc, order| acc + order.amount)
+    }
+
     /// Calculates total subaccount balance the contract will need to place all orders.
     pub(crate) fn total_deposit(
         &self,
@@ -326,8 +255,8 @@ impl<'a> SpotOrdersFactory<'a> {
     }
 
     /// Aggregates orders with the same price. Adjusts price to min_price_tick_size and converts
-    /// orders into Injective representation.
-    pub fn collect_orders(&self, fee_receiver: &Addr) -> StdResult<Vec<SpotOrder>> {
+    /// orders into exchange-agnostic [`Order`] representation.
+    pub fn collect_orders(&self) -> Vec<Order> {
         let mut temp_orders_map = HashMap::new();
 
         for order in &self.orders {
@@ -352,25 +281,32 @@ impl<'a> SpotOrdersFactory<'a> {
 
         temp_orders_map
             .values()
-            .map(|order| {
-                Ok(SpotOrder::new(
-                    order.price.conv()?,
-                    (order.amount * self.base_precision).conv()?,
-                    if order.is_buy {
-                        OrderType::BuyPo
-                    } else {
-                        OrderType::SellPo
-                    },
-                    self.market_id,
-                    self.subaccount.clone(),
-                    Some(fee_receiver.clone()),
-                ))
+            .map(|order| Order {
+                price: order.price,
+                quantity: order.amount * self.base_precision,
+                is_buy: order.is_buy,
             })
             .collect()
     }
 }
 
+/// Returns the weight of the orderbook level `level` (starting from 1) according to the distribution shape.
+pub fn level_weight(distribution: &LiquidityDistribution, level: u8) -> StdResult<Decimal256> {
+    let weight = match distribution {
+        LiquidityDistribution::AvgTradeSize {} => Decimal256::one(),
+        LiquidityDistribution::Linear { step } => {
+            Decimal256::one() + Decimal256::from(*step) * Decimal256::from_ratio(level - 1, 1u8)
+        }
+        LiquidityDistribution::Geometric { ratio } => {
+            Decimal256::from(*ratio).checked_pow((level - 1) as u32)?
+        }
+    };
+
+    Ok(weight)
+}
+
 /// Process filled orders as one cumulative trade. Send maker fees and run repegging algorithm.
+/// Returns maker fee messages along with the maker fee asset (if any).
 #[allow(clippy::too_many_arguments)]
 pub fn process_cumulative_trade<C, T>(
     querier: QuerierWrapper<C>,
@@ -381,7 +317,7 @@ pub fn process_cumulative_trade<C, T>(
     subacc_balances: &[Asset],
     base_precision: u8,
     quote_precision: u8,
-) -> Result<Vec<CosmosMsg<T>>, OrderbookError>
+) -> Result<(Vec<CosmosMsg<T>>, Option<Asset>), OrderbookError>
 where
     C: CustomQuery,
     T: CustomMsg,
@@ -404,6 +340,7 @@ where
     )?;
 
     let mut messages = vec![];
+    let mut maker_fee_asset = None;
     if let Some(fee_addr) = fee_info.fee_address {
         // This is safe conversion because fee_rate is always <= 1
         let dynamic_fee_rate: Decimal = config.pool_params.fee(&ixs).conv()?;
@@ -420,11 +357,9 @@ where
                 let maker_fee_dec = maker_fee.to_decimal256(base_precision)?;
                 ixs[0] -= maker_fee_dec;
                 pools[0] -= maker_fee_dec;
-                messages.push(
-                    config.pair_info.asset_infos[0]
-                        .with_balance(maker_fee)
-                        .into_msg(fee_addr)?,
-                );
+                let fee_asset = config.pair_info.asset_infos[0].with_balance(maker_fee);
+                messages.push(fee_asset.clone().into_msg(fee_addr)?);
+                maker_fee_asset = Some(fee_asset);
             }
             Ordering::Less => {
                 // base -> quote i.e. sell direction. Charging fees in quote asset
@@ -432,16 +367,14 @@ where
                 let maker_fee_dec = maker_fee.to_decimal256(quote_precision)?;
                 ixs[1] -= maker_fee_dec * config.pool_state.price_state.price_scale;
                 pools[1] -= maker_fee_dec;
-                messages.push(
-                    config.pair_info.asset_infos[1]
-                        .with_balance(maker_fee)
-                        .into_msg(fee_addr)?,
-                );
+                let fee_asset = config.pair_info.asset_infos[1].with_balance(maker_fee);
+                messages.push(fee_asset.clone().into_msg(fee_addr)?);
+                maker_fee_asset = Some(fee_asset);
             }
             Ordering::Equal => {
                 // this should never happen as we supposed to call this function only
                 // if there was at least one trade
-                return Ok(messages);
+                return Ok((messages, maker_fee_asset));
             }
         }
     }
@@ -456,14 +389,30 @@ where
         .pool_state
         .update_price(&config.pool_params, env, total_lp, &ixs, fba_price)?;
 
-    Ok(messages)
+    Ok((messages, maker_fee_asset))
+}
+
+/// Converts subaccount balances change into a cumulative [`OrderbookTrade`].
+/// Returns None if balances have not changed.
+pub fn cumulative_trade(last_balances: &[Asset], balances: &[Asset]) -> Option<OrderbookTrade> {
+    match last_balances[0].amount.cmp(&balances[0].amount) {
+        Ordering::Equal => None,
+        ordering => Some(OrderbookTrade {
+            // Base asset balance increased thus buy orders were filled
+            is_buy: ordering == Ordering::Less,
+            base_amount: last_balances[0].amount.diff(balances[0].amount),
+            quote_amount: last_balances[1].amount.diff(balances[1].amount),
+        }),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::orderbook::exchange::mock::{MockExchange, MockExchangeMsg};
     use astroport::asset::{native_asset_info, token_asset_info};
-    use cosmwasm_std::Addr;
+    use cosmwasm_std::testing::mock_env;
+    use cosmwasm_std::{coin, Addr, Uint128};
 
     #[test]
     fn test_calc_market_ids() {
@@ -510,4 +459,158 @@ mod tests {
             "Generic error: Orderbook integration supports only pools with 2 assets"
         );
     }
+
+    #[test]
+    fn test_leave_orderbook_with_mock_exchange() {
+        let env = mock_env();
+        let asset_infos = vec![
+            native_asset_info("uusd".to_string()),
+            native_asset_info("uatom".to_string()),
+        ];
+        let mut exchange = MockExchange::default();
+        exchange
+            .deposits
+            .insert("uatom".to_string(), Uint128::from(1000u128));
+
+        let ob_state = OrderbookState {
+            market_id: "test".to_string(),
+            subaccount: exchange.subaccount(&env.contract.address),
+            asset_infos: asset_infos.clone(),
+            min_price_tick_size: Default::default(),
+            min_quantity_tick_size: Default::default(),
+            need_reconcile: false,
+            last_balances: vec![],
+            orders_number: 1,
+            min_trades_to_avg: 1,
+            ready: true,
+            liquidity_distribution: Default::default(),
+            max_liquidity_share: None,
+        };
+
+        let balances =
+            get_subaccount_balances(&asset_infos, &exchange, &ob_state.subaccount).unwrap();
+        assert_eq!(
+            balances,
+            vec![
+                asset_infos[0].with_balance(0u8),
+                asset_infos[1].with_balance(1000u128)
+            ]
+        );
+
+        let messages = leave_orderbook(&exchange, &ob_state, balances, &env)
+            .unwrap()
+            .messages
+            .into_iter()
+            .map(|sub_msg| sub_msg.msg)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            messages,
+            vec![
+                CosmosMsg::Custom(MockExchangeMsg::CancelAll {
+                    market_id: "test".to_string()
+                }),
+                // Zero balances are not withdrawn
+                CosmosMsg::Custom(MockExchangeMsg::Withdraw {
+                    subaccount: ob_state.subaccount.clone(),
+                    coin: coin(1000, "uatom")
+                })
+            ]
+        );
+    }
+
+    #[test]
+    fn test_orders_factory_aggregates_orders() {
+        let mut factory = SpotOrdersFactory::new(Decimal256::from_ratio(1u8, 100u8), 6, 6);
+
+        factory.buy(Decimal256::from_ratio(101u8, 100u8), Decimal256::one());
+        factory.buy(Decimal256::from_ratio(1011u16, 1000u16), Decimal256::one());
+        factory.sell(Decimal256::from_ratio(12u8, 10u8), Decimal256::one());
+
+        let mut orders = factory.collect_orders();
+        orders.sort_by(|a, b| a.price.cmp(&b.price));
+
+        assert_eq!(
+            orders,
+            vec![
+                Order {
+                    price: Decimal256::from_ratio(101u8, 100u8),
+                    quantity: Decimal256::from_ratio(2_000000u128, 1u8),
+                    is_buy: true,
+                },
+                Order {
+                    price: Decimal256::from_ratio(12u8, 10u8),
+                    quantity: Decimal256::from_ratio(1_000000u128, 1u8),
+                    is_buy: false,
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_level_weights() {
+        let weights = |distribution: LiquidityDistribution| {
+            (1..=4)
+                .map(|level| level_weight(&distribution, level).unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            weights(LiquidityDistribution::AvgTradeSize {}),
+            vec![Decimal256::one(); 4]
+        );
+        assert_eq!(
+            weights(LiquidityDistribution::Linear {
+                step: Decimal::percent(50)
+            }),
+            vec![
+                Decimal256::one(),
+                Decimal256::percent(150),
+                Decimal256::percent(200),
+                Decimal256::percent(250)
+            ]
+        );
+        assert_eq!(
+            weights(LiquidityDistribution::Geometric {
+                ratio: Decimal::percent(50)
+            }),
+            vec![
+                Decimal256::one(),
+                Decimal256::percent(50),
+                Decimal256::percent(25),
+                Decimal256::permille(125)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cumulative_trade() {
+        let base = native_asset_info("uatom".to_string());
+        let quote = native_asset_info("uusd".to_string());
+        let last_balances = vec![base.with_balance(100u8), quote.with_balance(1000u16)];
+
+        assert_eq!(cumulative_trade(&last_balances, &last_balances), None);
+
+        // Buy orders were filled: base balance increased, quote balance decreased
+        let balances = vec![base.with_balance(110u8), quote.with_balance(900u16)];
+        assert_eq!(
+            cumulative_trade(&last_balances, &balances),
+            Some(OrderbookTrade {
+                is_buy: true,
+                base_amount: Uint128::new(10),
+                quote_amount: Uint128::new(100),
+            })
+        );
+
+        // Sell orders were filled: base balance decreased, quote balance increased
+        let balances = vec![base.with_balance(90u8), quote.with_balance(1100u16)];
+        assert_eq!(
+            cumulative_trade(&last_balances, &balances),
+            Some(OrderbookTrade {
+                is_buy: false,
+                base_amount: Uint128::new(10),
+                quote_amount: Uint128::new(100),
+            })
+        );
+    }
 }
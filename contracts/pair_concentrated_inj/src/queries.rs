@@ -18,7 +18,8 @@ use astroport_circular_buffer::BufferManager;
 use crate::contract::LP_TOKEN_PRECISION;
 use crate::error::ContractError;
 use crate::math::calc_d;
-use crate::orderbook::state::OrderbookState;
+use crate::orderbook::injective::InjectiveExchange;
+use crate::orderbook::state::{read_reconciliations, OrderbookState};
 use crate::state::{Observation, Precisions, CONFIG, OBSERVATIONS};
 use crate::utils::{
     before_swap_check, compute_offer_amount, compute_swap, get_share_in_assets, query_pools,
@@ -73,6 +74,9 @@ pub fn query(deps: Deps<InjectiveQueryWrapper>, env: Env, msg: QueryMsg) -> StdR
             let resp: OrderbookStateResponse = OrderbookState::load(deps.storage)?.into();
             to_binary(&resp)
         }
+        QueryMsg::OrderbookHistory { limit } => {
+            to_binary(&read_reconciliations(deps.storage, limit)?)
+        }
     }
 }
 
@@ -85,6 +89,7 @@ fn query_pool(deps: Deps<InjectiveQueryWrapper>, env: Env) -> Result<PoolRespons
 
     let assets = query_pools(
         deps.querier,
+        &InjectiveExchange::new(&deps.querier),
         &env.contract.address,
         &config,
         &ob_state,
@@ -120,6 +125,7 @@ fn query_share(
     let precisions = Precisions::new(deps.storage)?;
     let pools = query_pools(
         deps.querier,
+        &InjectiveExchange::new(&deps.querier),
         &config.pair_info.contract_addr,
         &config,
         &ob_config,
@@ -158,6 +164,7 @@ pub fn query_simulation(
 
     let pools = query_pools(
         deps.querier,
+        &InjectiveExchange::new(&deps.querier),
         &env.contract.address,
         &config,
         &ob_config,
@@ -217,6 +224,7 @@ pub fn query_reverse_simulation(
 
     let pools = query_pools(
         deps.querier,
+        &InjectiveExchange::new(&deps.querier),
         &env.contract.address,
         &config,
         &ob_config,
@@ -301,6 +309,7 @@ pub fn query_compute_d(deps: Deps<InjectiveQueryWrapper>, env: Env) -> StdResult
 
     let mut xs = query_pools(
         deps.querier,
+        &InjectiveExchange::new(&deps.querier),
         &env.contract.address,
         &config,
         &ob_config,
@@ -3,7 +3,6 @@ use cosmwasm_std::{
     Fraction, QuerierWrapper, StdError, StdResult, Storage, Uint128, Uint256,
 };
 use cw20::Cw20ExecuteMsg;
-use injective_cosmwasm::InjectiveQueryWrapper;
 use itertools::Itertools;
 
 use astroport::asset::{Asset, AssetInfo, DecimalAsset};
@@ -16,6 +15,7 @@ use astroport_factory::state::pair_key;
 use crate::consts::{DEFAULT_SLIPPAGE, MAX_ALLOWED_SLIPPAGE, N, OFFER_PERCENT};
 use crate::error::ContractError;
 use crate::math::{calc_d, calc_y};
+use crate::orderbook::exchange::ExchangeModule;
 use crate::orderbook::state::OrderbookState;
 use crate::orderbook::utils::get_subaccount_balances_dec;
 use crate::state::{Config, Observation, PoolParams, Precisions, OBSERVATIONS};
@@ -175,8 +175,8 @@ pub(crate) fn assert_max_spread(
     Ok(())
 }
 
-pub(crate) fn query_contract_balances(
-    querier: QuerierWrapper<InjectiveQueryWrapper>,
+pub(crate) fn query_contract_balances<C: CustomQuery>(
+    querier: QuerierWrapper<C>,
     addr: &Addr,
     config: &Config,
     precisions: &Precisions,
@@ -194,8 +194,9 @@ pub(crate) fn query_contract_balances(
 }
 
 /// Returns current pool's volumes where amount is in [`Decimal256`] form.
-pub(crate) fn query_pools(
-    querier: QuerierWrapper<InjectiveQueryWrapper>,
+pub(crate) fn query_pools<C: CustomQuery, E: ExchangeModule>(
+    querier: QuerierWrapper<C>,
+    exchange: &E,
     addr: &Addr,
     config: &Config,
     ob_config: &OrderbookState,
@@ -215,12 +216,11 @@ pub(crate) fn query_pools(
             })
             .collect::<Result<Vec<_>, ContractError>>()?
     } else {
-        let querier = injective_cosmwasm::InjectiveQuerier::new(&querier);
         get_subaccount_balances_dec(
             &config.pair_info.asset_infos,
             precisions,
-            &querier,
-            &ob_config.subaccount,
+            exchange,
+            &ob_config.subaccount_as::<E>()?,
         )?
         .into_iter()
         .map(|asset| asset.amount)
@@ -531,7 +531,6 @@ mod tests {
 
     use crate::orderbook::consts::MIN_TRADES_TO_AVG_LIMITS;
     use cosmwasm_std::testing::{mock_env, MockStorage};
-    use injective_cosmwasm::{MarketId, SubaccountId};
 
     use super::*;
 
@@ -583,8 +582,8 @@ mod tests {
         let mut store = MockStorage::new();
         let env = mock_env();
         let mut ob_state = OrderbookState {
-            market_id: MarketId::unchecked("test"),
-            subaccount: SubaccountId::unchecked("test"),
+            market_id: "test".to_string(),
+            subaccount: "test".to_string(),
             asset_infos: vec![],
             min_price_tick_size: Default::default(),
             min_quantity_tick_size: Default::default(),
@@ -593,6 +592,8 @@ mod tests {
             orders_number: 0,
             min_trades_to_avg: *MIN_TRADES_TO_AVG_LIMITS.start(),
             ready: false,
+            liquidity_distribution: Default::default(),
+            max_liquidity_share: None,
         };
         BufferManager::init(&mut store, OBSERVATIONS, 10).unwrap();
 
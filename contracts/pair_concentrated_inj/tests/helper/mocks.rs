@@ -32,8 +32,9 @@ use itertools::Itertools;
 use crate::helper::f64_to_dec;
 use astroport_factory::error::ContractError;
 use astroport_factory::state::{PAIRS, TMP_PAIR_INFO};
+use astroport_pair_concentrated_injective::orderbook::injective::get_subaccount;
 use astroport_pair_concentrated_injective::orderbook::msg::SudoMsg;
-use astroport_pair_concentrated_injective::orderbook::utils::{calc_hash, get_subaccount};
+use astroport_pair_concentrated_injective::orderbook::utils::calc_hash;
 
 // This is dirty workaround cuz we can't simulate real gas in cw_multitest
 const GAS_PER_BEGIN_BLOCK: u128 = 100_000;
@@ -27,12 +27,13 @@ use astroport::pair::{
 };
 use astroport::pair_concentrated::{ConcentratedPoolParams, ConcentratedPoolUpdateParams};
 use astroport::pair_concentrated_inj::{
-    ConcentratedInjObParams, OrderbookConfig, OrderbookStateResponse, QueryMsg,
+    ConcentratedInjObParams, OrderbookConfig, OrderbookReconciliation, OrderbookStateResponse,
+    QueryMsg,
 };
 use astroport_pair_concentrated_injective::contract::{execute, instantiate, reply};
 use astroport_pair_concentrated_injective::migrate::migrate;
+use astroport_pair_concentrated_injective::orderbook::injective::sudo;
 use astroport_pair_concentrated_injective::orderbook::state::OrderbookState;
-use astroport_pair_concentrated_injective::orderbook::sudo::sudo;
 use astroport_pair_concentrated_injective::orderbook::utils::calc_market_ids;
 use astroport_pair_concentrated_injective::queries::query;
 use astroport_pair_concentrated_injective::state::Config;
@@ -303,6 +304,8 @@ impl Helper {
                     market_id,
                     orders_number: 5,
                     min_trades_to_avg: 1,
+                    liquidity_distribution: None,
+                    max_liquidity_share: None,
                 },
             })
             .unwrap()
@@ -496,6 +499,12 @@ impl Helper {
             .query_wasm_smart(&self.pair_addr, &QueryMsg::OrderbookState {})
     }
 
+    pub fn query_ob_history(&self, limit: Option<u32>) -> StdResult<Vec<OrderbookReconciliation>> {
+        self.app
+            .wrap()
+            .query_wasm_smart(&self.pair_addr, &QueryMsg::OrderbookHistory { limit })
+    }
+
     fn init_token(
         app: &mut InjApp,
         token_code: u64,
@@ -1,6 +1,6 @@
-use cosmwasm_std::{coins, Addr, Decimal, Uint128};
+use cosmwasm_std::{coins, to_binary, Addr, Decimal, Uint128};
 use cw_multi_test::{next_block, Executor};
-use injective_cosmwasm::InjectiveQuerier;
+use injective_cosmwasm::{InjectiveQuerier, SubaccountId};
 use injective_testing::generate_inj_address;
 use itertools::Itertools;
 
@@ -9,7 +9,10 @@ use astroport::factory::PairType;
 use astroport::pair_concentrated::{
     ConcentratedPoolParams, ConcentratedPoolUpdateParams, PromoteParams, UpdatePoolParams,
 };
-use astroport::pair_concentrated_inj::{ExecuteMsg, MigrateMsg, OrderbookConfig};
+use astroport::pair_concentrated_inj::{
+    ConcentratedObPoolUpdateParams, ExecuteMsg, LiquidityDistribution, MigrateMsg, OrderbookConfig,
+    OrderbookTrade, UpdateMaxLiquidityShare,
+};
 use astroport_pair_concentrated_injective::consts::{AMP_MAX, AMP_MIN, MA_HALF_TIME_LIMITS};
 use astroport_pair_concentrated_injective::error::ContractError;
 use astroport_pair_concentrated_injective::orderbook::consts::MIN_TRADES_TO_AVG_LIMITS;
@@ -772,6 +775,106 @@ fn check_amp_gamma_change() {
     assert_eq!(amp_gamma.future_time, last_change_time);
 }
 
+#[test]
+fn check_update_orderbook_params() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("USDC")];
+
+    let params = ConcentratedPoolParams {
+        amp: f64_to_dec(40f64),
+        gamma: f64_to_dec(0.0001),
+        mid_fee: f64_to_dec(0.0026),
+        out_fee: f64_to_dec(0.0045),
+        fee_gamma: f64_to_dec(0.00023),
+        repeg_profit_threshold: f64_to_dec(0.000002),
+        min_price_scale_delta: f64_to_dec(0.000146),
+        price_scale: Decimal::one(),
+        ma_half_time: 600,
+        track_asset_balances: None,
+    };
+    let mut helper = Helper::new(&owner, test_coins, params, true).unwrap();
+
+    let update_msg = |params: &ConcentratedObPoolUpdateParams| ExecuteMsg::UpdateConfig {
+        params: to_binary(params).unwrap(),
+    };
+
+    let ob_state = helper.query_ob_config_smart().unwrap();
+    assert_eq!(
+        ob_state.liquidity_distribution,
+        LiquidityDistribution::AvgTradeSize {}
+    );
+    assert_eq!(ob_state.max_liquidity_share, None);
+
+    let action = ConcentratedObPoolUpdateParams::UpdateOrderbookParams {
+        orders_number: None,
+        liquidity_distribution: Some(LiquidityDistribution::Geometric {
+            ratio: f64_to_dec(20f64),
+        }),
+        max_liquidity_share: None,
+    };
+    let err = helper
+        .app
+        .execute_contract(
+            owner.clone(),
+            helper.pair_addr.clone(),
+            &update_msg(&action),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Generic error: Incorrect orderbook params: must be 0.1 <= ratio <= 10, but value is 20"
+    );
+
+    let action = ConcentratedObPoolUpdateParams::UpdateOrderbookParams {
+        orders_number: Some(3),
+        liquidity_distribution: Some(LiquidityDistribution::Linear {
+            step: f64_to_dec(0.5),
+        }),
+        max_liquidity_share: Some(UpdateMaxLiquidityShare::Set(f64_to_dec(0.3))),
+    };
+    helper
+        .app
+        .execute_contract(
+            owner.clone(),
+            helper.pair_addr.clone(),
+            &update_msg(&action),
+            &[],
+        )
+        .unwrap();
+
+    let ob_state = helper.query_ob_config_smart().unwrap();
+    assert_eq!(ob_state.orders_number, 3);
+    assert_eq!(
+        ob_state.liquidity_distribution,
+        LiquidityDistribution::Linear {
+            step: f64_to_dec(0.5)
+        }
+    );
+    assert_eq!(ob_state.max_liquidity_share, Some(f64_to_dec(0.3)));
+    assert!(ob_state.need_reconcile);
+
+    let action = ConcentratedObPoolUpdateParams::UpdateOrderbookParams {
+        orders_number: None,
+        liquidity_distribution: None,
+        max_liquidity_share: Some(UpdateMaxLiquidityShare::Remove {}),
+    };
+    helper
+        .app
+        .execute_contract(
+            owner.clone(),
+            helper.pair_addr.clone(),
+            &update_msg(&action),
+            &[],
+        )
+        .unwrap();
+
+    let ob_state = helper.query_ob_config_smart().unwrap();
+    assert_eq!(ob_state.orders_number, 3);
+    assert_eq!(ob_state.max_liquidity_share, None);
+}
+
 #[test]
 #[ignore]
 fn check_prices() {
@@ -1017,18 +1120,36 @@ fn check_orderbook_integration() {
     assert_eq!(ob_state.need_reconcile, true);
     assert_eq!(ob_state.ready, true);
 
+    let history = helper.query_ob_history(Some(5)).unwrap();
+    assert_eq!(history.len(), 5);
+    assert!(history
+        .windows(2)
+        .all(|records| records[0].timestamp >= records[1].timestamp));
+    // Orders were not filled thus reconciliations only rebuilt orders
+    for record in &history {
+        assert_eq!(record.trade, None);
+        assert_eq!(record.maker_fee, None);
+        assert_eq!(record.last_balances, record.balances);
+    }
+
     let ob_config = helper.query_ob_config().unwrap();
     let querier_wrapper = helper.app.wrap();
     let inj_querier = InjectiveQuerier::new(&querier_wrapper);
 
     let inj_deposit: u128 = inj_querier
-        .query_subaccount_deposit(&ob_config.subaccount, &"inj".to_string())
+        .query_subaccount_deposit(
+            &SubaccountId::unchecked(&ob_config.subaccount),
+            &"inj".to_string(),
+        )
         .unwrap()
         .deposits
         .total_balance
         .into();
     let astro_deposit: u128 = inj_querier
-        .query_subaccount_deposit(&ob_config.subaccount, &"astro".to_string())
+        .query_subaccount_deposit(
+            &SubaccountId::unchecked(&ob_config.subaccount),
+            &"astro".to_string(),
+        )
         .unwrap()
         .deposits
         .total_balance
@@ -1074,13 +1195,19 @@ fn check_orderbook_integration() {
     let querier_wrapper = helper.app.wrap();
     let inj_querier = InjectiveQuerier::new(&querier_wrapper);
     let inj_deposit: u128 = inj_querier
-        .query_subaccount_deposit(&ob_config.subaccount, &"inj".to_string())
+        .query_subaccount_deposit(
+            &SubaccountId::unchecked(&ob_config.subaccount),
+            &"inj".to_string(),
+        )
         .unwrap()
         .deposits
         .total_balance
         .into();
     let astro_deposit: u128 = inj_querier
-        .query_subaccount_deposit(&ob_config.subaccount, &"astro".to_string())
+        .query_subaccount_deposit(
+            &SubaccountId::unchecked(&ob_config.subaccount),
+            &"astro".to_string(),
+        )
         .unwrap()
         .deposits
         .total_balance
@@ -1212,13 +1339,19 @@ fn check_deactivate_orderbook() {
     let inj_querier = InjectiveQuerier::new(&querier_wrapper);
 
     let inj_deposit: u128 = inj_querier
-        .query_subaccount_deposit(&ob_config.subaccount, &"inj".to_string())
+        .query_subaccount_deposit(
+            &SubaccountId::unchecked(&ob_config.subaccount),
+            &"inj".to_string(),
+        )
         .unwrap()
         .deposits
         .total_balance
         .into();
     let astro_deposit: u128 = inj_querier
-        .query_subaccount_deposit(&ob_config.subaccount, &"astro".to_string())
+        .query_subaccount_deposit(
+            &SubaccountId::unchecked(&ob_config.subaccount),
+            &"astro".to_string(),
+        )
         .unwrap()
         .deposits
         .total_balance
@@ -1267,7 +1400,9 @@ fn check_deactivate_orderbook() {
     let maker_fee = 12870_u128;
     let module_addr = helper.app.init_modules(|router, _, _| {
         let mut deposits = router.custom.deposit.borrow_mut();
-        let subacc = deposits.get_mut(&ob_config.subaccount).unwrap();
+        let subacc = deposits
+            .get_mut(&SubaccountId::unchecked(&ob_config.subaccount))
+            .unwrap();
         subacc[0].amount += Uint128::from(5e18 as u128);
         subacc[1].amount -= Uint128::from(astro_amnt);
 
@@ -1315,6 +1450,21 @@ fn check_deactivate_orderbook() {
 
     let maker_bal_after = helper.coin_balance(&test_coins[1], &helper.maker);
     assert_eq!(maker_bal_after - maker_bal_before, maker_fee);
+
+    // Trade processed during emergency withdraw is recorded in the orderbook history
+    let record = helper.query_ob_history(Some(1)).unwrap().remove(0);
+    assert_eq!(
+        record.trade,
+        Some(OrderbookTrade {
+            is_buy: true,
+            base_amount: Uint128::from(5e18 as u128),
+            quote_amount: Uint128::from(astro_amnt),
+        })
+    );
+    assert_eq!(
+        record.maker_fee,
+        Some(helper.assets[&test_coins[1]].with_balance(maker_fee))
+    );
     let oracle_price_after = helper
         .query_config()
         .unwrap()
@@ -1327,13 +1477,19 @@ fn check_deactivate_orderbook() {
     let querier_wrapper = helper.app.wrap();
     let inj_querier = InjectiveQuerier::new(&querier_wrapper);
     let inj_deposit: u128 = inj_querier
-        .query_subaccount_deposit(&ob_config.subaccount, &"inj".to_string())
+        .query_subaccount_deposit(
+            &SubaccountId::unchecked(&ob_config.subaccount),
+            &"inj".to_string(),
+        )
         .unwrap()
         .deposits
         .total_balance
         .into();
     let astro_deposit: u128 = inj_querier
-        .query_subaccount_deposit(&ob_config.subaccount, &"astro".to_string())
+        .query_subaccount_deposit(
+            &SubaccountId::unchecked(&ob_config.subaccount),
+            &"astro".to_string(),
+        )
         .unwrap()
         .deposits
         .total_balance
@@ -1399,7 +1555,9 @@ fn check_deactivate_orderbook() {
     // Simulate trade after contract was kicked out
     let module_addr = helper.app.init_modules(|router, _, _| {
         let mut deposits = router.custom.deposit.borrow_mut();
-        let subacc = deposits.get_mut(&ob_config.subaccount).unwrap();
+        let subacc = deposits
+            .get_mut(&SubaccountId::unchecked(&ob_config.subaccount))
+            .unwrap();
         subacc[0].amount += Uint128::from(5e18 as u128);
         subacc[1].amount -= Uint128::from(9.9e6 as u128);
 
@@ -1443,6 +1601,20 @@ fn check_deactivate_orderbook() {
 
     let maker_bal_after = helper.coin_balance(&test_coins[1], &helper.maker);
     assert_eq!(maker_bal_after - maker_bal_before, 12908);
+
+    let record = helper.query_ob_history(Some(1)).unwrap().remove(0);
+    assert_eq!(
+        record.trade,
+        Some(OrderbookTrade {
+            is_buy: true,
+            base_amount: Uint128::from(5e18 as u128),
+            quote_amount: Uint128::from(9.9e6 as u128),
+        })
+    );
+    assert_eq!(
+        record.maker_fee,
+        Some(helper.assets[&test_coins[1]].with_balance(12908u128))
+    );
     let oracle_price_after = helper
         .query_config()
         .unwrap()
@@ -1487,7 +1659,9 @@ fn check_deactivate_orderbook() {
     // Simulate trade after contract was kicked out
     let module_addr = helper.app.init_modules(|router, _, _| {
         let mut deposits = router.custom.deposit.borrow_mut();
-        let subacc = deposits.get_mut(&ob_config.subaccount).unwrap();
+        let subacc = deposits
+            .get_mut(&SubaccountId::unchecked(&ob_config.subaccount))
+            .unwrap();
         subacc[0].amount += Uint128::from(5e18 as u128);
         subacc[1].amount -= Uint128::from(9.9e6 as u128);
 
@@ -1530,6 +1704,20 @@ fn check_deactivate_orderbook() {
 
     let maker_bal_after = helper.coin_balance(&test_coins[1], &helper.maker);
     assert_eq!(maker_bal_after - maker_bal_before, 13024);
+
+    let record = helper.query_ob_history(Some(1)).unwrap().remove(0);
+    assert_eq!(
+        record.trade,
+        Some(OrderbookTrade {
+            is_buy: true,
+            base_amount: Uint128::from(5e18 as u128),
+            quote_amount: Uint128::from(9.9e6 as u128),
+        })
+    );
+    assert_eq!(
+        record.maker_fee,
+        Some(helper.assets[&test_coins[1]].with_balance(13024u128))
+    );
     let oracle_price_after = helper
         .query_config()
         .unwrap()
@@ -1599,6 +1787,8 @@ fn test_migrate_cl_to_orderbook_cl() {
                 .to_string(),
             orders_number: 5,
             min_trades_to_avg: 1,
+            liquidity_distribution: None,
+            max_liquidity_share: None,
         },
     };
 
@@ -1625,6 +1815,8 @@ fn test_migrate_cl_to_orderbook_cl() {
             market_id,
             orders_number: 5,
             min_trades_to_avg: 1,
+            liquidity_distribution: None,
+            max_liquidity_share: None,
         },
     };
     helper
@@ -1673,13 +1865,19 @@ fn test_migrate_cl_to_orderbook_cl() {
     let ob_config = helper.query_ob_config().unwrap();
 
     let inj_deposit: u128 = inj_querier
-        .query_subaccount_deposit(&ob_config.subaccount, &"inj".to_string())
+        .query_subaccount_deposit(
+            &SubaccountId::unchecked(&ob_config.subaccount),
+            &"inj".to_string(),
+        )
         .unwrap()
         .deposits
         .total_balance
         .into();
     let astro_deposit: u128 = inj_querier
-        .query_subaccount_deposit(&ob_config.subaccount, &"astro".to_string())
+        .query_subaccount_deposit(
+            &SubaccountId::unchecked(&ob_config.subaccount),
+            &"astro".to_string(),
+        )
         .unwrap()
         .deposits
         .total_balance
@@ -1731,6 +1929,8 @@ fn test_wrong_assets_order() {
             market_id,
             orders_number: 5,
             min_trades_to_avg: *MIN_TRADES_TO_AVG_LIMITS.start(),
+            liquidity_distribution: None,
+            max_liquidity_share: None,
         },
     };
     let new_code_id = helper.app.store_code(orderbook_pair_contract());
@@ -1,17 +1,22 @@
 use crate::error::ContractError;
-use crate::migration::PRICE_LAST_V100;
+use crate::migration::{PRICE_LAST_V100, PRICE_LAST_V2};
 use crate::querier::{query_cumulative_prices, query_prices};
 use crate::state::{
-    get_precision, store_precisions, Config, PriceCumulativeLast, CONFIG, PRICE_LAST,
+    get_precision, store_precisions, Config, PriceCumulativeLast, PriceSnapshot, SnapshotsKeys,
+    CONFIG, PRICE_LAST,
 };
 use astroport::asset::{Asset, AssetInfo};
-use astroport::oracle::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
+use astroport::oracle::{
+    ConsultResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, PriceSource, QueryMsg,
+    DEFAULT_MAX_SNAPSHOTS, DEFAULT_PERIOD, MAX_SNAPSHOTS_LIMIT,
+};
 use astroport::pair::TWAP_PRECISION;
 use astroport::querier::query_pair_info;
+use astroport_circular_buffer::BufferManager;
 
 use cosmwasm_std::{
-    entry_point, to_binary, Binary, Decimal256, Deps, DepsMut, Env, MessageInfo, Response,
-    StdError, StdResult, Uint128, Uint256,
+    attr, entry_point, to_binary, Addr, Binary, Decimal256, Deps, DepsMut, Env, MessageInfo,
+    Response, StdError, StdResult, Storage, Uint128, Uint256,
 };
 use cw2::{get_contract_version, set_contract_version};
 
@@ -20,8 +25,8 @@ const CONTRACT_NAME: &str = "astroport-oracle";
 /// Contract version that is used for migration.
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
-/// Time between two consecutive TWAP updates.
-pub const PERIOD: u64 = 86400;
+/// The minimum amount of snapshots required to calculate a windowed TWAP.
+const MIN_SNAPSHOTS: u32 = 2;
 
 /// Creates a new contract with the specified parameters in the [`InstantiateMsg`].
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -33,6 +38,19 @@ pub fn instantiate(
 ) -> Result<Response, ContractError> {
     let factory_contract = deps.api.addr_validate(&msg.factory_contract)?;
 
+    let period = msg.period.unwrap_or(DEFAULT_PERIOD);
+    if period == 0 {
+        return Err(ContractError::InvalidPeriod {});
+    }
+
+    let max_snapshots = msg.max_snapshots.unwrap_or(DEFAULT_MAX_SNAPSHOTS);
+    if !(MIN_SNAPSHOTS..=MAX_SNAPSHOTS_LIMIT).contains(&max_snapshots) {
+        return Err(ContractError::InvalidMaxSnapshots {
+            min: MIN_SNAPSHOTS,
+            max: MAX_SNAPSHOTS_LIMIT,
+        });
+    }
+
     for asset_info in &msg.asset_infos {
         asset_info.check(deps.api)?;
         store_precisions(deps.branch(), asset_info, &factory_contract)?;
@@ -46,10 +64,24 @@ pub fn instantiate(
         factory: factory_contract,
         asset_infos: msg.asset_infos,
         pair: pair_info.clone(),
+        period,
+        max_snapshots,
     };
     CONFIG.save(deps.storage, &config)?;
 
-    let prices = query_cumulative_prices(deps.querier, pair_info.contract_addr)?;
+    init_pair_prices(deps, &env, &pair_info.contract_addr, max_snapshots)?;
+
+    Ok(Response::default())
+}
+
+/// Starts tracking cumulative prices of the given pair.
+fn init_pair_prices(
+    deps: DepsMut,
+    env: &Env,
+    pair: &Addr,
+    max_snapshots: u32,
+) -> Result<(), ContractError> {
+    let prices = query_cumulative_prices(deps.querier, pair)?;
     let average_prices = prices
         .cumulative_prices
         .iter()
@@ -57,42 +89,110 @@ pub fn instantiate(
         .map(|(from, to, _)| (from, to, Decimal256::zero()))
         .collect();
 
+    let snapshot = PriceSnapshot {
+        cumulative_prices: prices.cumulative_prices.clone(),
+        timestamp: env.block.time.seconds(),
+    };
+    let keys = SnapshotsKeys::new(pair);
+    BufferManager::init(deps.storage, keys.buffer(), max_snapshots)?;
+    BufferManager::new(deps.storage, keys.buffer())?.instant_push(deps.storage, &snapshot)?;
+
     let price = PriceCumulativeLast {
         cumulative_prices: prices.cumulative_prices,
         average_prices,
         block_timestamp_last: env.block.time.seconds(),
     };
-    PRICE_LAST.save(deps.storage, &price)?;
+    PRICE_LAST.save(deps.storage, pair, &price)?;
 
-    Ok(Response::default())
+    Ok(())
+}
+
+/// Returns the address of the given pair or the default oracle pair if none was specified.
+fn resolve_pair(deps: Deps, pair: Option<String>) -> StdResult<Addr> {
+    match pair {
+        Some(pair) => deps.api.addr_validate(&pair),
+        None => Ok(CONFIG.load(deps.storage)?.pair.contract_addr),
+    }
+}
+
+/// Loads the latest prices of a registered pair.
+fn load_price_last(storage: &dyn Storage, pair: &Addr) -> StdResult<PriceCumulativeLast> {
+    PRICE_LAST
+        .may_load(storage, pair)?
+        .ok_or_else(|| StdError::generic_err(format!("Pair {pair} is not registered")))
 }
 
 /// Exposes all the execute functions available in the contract.
 ///
 /// ## Variants
-/// * **ExecuteMsg::Update {}** Updates the local TWAP values for the assets in the Astroport pool.
+/// * **ExecuteMsg::Update { pair }** Updates the local TWAP values for the assets in the Astroport pool.
+///
+/// * **ExecuteMsg::RegisterPair { asset_infos }** Starts tracking the prices of one more pair.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
     env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::Update {} => update(deps, env),
+        ExecuteMsg::Update { pair } => update(deps, env, pair),
+        ExecuteMsg::RegisterPair { asset_infos } => register_pair(deps, env, info, asset_infos),
     }
 }
 
-/// Updates the local TWAP values for the tokens in the target Astroport pool.
-pub fn update(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+/// Registers the factory pair with the specified assets in the oracle.
+/// Only the contract owner can execute this.
+///
+/// * **asset_infos** the assets of the pair to register.
+pub fn register_pair(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset_infos: Vec<AssetInfo>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    for asset_info in &asset_infos {
+        asset_info.check(deps.api)?;
+    }
+
+    let pair_info = query_pair_info(&deps.querier, &config.factory, &asset_infos)?;
+    if PRICE_LAST.has(deps.storage, &pair_info.contract_addr) {
+        return Err(ContractError::PairAlreadyRegistered(
+            pair_info.contract_addr.to_string(),
+        ));
+    }
+
+    for asset_info in &asset_infos {
+        store_precisions(deps.branch(), asset_info, &config.factory)?;
+    }
+
+    init_pair_prices(deps, &env, &pair_info.contract_addr, config.max_snapshots)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "register_pair"),
+        attr("pair", pair_info.contract_addr),
+    ]))
+}
+
+/// Updates the local TWAP values for the tokens in the target Astroport pool
+/// and stores a new cumulative prices snapshot.
+///
+/// * **pair** the registered pair to update. The default oracle pair is updated if none was specified.
+pub fn update(deps: DepsMut, env: Env, pair: Option<String>) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
-    let price_last = PRICE_LAST.load(deps.storage)?;
+    let pair = resolve_pair(deps.as_ref(), pair)?;
+    let price_last = load_price_last(deps.storage, &pair)?;
 
-    let prices = query_cumulative_prices(deps.querier, config.pair.contract_addr)?;
+    let prices = query_cumulative_prices(deps.querier, &pair)?;
     let time_elapsed = env.block.time.seconds() - price_last.block_timestamp_last;
 
     // Ensure that at least one full period has passed since the last update
-    if time_elapsed < PERIOD {
+    if time_elapsed < config.period {
         return Err(ContractError::WrongPeriod {});
     }
 
@@ -112,38 +212,88 @@ pub fn update(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
         }
     }
 
+    let snapshot = PriceSnapshot {
+        cumulative_prices: prices.cumulative_prices.clone(),
+        timestamp: env.block.time.seconds(),
+    };
+    let keys = SnapshotsKeys::new(&pair);
+    BufferManager::new(deps.storage, keys.buffer())?.instant_push(deps.storage, &snapshot)?;
+
     let prices = PriceCumulativeLast {
         cumulative_prices: prices.cumulative_prices,
         average_prices,
         block_timestamp_last: env.block.time.seconds(),
     };
-    PRICE_LAST.save(deps.storage, &prices)?;
+    PRICE_LAST.save(deps.storage, &pair, &prices)?;
     Ok(Response::default())
 }
 
 /// Exposes all the queries available in the contract.
 ///
 /// ## Queries
-/// * **QueryMsg::Consult { token, amount }** Validates assets and calculates a new average
+/// * **QueryMsg::Consult { pair, token, amount }** Validates assets and calculates a new average
 /// amount with updated precision
+///
+/// * **QueryMsg::ConsultWindow { pair, token, amount, window }** Calculates an average amount
+/// over stored snapshots which span at least the given window
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
-        QueryMsg::Consult { token, amount } => to_binary(&consult(deps, token, amount)?),
+        QueryMsg::Consult {
+            pair,
+            token,
+            amount,
+            max_age,
+        } => to_binary(&consult(deps, env, pair, token, amount, max_age)?),
+        QueryMsg::ConsultWindow {
+            pair,
+            token,
+            amount,
+            window,
+            max_age,
+        } => to_binary(&consult_window(
+            deps, env, pair, token, amount, window, max_age,
+        )?),
     }
 }
 
+/// Ensures that prices were recorded no more than `max_age` seconds ago.
+fn assert_not_stale(env: &Env, block_timestamp_last: u64, max_age: Option<u64>) -> StdResult<()> {
+    if let Some(max_age) = max_age {
+        let age = env
+            .block
+            .time
+            .seconds()
+            .saturating_sub(block_timestamp_last);
+        if age > max_age {
+            return Err(StdError::generic_err(format!(
+                "Prices are stale: updated {age} seconds ago while max age is {max_age} seconds"
+            )));
+        }
+    }
+
+    Ok(())
+}
+
 /// Multiplies a token amount by its latest TWAP value.
+/// * **pair** the registered pair to use. The default oracle pair is used if none was specified.
+///
 /// * **token** token for which we multiply its TWAP value by an amount.
 ///
 /// * **amount** amount of tokens we multiply the TWAP by.
+///
+/// * **max_age** the maximum age of the TWAP in seconds. The spot price fallback is disabled if it is set.
 fn consult(
     deps: Deps,
+    env: Env,
+    pair: Option<String>,
     token: AssetInfo,
     amount: Uint128,
-) -> Result<Vec<(AssetInfo, Uint256)>, StdError> {
-    let config = CONFIG.load(deps.storage)?;
-    let price_last = PRICE_LAST.load(deps.storage)?;
+    max_age: Option<u64>,
+) -> StdResult<ConsultResponse> {
+    let pair = resolve_pair(deps, pair)?;
+    let price_last = load_price_last(deps.storage, &pair)?;
+    assert_not_stale(&env, price_last.block_timestamp_last, max_age)?;
 
     let mut average_prices = vec![];
     for (from, to, value) in price_last.average_prices {
@@ -160,13 +310,20 @@ fn consult(
     let p = get_precision(deps.storage, &token)?;
     let one = Uint128::new(10_u128.pow(p.into()));
 
-    average_prices
+    let mut source = PriceSource::Twap;
+    let prices = average_prices
         .iter()
         .map(|(asset, price_average)| {
             if price_average.is_zero() {
+                // The spot price can be manipulated thus callers which limit the TWAP age don't get it
+                if max_age.is_some() {
+                    return Err(StdError::generic_err("TWAP is not available yet"));
+                }
+                source = PriceSource::Spot;
+
                 let price = query_prices(
                     deps.querier,
-                    config.pair.contract_addr.clone(),
+                    pair.clone(),
                     Asset {
                         info: token.clone(),
                         amount: one,
@@ -186,7 +343,92 @@ fn consult(
                 ))
             }
         })
-        .collect::<Result<Vec<(AssetInfo, Uint256)>, StdError>>()
+        .collect::<Result<Vec<(AssetInfo, Uint256)>, StdError>>()?;
+
+    Ok(ConsultResponse {
+        prices,
+        block_timestamp_last: price_last.block_timestamp_last,
+        source,
+    })
+}
+
+/// Multiplies a token amount by its TWAP value calculated over the stored snapshots.
+/// The TWAP is taken between the latest snapshot and the newest snapshot which is
+/// at least `window` seconds older, so the actual span might exceed the window by up to one period.
+/// * **pair** the registered pair to use. The default oracle pair is used if none was specified.
+///
+/// * **token** token for which we multiply its TWAP value by an amount.
+///
+/// * **amount** amount of tokens we multiply the TWAP by.
+///
+/// * **window** the minimum time span in seconds covered by the TWAP.
+///
+/// * **max_age** the maximum age of the latest snapshot in seconds.
+fn consult_window(
+    deps: Deps,
+    env: Env,
+    pair: Option<String>,
+    token: AssetInfo,
+    amount: Uint128,
+    window: u64,
+    max_age: Option<u64>,
+) -> StdResult<ConsultResponse> {
+    if window == 0 {
+        return Err(StdError::generic_err("Window must be greater than zero"));
+    }
+
+    let pair = resolve_pair(deps, pair)?;
+    // Ensure the pair is registered
+    load_price_last(deps.storage, &pair)?;
+
+    let keys = SnapshotsKeys::new(&pair);
+    let snapshots = BufferManager::new(deps.storage, keys.buffer())?.read_all(deps.storage)?;
+    let latest = snapshots
+        .iter()
+        .max_by_key(|snapshot| snapshot.timestamp)
+        .ok_or_else(|| StdError::generic_err("No price snapshots found"))?;
+    assert_not_stale(&env, latest.timestamp, max_age)?;
+    let oldest = snapshots
+        .iter()
+        .filter(|snapshot| snapshot.timestamp + window <= latest.timestamp)
+        .max_by_key(|snapshot| snapshot.timestamp)
+        .ok_or_else(|| {
+            StdError::generic_err(format!(
+                "Not enough price snapshots to cover {window} seconds"
+            ))
+        })?;
+
+    let time_elapsed = latest.timestamp - oldest.timestamp;
+    let price_precision = Uint256::from(10_u128.pow(TWAP_PRECISION.into()));
+
+    let mut prices = vec![];
+    for (from, to, price) in &latest.cumulative_prices {
+        if !from.equal(&token) {
+            continue;
+        }
+        if let Some((_, _, price_old)) = oldest
+            .cumulative_prices
+            .iter()
+            .find(|(from_old, to_old, _)| from_old.equal(from) && to_old.equal(to))
+        {
+            let price_average =
+                Decimal256::from_ratio(Uint256::from(price.wrapping_sub(*price_old)), time_elapsed);
+            prices.push((
+                to.clone(),
+                Uint256::from(amount) * price_average / price_precision,
+            ));
+        }
+    }
+
+    if prices.is_empty() {
+        return Err(StdError::generic_err("Invalid Token"));
+    }
+
+    Ok(ConsultResponse {
+        prices,
+        block_timestamp_last: latest.timestamp,
+        source: PriceSource::Twap,
+    })
 }
 
 /// Manages the contract migration.
@@ -194,7 +436,7 @@ fn consult(
 pub fn migrate(mut deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
     let contract_version = get_contract_version(deps.storage)?;
 
-    match contract_version.contract.as_ref() {
+    let price_last = match contract_version.contract.as_ref() {
         "astroport-oracle" => match contract_version.version.as_ref() {
             "1.0.0" | "1.0.1" | "1.0.2" => {
                 let config = CONFIG.load(deps.storage)?;
@@ -225,22 +467,36 @@ pub fn migrate(mut deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Respons
                     ),
                 ];
 
-                PRICE_LAST.save(
-                    deps.storage,
-                    &PriceCumulativeLast {
-                        cumulative_prices,
-                        average_prices,
-                        block_timestamp_last: price_last_v100.block_timestamp_last,
-                    },
-                )?;
                 for asset_info in &config.asset_infos {
                     store_precisions(deps.branch(), asset_info, &config.factory)?;
                 }
+
+                PriceCumulativeLast {
+                    cumulative_prices,
+                    average_prices,
+                    block_timestamp_last: price_last_v100.block_timestamp_last,
+                }
             }
+            "2.1.1" => PRICE_LAST_V2.load(deps.storage)?,
             _ => return Err(ContractError::MigrationError {}),
         },
         _ => return Err(ContractError::MigrationError {}),
-    }
+    };
+
+    // Move the single pair prices under the pair address
+    // and start the snapshots history from the latest stored cumulative prices
+    let config = CONFIG.load(deps.storage)?;
+    let pair = config.pair.contract_addr;
+    let snapshot = PriceSnapshot {
+        cumulative_prices: price_last.cumulative_prices.clone(),
+        timestamp: price_last.block_timestamp_last,
+    };
+    let keys = SnapshotsKeys::new(&pair);
+    BufferManager::init(deps.storage, keys.buffer(), config.max_snapshots)?;
+    BufferManager::new(deps.storage, keys.buffer())?.instant_push(deps.storage, &snapshot)?;
+
+    PRICE_LAST_V2.remove(deps.storage);
+    PRICE_LAST.save(deps.storage, &pair, &price_last)?;
 
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
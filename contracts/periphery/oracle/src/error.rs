@@ -1,3 +1,4 @@
+use astroport_circular_buffer::error::BufferError;
 use cosmwasm_std::StdError;
 use thiserror::Error;
 
@@ -7,12 +8,24 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    CircularBuffer(#[from] BufferError),
+
     #[error("Unauthorized")]
     Unauthorized {},
 
     #[error("Period not elapsed")]
     WrongPeriod {},
 
+    #[error("Period must be greater than zero")]
+    InvalidPeriod {},
+
+    #[error("The amount of snapshots must be between {min} and {max}")]
+    InvalidMaxSnapshots { min: u32, max: u32 },
+
+    #[error("Pair {0} is already registered")]
+    PairAlreadyRegistered(String),
+
     #[error("Contract can't be migrated!")]
     MigrationError {},
 }
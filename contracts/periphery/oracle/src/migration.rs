@@ -2,6 +2,8 @@ use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{Decimal256, Uint128};
 use cw_storage_plus::Item;
 
+use crate::state::PriceCumulativeLast;
+
 /// This structure stores the latest cumulative and average token prices for the target pool
 #[cw_serde]
 pub struct PriceCumulativeLastV100 {
@@ -17,4 +19,7 @@ pub struct PriceCumulativeLastV100 {
     pub block_timestamp_last: u64,
 }
 
+/// Stores the latest cumulative and average prices of the single oracle pair
+pub const PRICE_LAST_V2: Item<PriceCumulativeLast> = Item::new("price_last");
+
 pub const PRICE_LAST_V100: Item<PriceCumulativeLastV100> = Item::new("price_last");
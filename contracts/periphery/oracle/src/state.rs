@@ -1,13 +1,35 @@
 use cosmwasm_schema::cw_serde;
 
 use astroport::asset::{AssetInfo, PairInfo};
+use astroport::oracle::{DEFAULT_MAX_SNAPSHOTS, DEFAULT_PERIOD};
+use astroport_circular_buffer::CircularBuffer;
 use cosmwasm_std::{Addr, Decimal256, DepsMut, StdResult, Storage, Uint128};
 use cw_storage_plus::{Item, Map};
 
 /// Stores the contract config at the given key
 pub const CONFIG: Item<Config> = Item::new("config");
-/// Stores the latest cumulative and average prices at the given key
-pub const PRICE_LAST: Item<PriceCumulativeLast> = Item::new("price_last");
+/// Stores the latest cumulative and average prices for each registered pair
+pub const PRICE_LAST: Map<&Addr, PriceCumulativeLast> = Map::new("prices_last");
+
+/// Storage keys of the circular buffer which stores cumulative price snapshots of a pair.
+/// Each registered pair has its own buffer.
+pub struct SnapshotsKeys {
+    state_key: String,
+    array_namespace: String,
+}
+
+impl SnapshotsKeys {
+    pub fn new(pair: &Addr) -> Self {
+        Self {
+            state_key: format!("snapshots_state_{pair}"),
+            array_namespace: format!("snapshots_buffer_{pair}"),
+        }
+    }
+
+    pub fn buffer(&self) -> CircularBuffer<PriceSnapshot> {
+        CircularBuffer::new(&self.state_key, &self.array_namespace)
+    }
+}
 
 /// This structure stores the latest cumulative and average token prices for the target pool
 #[cw_serde]
@@ -20,6 +42,15 @@ pub struct PriceCumulativeLast {
     pub block_timestamp_last: u64,
 }
 
+/// This structure stores the pool cumulative prices observed at a specific time
+#[cw_serde]
+pub struct PriceSnapshot {
+    /// The vector contains cumulative prices for each pair of assets in the pool
+    pub cumulative_prices: Vec<(AssetInfo, AssetInfo, Uint128)>,
+    /// The timestamp at which the snapshot was taken
+    pub timestamp: u64,
+}
+
 /// Global configuration for the contract
 #[cw_serde]
 pub struct Config {
@@ -29,8 +60,22 @@ pub struct Config {
     pub factory: Addr,
     /// The assets in the pool. Each asset is described using a [`AssetInfo`]
     pub asset_infos: Vec<AssetInfo>,
-    /// Information about the pair (LP token address, pair type etc)
+    /// Information about the default pair (LP token address, pair type etc)
     pub pair: PairInfo,
+    /// Minimum time in seconds between two consecutive updates
+    #[serde(default = "default_period")]
+    pub period: u64,
+    /// The amount of price snapshots kept for each pair
+    #[serde(default = "default_max_snapshots")]
+    pub max_snapshots: u32,
+}
+
+fn default_period() -> u64 {
+    DEFAULT_PERIOD
+}
+
+fn default_max_snapshots() -> u32 {
+    DEFAULT_MAX_SNAPSHOTS
 }
 
 /// Stores map of AssetInfo (as String) -> precision
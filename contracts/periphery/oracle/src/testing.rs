@@ -1,9 +1,10 @@
-use crate::contract::{execute, instantiate};
-use crate::mock_querier::mock_dependencies;
+use crate::contract::{execute, instantiate, query};
+use crate::error::ContractError;
+use crate::mock_querier::{mock_dependencies, WasmMockQuerier};
 use astroport::asset::{Asset, AssetInfo};
-use astroport::oracle::{ExecuteMsg, InstantiateMsg};
-use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
-use cosmwasm_std::{Addr, Decimal256, Uint128, Uint256};
+use astroport::oracle::{ConsultResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
+use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockStorage, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{from_binary, Addr, Decimal256, OwnedDeps, StdError, Uint128, Uint256};
 use std::ops::Mul;
 
 #[test]
@@ -64,6 +65,8 @@ fn oracle_overflow() {
     let instantiate_msg = InstantiateMsg {
         factory_contract: factory.to_string(),
         asset_infos: vec![astro_asset_info, usdc_asset_info],
+        period: None,
+        max_snapshots: None,
     };
 
     // Set cumulative price to 192738282u128
@@ -105,5 +108,163 @@ fn oracle_overflow() {
         ],
     );
     env.block.time = env.block.time.plus_seconds(86400);
-    execute(deps.as_mut(), env, info, ExecuteMsg::Update {}).unwrap();
+    execute(deps.as_mut(), env, info, ExecuteMsg::Update { pair: None }).unwrap();
+}
+
+#[test]
+fn consult_window() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info("addr0000", &[]);
+
+    let mut env = mock_env();
+    let factory = Addr::unchecked("factory");
+    let astro_token_contract = Addr::unchecked("astro-token");
+    let usdc_token_contract = Addr::unchecked("usdc-token");
+
+    deps.querier.with_token_balances(&[
+        (
+            &astro_token_contract.to_string(),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::new(10000))],
+        ),
+        (
+            &usdc_token_contract.to_string(),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::new(10000))],
+        ),
+    ]);
+
+    let astro_asset_info = AssetInfo::Token {
+        contract_addr: astro_token_contract,
+    };
+    let usdc_asset_info = AssetInfo::Token {
+        contract_addr: usdc_token_contract,
+    };
+    let assets = vec![
+        Asset {
+            info: astro_asset_info.clone(),
+            amount: Uint128::zero(),
+        },
+        Asset {
+            info: usdc_asset_info.clone(),
+            amount: Uint128::zero(),
+        },
+    ];
+
+    let set_cumulative_prices = |deps: &mut OwnedDeps<MockStorage, MockApi, WasmMockQuerier>,
+                                 price: u128| {
+        deps.querier.set_cumulative_price(
+            Addr::unchecked("pair"),
+            assets.clone(),
+            Uint128::zero(),
+            vec![
+                (
+                    astro_asset_info.clone(),
+                    usdc_asset_info.clone(),
+                    Uint128::new(price),
+                ),
+                (
+                    usdc_asset_info.clone(),
+                    astro_asset_info.clone(),
+                    Uint128::new(price / 4),
+                ),
+            ],
+        )
+    };
+    set_cumulative_prices(&mut deps, 0);
+
+    let mut instantiate_msg = InstantiateMsg {
+        factory_contract: factory.to_string(),
+        asset_infos: vec![astro_asset_info.clone(), usdc_asset_info.clone()],
+        period: Some(0),
+        max_snapshots: Some(3),
+    };
+    let err = instantiate(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        instantiate_msg.clone(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::InvalidPeriod {}));
+
+    instantiate_msg.period = Some(1800);
+    instantiate_msg.max_snapshots = Some(1);
+    let err = instantiate(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        instantiate_msg.clone(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::InvalidMaxSnapshots { .. }));
+
+    instantiate_msg.max_snapshots = Some(3);
+    instantiate(deps.as_mut(), env.clone(), info.clone(), instantiate_msg).unwrap();
+
+    let consult_window = |deps: &OwnedDeps<MockStorage, MockApi, WasmMockQuerier>, window: u64| {
+        query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ConsultWindow {
+                token: astro_asset_info.clone(),
+                amount: Uint128::new(100),
+                window,
+                pair: None,
+                max_age: None,
+            },
+        )
+        .map(|res| from_binary::<ConsultResponse>(&res).unwrap().prices[0].1)
+    };
+
+    // Only the initial snapshot is available
+    let err = consult_window(&deps, 1800).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Not enough price snapshots to cover 1800 seconds")
+    );
+
+    // The price is 2 during the first period
+    env.block.time = env.block.time.plus_seconds(1800);
+    set_cumulative_prices(&mut deps, 1800 * 2_000000);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::Update { pair: None },
+    )
+    .unwrap();
+
+    // Updates are allowed once per period
+    env.block.time = env.block.time.plus_seconds(1000);
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::Update { pair: None },
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::WrongPeriod {}));
+
+    // The price is 4 during the second period
+    env.block.time = env.block.time.plus_seconds(800);
+    set_cumulative_prices(&mut deps, 1800 * 6_000000);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::Update { pair: None },
+    )
+    .unwrap();
+
+    assert_eq!(consult_window(&deps, 1800).unwrap(), Uint256::from(400u16));
+    assert_eq!(consult_window(&deps, 3600).unwrap(), Uint256::from(300u16));
+    // The closest snapshot which covers the window is taken
+    assert_eq!(consult_window(&deps, 1000).unwrap(), Uint256::from(400u16));
+
+    // The price is 4 during the third period. The initial snapshot is overwritten
+    env.block.time = env.block.time.plus_seconds(1800);
+    set_cumulative_prices(&mut deps, 1800 * 10_000000);
+    execute(deps.as_mut(), env, info, ExecuteMsg::Update { pair: None }).unwrap();
+
+    assert_eq!(consult_window(&deps, 3600).unwrap(), Uint256::from(400u16));
+    consult_window(&deps, 5400).unwrap_err();
 }
@@ -1,6 +1,7 @@
 use anyhow::Result;
 use cosmwasm_std::{
-    attr, to_binary, Addr, BlockInfo, Coin, Decimal, QueryRequest, StdResult, Uint128, WasmQuery,
+    attr, to_binary, Addr, BlockInfo, Coin, Decimal, QueryRequest, StdResult, Uint128, Uint256,
+    WasmQuery,
 };
 use cw20::{BalanceResponse, Cw20QueryMsg, MinterResponse};
 use cw_multi_test::{App, AppResponse, ContractWrapper, Executor};
@@ -12,7 +13,7 @@ use astroport::token::InstantiateMsg as TokenInstantiateMsg;
 use astroport::factory::{PairConfig, PairType};
 
 use astroport::oracle::QueryMsg::Consult;
-use astroport::oracle::{ExecuteMsg, InstantiateMsg};
+use astroport::oracle::{ConsultResponse, ExecuteMsg, InstantiateMsg, PriceSource};
 use astroport::pair::StablePoolParams;
 
 const OWNER: &str = "owner";
@@ -637,6 +638,8 @@ fn consult() {
     let msg = InstantiateMsg {
         factory_contract: factory_instance.to_string(),
         asset_infos: asset_infos.clone(),
+        period: None,
+        max_snapshots: None,
     };
     let oracle_instance = router
         .instantiate_contract(
@@ -653,7 +656,7 @@ fn consult() {
         .execute_contract(
             owner.clone(),
             oracle_instance.clone(),
-            &ExecuteMsg::Update {},
+            &ExecuteMsg::Update { pair: None },
             &[],
         )
         .unwrap_err();
@@ -676,7 +679,7 @@ fn consult() {
         .execute_contract(
             owner.clone(),
             oracle_instance.clone(),
-            &ExecuteMsg::Update {},
+            &ExecuteMsg::Update { pair: None },
             &[],
         )
         .unwrap();
@@ -690,15 +693,17 @@ fn consult() {
                 contract_addr: addr,
             },
             amount,
+            pair: None,
+            max_age: None,
         };
-        let res: Vec<(AssetInfo, Uint128)> = router
+        let res: ConsultResponse = router
             .wrap()
             .query(&QueryRequest::Wasm(WasmQuery::Smart {
                 contract_addr: oracle_instance.to_string(),
                 msg: to_binary(&msg).unwrap(),
             }))
             .unwrap();
-        assert_eq!(res[0].1, amount);
+        assert_eq!(res.prices[0].1, Uint256::from(amount));
     }
 }
 
@@ -774,6 +779,8 @@ fn consult_pair_stable() {
     let msg = InstantiateMsg {
         factory_contract: factory_instance.to_string(),
         asset_infos: asset_infos.clone(),
+        period: None,
+        max_snapshots: None,
     };
     let oracle_instance = router
         .instantiate_contract(
@@ -790,7 +797,7 @@ fn consult_pair_stable() {
         .execute_contract(
             owner.clone(),
             oracle_instance.clone(),
-            &ExecuteMsg::Update {},
+            &ExecuteMsg::Update { pair: None },
             &[],
         )
         .unwrap_err();
@@ -819,7 +826,7 @@ fn consult_pair_stable() {
         .execute_contract(
             owner.clone(),
             oracle_instance.clone(),
-            &ExecuteMsg::Update {},
+            &ExecuteMsg::Update { pair: None },
             &[],
         )
         .unwrap();
@@ -833,15 +840,17 @@ fn consult_pair_stable() {
                 contract_addr: addr,
             },
             amount,
+            pair: None,
+            max_age: None,
         };
-        let res: Vec<(AssetInfo, Uint128)> = router
+        let res: ConsultResponse = router
             .wrap()
             .query(&QueryRequest::Wasm(WasmQuery::Smart {
                 contract_addr: oracle_instance.to_string(),
                 msg: to_binary(&msg).unwrap(),
             }))
             .unwrap();
-        assert_eq!(res[0].1, amount);
+        assert_eq!(res.prices[0].1, Uint256::from(amount));
     }
 }
 
@@ -968,6 +977,8 @@ fn consult2() {
     let msg = InstantiateMsg {
         factory_contract: factory_instance.to_string(),
         asset_infos: asset_infos.clone(),
+        period: None,
+        max_snapshots: None,
     };
     let oracle_instance = router
         .instantiate_contract(
@@ -984,7 +995,7 @@ fn consult2() {
         .execute_contract(
             owner.clone(),
             oracle_instance.clone(),
-            &ExecuteMsg::Update {},
+            &ExecuteMsg::Update { pair: None },
             &[],
         )
         .unwrap_err();
@@ -994,7 +1005,7 @@ fn consult2() {
         .execute_contract(
             owner.clone(),
             oracle_instance.clone(),
-            &ExecuteMsg::Update {},
+            &ExecuteMsg::Update { pair: None },
             &[],
         )
         .unwrap();
@@ -1019,7 +1030,7 @@ fn consult2() {
             .execute_contract(
                 owner.clone(),
                 oracle_instance.clone(),
-                &ExecuteMsg::Update {},
+                &ExecuteMsg::Update { pair: None },
                 &[],
             )
             .unwrap();
@@ -1041,15 +1052,17 @@ fn consult2() {
                 contract_addr: addr,
             },
             amount,
+            pair: None,
+            max_age: None,
         };
-        let res: Vec<(AssetInfo, Uint128)> = router
+        let res: ConsultResponse = router
             .wrap()
             .query(&QueryRequest::Wasm(WasmQuery::Smart {
                 contract_addr: oracle_instance.to_string(),
                 msg: to_binary(&msg).unwrap(),
             }))
             .unwrap();
-        assert_eq!(res[0].1, amount_exp);
+        assert_eq!(res.prices[0].1, Uint256::from(amount_exp));
     }
 
     // Change pair liquidity
@@ -1072,7 +1085,7 @@ fn consult2() {
             .execute_contract(
                 owner.clone(),
                 oracle_instance.clone(),
-                &ExecuteMsg::Update {},
+                &ExecuteMsg::Update { pair: None },
                 &[],
             )
             .unwrap();
@@ -1094,15 +1107,17 @@ fn consult2() {
                 contract_addr: addr,
             },
             amount,
+            pair: None,
+            max_age: None,
         };
-        let res: Vec<(AssetInfo, Uint128)> = router
+        let res: ConsultResponse = router
             .wrap()
             .query(&QueryRequest::Wasm(WasmQuery::Smart {
                 contract_addr: oracle_instance.to_string(),
                 msg: to_binary(&msg).unwrap(),
             }))
             .unwrap();
-        assert_eq!(res[0].1, amount_exp);
+        assert_eq!(res.prices[0].1, Uint256::from(amount_exp));
     }
 }
 
@@ -1182,6 +1197,8 @@ fn consult_zero_price() {
     let msg = InstantiateMsg {
         factory_contract: factory_instance.to_string(),
         asset_infos: asset_infos.clone(),
+        period: None,
+        max_snapshots: None,
     };
     let oracle_instance = router
         .instantiate_contract(
@@ -1198,7 +1215,7 @@ fn consult_zero_price() {
         .execute_contract(
             owner.clone(),
             oracle_instance.clone(),
-            &ExecuteMsg::Update {},
+            &ExecuteMsg::Update { pair: None },
             &[],
         )
         .unwrap_err();
@@ -1208,7 +1225,7 @@ fn consult_zero_price() {
         .execute_contract(
             owner.clone(),
             oracle_instance.clone(),
-            &ExecuteMsg::Update {},
+            &ExecuteMsg::Update { pair: None },
             &[],
         )
         .unwrap();
@@ -1230,15 +1247,17 @@ fn consult_zero_price() {
                 contract_addr: addr,
             },
             amount: amount_in,
+            pair: None,
+            max_age: None,
         };
-        let res: Vec<(AssetInfo, Uint128)> = router
+        let res: ConsultResponse = router
             .wrap()
             .query(&QueryRequest::Wasm(WasmQuery::Smart {
                 contract_addr: oracle_instance.to_string(),
                 msg: to_binary(&msg).unwrap(),
             }))
             .unwrap();
-        assert_eq!(res[0].1, amount_out);
+        assert_eq!(res.prices[0].1, Uint256::from(amount_out));
     }
 
     let res: StdResult<Uint128> = router.wrap().query_wasm_smart(
@@ -1248,6 +1267,8 @@ fn consult_zero_price() {
                 denom: "uusd".to_string(),
             },
             amount: Default::default(),
+            pair: None,
+            max_age: None,
         },
     );
     assert_eq!(
@@ -1308,6 +1329,8 @@ fn consult_zero_price() {
             &InstantiateMsg {
                 factory_contract: factory_instance.to_string(),
                 asset_infos: asset_infos.clone(),
+                period: None,
+                max_snapshots: None,
             },
             &[],
             String::from("ORACLE 2"),
@@ -1315,18 +1338,20 @@ fn consult_zero_price() {
         )
         .unwrap();
 
-    let res: Vec<(AssetInfo, Uint128)> = router
+    let res: ConsultResponse = router
         .wrap()
         .query_wasm_smart(
             &oracle_instance,
             &Consult {
                 token: asset_infos[1].clone(),
                 amount: Uint128::from(1u8),
+                pair: None,
+                max_age: None,
             },
         )
         .unwrap();
     // Price is too small thus we get zero
-    assert_eq!(res[0].1.u128(), 0u128);
+    assert_eq!(res.prices[0].1, Uint256::zero());
 }
 
 #[ignore]
@@ -1420,6 +1445,8 @@ fn consult_multiple_assets() {
     let msg = InstantiateMsg {
         factory_contract: factory_instance.to_string(),
         asset_infos: asset_infos.clone(),
+        period: None,
+        max_snapshots: None,
     };
     let oracle_instance = router
         .instantiate_contract(
@@ -1436,7 +1463,7 @@ fn consult_multiple_assets() {
         .execute_contract(
             owner.clone(),
             oracle_instance.clone(),
-            &ExecuteMsg::Update {},
+            &ExecuteMsg::Update { pair: None },
             &[],
         )
         .unwrap_err();
@@ -1446,7 +1473,7 @@ fn consult_multiple_assets() {
         .execute_contract(
             owner.clone(),
             oracle_instance.clone(),
-            &ExecuteMsg::Update {},
+            &ExecuteMsg::Update { pair: None },
             &[],
         )
         .unwrap();
@@ -1480,7 +1507,7 @@ fn consult_multiple_assets() {
             .execute_contract(
                 owner.clone(),
                 oracle_instance.clone(),
-                &ExecuteMsg::Update {},
+                &ExecuteMsg::Update { pair: None },
                 &[],
             )
             .unwrap();
@@ -1546,15 +1573,23 @@ fn consult_multiple_assets() {
                 contract_addr: addr,
             },
             amount,
+            pair: None,
+            max_age: None,
         };
-        let res: Vec<(AssetInfo, Uint128)> = router
+        let res: ConsultResponse = router
             .wrap()
             .query(&QueryRequest::Wasm(WasmQuery::Smart {
                 contract_addr: oracle_instance.to_string(),
                 msg: to_binary(&msg).unwrap(),
             }))
             .unwrap();
-        assert_eq!(res, amounts_exp);
+        assert_eq!(
+            res.prices,
+            amounts_exp
+                .into_iter()
+                .map(|(asset, amount)| (asset, Uint256::from(amount)))
+                .collect::<Vec<_>>()
+        );
     }
 
     // Change pair liquidity
@@ -1591,7 +1626,7 @@ fn consult_multiple_assets() {
             .execute_contract(
                 owner.clone(),
                 oracle_instance.clone(),
-                &ExecuteMsg::Update {},
+                &ExecuteMsg::Update { pair: None },
                 &[],
             )
             .unwrap();
@@ -1657,14 +1692,268 @@ fn consult_multiple_assets() {
                 contract_addr: addr,
             },
             amount,
+            pair: None,
+            max_age: None,
         };
-        let res: Vec<(AssetInfo, Uint128)> = router
+        let res: ConsultResponse = router
             .wrap()
             .query(&QueryRequest::Wasm(WasmQuery::Smart {
                 contract_addr: oracle_instance.to_string(),
                 msg: to_binary(&msg).unwrap(),
             }))
             .unwrap();
-        assert_eq!(res, amount_exp);
+        assert_eq!(
+            res.prices,
+            amount_exp
+                .into_iter()
+                .map(|(asset, amount)| (asset, Uint256::from(amount)))
+                .collect::<Vec<_>>()
+        );
+    }
+}
+
+#[test]
+fn register_pair() {
+    let mut router = mock_app(None, None);
+    let owner = Addr::unchecked("owner");
+    let user = Addr::unchecked("user0000");
+    let (astro_token_instance, factory_instance, oracle_code_id) =
+        instantiate_contracts(&mut router, owner.clone());
+
+    let mut pairs = vec![];
+    for (name, symbol, amount) in [
+        ("Usdc token", "USDC", 200_000_u128),
+        ("Usdt token", "USDT", 400_000_u128),
+    ] {
+        let token_instance = instantiate_token(
+            &mut router,
+            owner.clone(),
+            name.to_string(),
+            symbol.to_string(),
+        );
+        let assets = vec![
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: astro_token_instance.clone(),
+                },
+                amount: Uint128::from(100_000_u128),
+            },
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: token_instance,
+                },
+                amount: Uint128::from(amount),
+            },
+        ];
+        let pair_info = create_pair(
+            &mut router,
+            owner.clone(),
+            user.clone(),
+            &factory_instance,
+            assets.clone(),
+        );
+        provide_liquidity(
+            &mut router,
+            owner.clone(),
+            user.clone(),
+            &pair_info,
+            assets.clone(),
+        )
+        .unwrap();
+        pairs.push((pair_info, assets));
+    }
+
+    let msg = InstantiateMsg {
+        factory_contract: factory_instance.to_string(),
+        asset_infos: vec![pairs[0].1[0].info.clone(), pairs[0].1[1].info.clone()],
+        period: None,
+        max_snapshots: None,
+    };
+    let oracle_instance = router
+        .instantiate_contract(
+            oracle_code_id,
+            owner.clone(),
+            &msg,
+            &[],
+            String::from("ORACLE"),
+            None,
+        )
+        .unwrap();
+
+    let register_msg = ExecuteMsg::RegisterPair {
+        asset_infos: vec![pairs[1].1[0].info.clone(), pairs[1].1[1].info.clone()],
+    };
+    let err = router
+        .execute_contract(user.clone(), oracle_instance.clone(), &register_msg, &[])
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "Unauthorized");
+
+    router
+        .execute_contract(owner.clone(), oracle_instance.clone(), &register_msg, &[])
+        .unwrap();
+    let err = router
+        .execute_contract(owner.clone(), oracle_instance.clone(), &register_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        format!("Pair {} is already registered", pairs[1].0.contract_addr)
+    );
+
+    router.update_block(next_day);
+
+    // The default pair is updated when no pair is specified
+    router
+        .execute_contract(
+            owner.clone(),
+            oracle_instance.clone(),
+            &ExecuteMsg::Update { pair: None },
+            &[],
+        )
+        .unwrap();
+    router
+        .execute_contract(
+            owner.clone(),
+            oracle_instance.clone(),
+            &ExecuteMsg::Update {
+                pair: Some(pairs[1].0.contract_addr.to_string()),
+            },
+            &[],
+        )
+        .unwrap();
+
+    for (pair, expected) in [
+        (None, 200u128),
+        (Some(pairs[0].0.contract_addr.to_string()), 200),
+        (Some(pairs[1].0.contract_addr.to_string()), 400),
+    ] {
+        let res: ConsultResponse = router
+            .wrap()
+            .query_wasm_smart(
+                &oracle_instance,
+                &Consult {
+                    pair,
+                    token: AssetInfo::Token {
+                        contract_addr: astro_token_instance.clone(),
+                    },
+                    amount: Uint128::from(100u128),
+                    max_age: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(res.prices[0].1, Uint256::from(expected));
     }
+
+    let err = router
+        .wrap()
+        .query_wasm_smart::<ConsultResponse>(
+            &oracle_instance,
+            &Consult {
+                pair: Some(factory_instance.to_string()),
+                token: AssetInfo::Token {
+                    contract_addr: astro_token_instance.clone(),
+                },
+                amount: Uint128::from(100u128),
+                max_age: None,
+            },
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains("is not registered"));
+}
+
+#[test]
+fn consult_staleness() {
+    let mut router = mock_app(None, None);
+    let owner = Addr::unchecked("owner");
+    let user = Addr::unchecked("user0000");
+    let (astro_token_instance, factory_instance, oracle_code_id) =
+        instantiate_contracts(&mut router, owner.clone());
+
+    let usdc_token_instance = instantiate_token(
+        &mut router,
+        owner.clone(),
+        "Usdc token".to_string(),
+        "USDC".to_string(),
+    );
+
+    let assets = vec![
+        Asset {
+            info: AssetInfo::Token {
+                contract_addr: astro_token_instance.clone(),
+            },
+            amount: Uint128::from(100_000_u128),
+        },
+        Asset {
+            info: AssetInfo::Token {
+                contract_addr: usdc_token_instance,
+            },
+            amount: Uint128::from(200_000_u128),
+        },
+    ];
+    let pair_info = create_pair(
+        &mut router,
+        owner.clone(),
+        user.clone(),
+        &factory_instance,
+        assets.clone(),
+    );
+    provide_liquidity(&mut router, owner.clone(), user, &pair_info, assets.clone()).unwrap();
+
+    let msg = InstantiateMsg {
+        factory_contract: factory_instance.to_string(),
+        asset_infos: vec![assets[0].info.clone(), assets[1].info.clone()],
+        period: None,
+        max_snapshots: None,
+    };
+    let oracle_instance = router
+        .instantiate_contract(
+            oracle_code_id,
+            owner.clone(),
+            &msg,
+            &[],
+            String::from("ORACLE"),
+            None,
+        )
+        .unwrap();
+
+    let consult = |router: &App, max_age: Option<u64>| {
+        router.wrap().query_wasm_smart::<ConsultResponse>(
+            &oracle_instance,
+            &Consult {
+                pair: None,
+                token: assets[0].info.clone(),
+                amount: Uint128::from(100u128),
+                max_age,
+            },
+        )
+    };
+
+    // No TWAP is available right after instantiation thus the spot price is returned
+    let res = consult(&router, None).unwrap();
+    assert_eq!(res.source, PriceSource::Spot);
+    let err = consult(&router, Some(86400)).unwrap_err();
+    assert!(err.to_string().contains("TWAP is not available yet"));
+
+    router.update_block(next_day);
+    router
+        .execute_contract(
+            owner.clone(),
+            oracle_instance.clone(),
+            &ExecuteMsg::Update { pair: None },
+            &[],
+        )
+        .unwrap();
+
+    let res = consult(&router, Some(3600)).unwrap();
+    assert_eq!(res.source, PriceSource::Twap);
+    assert_eq!(res.prices[0].1, Uint256::from(200u128));
+    assert_eq!(res.block_timestamp_last, router.block_info().time.seconds());
+
+    // The TWAP becomes stale if the oracle isn't updated
+    router.update_block(next_day);
+    let err = consult(&router, Some(3600)).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Prices are stale: updated 86400 seconds ago while max age is 3600 seconds"));
+    let res = consult(&router, None).unwrap();
+    assert_eq!(res.source, PriceSource::Twap);
 }
@@ -1,23 +1,30 @@
 use std::collections::HashSet;
 
 use cosmwasm_std::{
-    entry_point, from_binary, to_binary, Addr, Api, Binary, CosmosMsg, Decimal, Deps, DepsMut, Env,
-    MessageInfo, Response, StdError, StdResult, Uint128, WasmMsg,
+    attr, coin, entry_point, from_binary, to_binary, Addr, Api, Binary, CosmosMsg, Decimal, Deps,
+    DepsMut, Env, MessageInfo, Response, StdError, StdResult, Uint128, WasmMsg,
 };
 use cw2::{get_contract_version, set_contract_version};
 use cw20::Cw20ReceiveMsg;
 
 use astroport::asset::{addr_opt_validate, Asset, AssetInfo};
-use astroport::pair::{QueryMsg as PairQueryMsg, SimulationResponse};
+use astroport::factory::UpdateAddr;
+use astroport::pair::{QueryMsg as PairQueryMsg, ReverseSimulationResponse, SimulationResponse};
 use astroport::querier::query_pair_info;
 use astroport::router::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg,
-    SimulateSwapOperationsResponse, SwapOperation, MAX_SWAP_OPERATIONS,
+    BestRouteResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg, HopSimulation, InstantiateMsg,
+    MigrateMsg, NativeSwapHandlerQueryMsg, NativeSwapReverseSimulationResponse,
+    NativeSwapSimulationResponse, QueryMsg, ReverseSimulateSwapOperationsResponse,
+    SimulateSplitSwapResponse, SimulateSwapOperationsDetailedResponse,
+    SimulateSwapOperationsResponse, SwapOperation, DEFAULT_MAX_REFERRAL_COMMISSION,
+    MAX_SPLIT_ROUTES, MAX_SWAP_OPERATIONS,
 };
 
 use crate::error::ContractError;
-use crate::operations::execute_swap_operation;
-use crate::state::{Config, CONFIG};
+use crate::operations::{asset_into_swap_msg, execute_swap_operation, native_swap_msg};
+use crate::routes::{find_best_route, validate_max_hops};
+use crate::state::{Config, CONFIG, CONFIG_V111};
+use crate::zap::{execute_zap_in, execute_zap_out, zap_in_provide, zap_in_swap, zap_out_swap};
 
 /// Contract name that is used for migration.
 const CONTRACT_NAME: &str = "astroport-router";
@@ -38,12 +45,27 @@ pub fn instantiate(
         deps.storage,
         &Config {
             astroport_factory: deps.api.addr_validate(&msg.astroport_factory)?,
+            owner: deps.api.addr_validate(&msg.owner)?,
+            native_swap_handler: addr_opt_validate(deps.api, &msg.native_swap_handler)?,
+            max_referral_commission: validate_max_referral_commission(
+                msg.max_referral_commission
+                    .unwrap_or(DEFAULT_MAX_REFERRAL_COMMISSION),
+            )?,
         },
     )?;
 
     Ok(Response::default())
 }
 
+/// Validates the maximum referral commission.
+fn validate_max_referral_commission(commission: Decimal) -> Result<Decimal, ContractError> {
+    if commission >= Decimal::one() {
+        return Err(ContractError::InvalidMaxReferralCommission {});
+    }
+
+    Ok(commission)
+}
+
 /// Exposes all the execute functions available in the contract.
 ///
 /// ## Variants
@@ -53,9 +75,48 @@ pub fn instantiate(
 /// * **ExecuteMsg::ExecuteSwapOperations {
 ///             operations,
 ///             minimum_receive,
-///             to
+///             to,
+///             max_spread,
+///             referral
 ///         }** Performs swap operations with the specified parameters.
 ///
+/// * **ExecuteMsg::ExecuteBestRoute {
+///             ask_asset_info,
+///             max_hops,
+///             minimum_receive,
+///             to,
+///             max_spread
+///         }** Swaps native coins through the route with the highest simulated output.
+///
+/// * **ExecuteMsg::ExecuteSwapOperationsExactOut {
+///             operations,
+///             ask_amount,
+///             max_offer,
+///             to,
+///             max_spread
+///         }** Swaps native coins so the recipient receives the specified ask amount. Leftover coins are refunded.
+///
+/// * **ExecuteMsg::ExecuteSplitSwap {
+///             routes,
+///             minimum_receive,
+///             to,
+///             max_spread
+///         }** Swaps native coins split across parallel routes.
+///
+/// * **ExecuteMsg::ZapIn {
+///             pair_addr,
+///             operations,
+///             slippage_tolerance,
+///             auto_stake,
+///             receiver,
+///             max_spread
+///         }** Swaps native coins into the pair assets and provides liquidity.
+///
+/// * **ExecuteMsg::UpdateConfig {
+///             native_swap_handler,
+///             max_referral_commission
+///         }** Updates the contract parameters. Only the owner can execute this.
+///
 /// * **ExecuteMsg::ExecuteSwapOperation { operation, to }** Execute a single swap operation.
 ///
 /// * **ExecuteMsg::AssertMinimumReceive {
@@ -72,20 +133,104 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::Receive(msg) => receive_cw20(deps, env, msg),
+        ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
         ExecuteMsg::ExecuteSwapOperations {
             operations,
             minimum_receive,
             to,
             max_spread,
-        } => execute_swap_operations(
+            referral,
+        } => {
+            // The offer amount is only required to calculate the referral commission
+            let offer_amount = match operations.first().map(|op| op.get_offer_asset_info()) {
+                Some(AssetInfo::NativeToken { denom }) => info
+                    .funds
+                    .iter()
+                    .find(|coin| coin.denom == denom)
+                    .map(|coin| coin.amount)
+                    .unwrap_or_default(),
+                _ => Uint128::zero(),
+            };
+
+            execute_swap_operations(
+                deps,
+                env,
+                info.sender,
+                operations,
+                offer_amount,
+                minimum_receive,
+                to,
+                max_spread,
+                referral,
+            )
+        }
+        ExecuteMsg::ExecuteBestRoute {
+            ask_asset_info,
+            max_hops,
+            minimum_receive,
+            to,
+            max_spread,
+        } => execute_best_route(
             deps,
             env,
-            info.sender,
+            info.sender.clone(),
+            native_offer_asset(&info)?,
+            ask_asset_info,
+            max_hops,
+            minimum_receive,
+            to,
+            max_spread,
+        ),
+        ExecuteMsg::ExecuteSwapOperationsExactOut {
             operations,
+            ask_amount,
+            max_offer,
+            to,
+            max_spread,
+        } => execute_swap_operations_exact_out(
+            deps,
+            env,
+            info.sender.clone(),
+            native_offer_asset(&info)?,
+            operations,
+            ask_amount,
+            max_offer,
+            to,
+            max_spread,
+        ),
+        ExecuteMsg::ExecuteSplitSwap {
+            routes,
             minimum_receive,
             to,
             max_spread,
+        } => execute_split_swap(
+            deps,
+            env,
+            info.sender.clone(),
+            native_offer_asset(&info)?,
+            routes,
+            minimum_receive,
+            to,
+            max_spread,
+        ),
+        ExecuteMsg::ZapIn {
+            pair_addr,
+            operations,
+            slippage_tolerance,
+            auto_stake,
+            receiver,
+            max_spread,
+        } => execute_zap_in(
+            deps,
+            env,
+            info.sender.clone(),
+            native_offer_asset(&info)?,
+            pair_addr,
+            operations,
+            slippage_tolerance,
+            auto_stake,
+            receiver,
+            max_spread,
         ),
         ExecuteMsg::ExecuteSwapOperation {
             operation,
@@ -105,15 +250,89 @@ pub fn execute(
             minimum_receive,
             deps.api.addr_validate(&receiver)?,
         ),
+        ExecuteMsg::ZapInSwap {
+            pair_addr,
+            offer_asset_info,
+            max_spread,
+        } => zap_in_swap(deps, env, info, pair_addr, offer_asset_info, max_spread),
+        ExecuteMsg::ZapInProvide {
+            pair_addr,
+            slippage_tolerance,
+            auto_stake,
+            receiver,
+        } => zap_in_provide(
+            deps,
+            env,
+            info,
+            pair_addr,
+            slippage_tolerance,
+            auto_stake,
+            receiver,
+        ),
+        ExecuteMsg::ZapOutSwap {
+            pair_addr,
+            ask_asset_info,
+            to,
+            max_spread,
+        } => zap_out_swap(deps, env, info, pair_addr, ask_asset_info, to, max_spread),
+        ExecuteMsg::UpdateConfig {
+            native_swap_handler,
+            max_referral_commission,
+        } => update_config(deps, info, native_swap_handler, max_referral_commission),
     }
 }
 
+/// Updates the contract parameters. Only the owner can execute this.
+///
+/// * **native_swap_handler** sets or removes the contract which performs native swap operations.
+///
+/// * **max_referral_commission** sets the maximum commission a referrer can take from the offer amount.
+fn update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    native_swap_handler: Option<UpdateAddr>,
+    max_referral_commission: Option<Decimal>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut attributes = vec![attr("action", "update_config")];
+
+    match native_swap_handler {
+        Some(UpdateAddr::Set(handler)) => {
+            config.native_swap_handler = Some(deps.api.addr_validate(&handler)?);
+            attributes.push(attr("native_swap_handler", handler));
+        }
+        Some(UpdateAddr::Remove {}) => {
+            config.native_swap_handler = None;
+            attributes.push(attr("native_swap_handler", "removed"));
+        }
+        None => {}
+    }
+
+    if let Some(max_referral_commission) = max_referral_commission {
+        config.max_referral_commission = validate_max_referral_commission(max_referral_commission)?;
+        attributes.push(attr(
+            "max_referral_commission",
+            max_referral_commission.to_string(),
+        ));
+    }
+
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
 /// Receives a message of type [`Cw20ReceiveMsg`] and processes it depending on the received template.
 ///
 /// * **cw20_msg** is an object of type [`Cw20ReceiveMsg`].
 pub fn receive_cw20(
     deps: DepsMut,
     env: Env,
+    info: MessageInfo,
     cw20_msg: Cw20ReceiveMsg,
 ) -> Result<Response, ContractError> {
     match from_binary(&cw20_msg.msg)? {
@@ -122,36 +341,213 @@ pub fn receive_cw20(
             minimum_receive,
             to,
             max_spread,
+            referral,
         } => execute_swap_operations(
             deps,
             env,
             Addr::unchecked(cw20_msg.sender),
             operations,
+            cw20_msg.amount,
             minimum_receive,
             to,
             max_spread,
+            referral,
         ),
+        Cw20HookMsg::ExecuteBestRoute {
+            ask_asset_info,
+            max_hops,
+            minimum_receive,
+            to,
+            max_spread,
+        } => execute_best_route(
+            deps,
+            env,
+            Addr::unchecked(cw20_msg.sender),
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: info.sender,
+                },
+                amount: cw20_msg.amount,
+            },
+            ask_asset_info,
+            max_hops,
+            minimum_receive,
+            to,
+            max_spread,
+        ),
+        Cw20HookMsg::ExecuteSwapOperationsExactOut {
+            operations,
+            ask_amount,
+            max_offer,
+            to,
+            max_spread,
+        } => execute_swap_operations_exact_out(
+            deps,
+            env,
+            Addr::unchecked(cw20_msg.sender),
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: info.sender.clone(),
+                },
+                amount: cw20_msg.amount,
+            },
+            operations,
+            ask_amount,
+            max_offer,
+            to,
+            max_spread,
+        ),
+        Cw20HookMsg::ExecuteSplitSwap {
+            routes,
+            minimum_receive,
+            to,
+            max_spread,
+        } => execute_split_swap(
+            deps,
+            env,
+            Addr::unchecked(cw20_msg.sender),
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: info.sender,
+                },
+                amount: cw20_msg.amount,
+            },
+            routes,
+            minimum_receive,
+            to,
+            max_spread,
+        ),
+        Cw20HookMsg::ZapIn {
+            pair_addr,
+            operations,
+            slippage_tolerance,
+            auto_stake,
+            receiver,
+            max_spread,
+        } => execute_zap_in(
+            deps,
+            env,
+            Addr::unchecked(cw20_msg.sender),
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: info.sender,
+                },
+                amount: cw20_msg.amount,
+            },
+            pair_addr,
+            operations,
+            slippage_tolerance,
+            auto_stake,
+            receiver,
+            max_spread,
+        ),
+        Cw20HookMsg::ZapOut {
+            ask_asset_info,
+            minimum_receive,
+            to,
+            max_spread,
+        } => execute_zap_out(
+            deps,
+            env,
+            Addr::unchecked(cw20_msg.sender),
+            info.sender,
+            cw20_msg.amount,
+            ask_asset_info,
+            minimum_receive,
+            to,
+            max_spread,
+        ),
+    }
+}
+
+/// Returns the only native coin sent along with the message as an offer asset.
+fn native_offer_asset(info: &MessageInfo) -> Result<Asset, ContractError> {
+    match &info.funds[..] {
+        [coin] if !coin.amount.is_zero() => Ok(Asset {
+            info: AssetInfo::NativeToken {
+                denom: coin.denom.clone(),
+            },
+            amount: coin.amount,
+        }),
+        _ => Err(StdError::generic_err("Exactly one native coin must be sent to swap").into()),
     }
 }
 
+/// Discovers the route with the highest simulated output and performs swap operations along it.
+///
+/// * **sender** address that swaps tokens.
+///
+/// * **offer_asset** asset that is swapped.
+///
+/// * **ask_asset_info** asset that is swapped to.
+///
+/// * **max_hops** the maximum amount of swaps in the route.
+///
+/// * **minimum_receive** used to guarantee that the ask amount is above a minimum amount.
+///
+/// * **to** recipient of the ask tokens.
+#[allow(clippy::too_many_arguments)]
+pub fn execute_best_route(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    offer_asset: Asset,
+    ask_asset_info: AssetInfo,
+    max_hops: Option<u32>,
+    minimum_receive: Option<Uint128>,
+    to: Option<String>,
+    max_spread: Option<Decimal>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let max_hops = validate_max_hops(max_hops)?;
+    let (operations, _) = find_best_route(
+        deps.as_ref(),
+        &config.astroport_factory,
+        &offer_asset,
+        &ask_asset_info,
+        max_hops,
+    )?;
+    let hops = operations.len();
+
+    Ok(execute_swap_operations(
+        deps,
+        env,
+        sender,
+        operations,
+        offer_asset.amount,
+        minimum_receive,
+        to,
+        max_spread,
+        None,
+    )?
+    .add_attribute("action", "execute_best_route")
+    .add_attribute("hops", hops.to_string()))
+}
+
 /// Performs swap operations with the specified parameters.
 ///
 /// * **sender** address that swaps tokens.
 ///
 /// * **operations** all swap operations to perform.
 ///
+/// * **offer_amount** amount of offer assets sent to the router. It is used to calculate the referral commission.
+///
 /// * **minimum_receive** used to guarantee that the ask amount is above a minimum amount.
 ///
 /// * **to** recipient of the ask tokens.
+///
+/// * **referral** referrer address and its commission which is deducted from the offer amount before the first hop.
 #[allow(clippy::too_many_arguments)]
 pub fn execute_swap_operations(
     deps: DepsMut,
     env: Env,
     sender: Addr,
     operations: Vec<SwapOperation>,
+    offer_amount: Uint128,
     minimum_receive: Option<Uint128>,
     to: Option<String>,
     max_spread: Option<Decimal>,
+    referral: Option<(String, Decimal)>,
 ) -> Result<Response, ContractError> {
     let operations_len = operations.len();
     if operations_len == 0 {
@@ -167,45 +563,352 @@ pub fn execute_swap_operations(
 
     let to = addr_opt_validate(deps.api, &to)?.unwrap_or(sender);
 
+    let offer_asset_info = operations[0].get_offer_asset_info();
     let target_asset_info = operations.last().unwrap().get_target_asset_info();
 
-    let mut messages = operations
-        .into_iter()
-        .enumerate()
-        .map(|(operation_index, op)| {
-            Ok(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: env.contract.address.to_string(),
-                funds: vec![],
-                msg: to_binary(&ExecuteMsg::ExecuteSwapOperation {
-                    operation: op,
-                    to: if operation_index == operations_len - 1 {
-                        Some(to.to_string())
-                    } else {
-                        None
-                    },
-                    max_spread,
-                    single: operations_len == 1,
-                })?,
-            }))
-        })
-        .collect::<StdResult<Vec<CosmosMsg>>>()?;
+    let mut messages = vec![];
+    let mut attrs = vec![];
+
+    // Send the referral commission before the first hop swaps the rest of the router balance
+    if let Some((referrer, commission)) = referral {
+        let referrer = deps.api.addr_validate(&referrer)?;
+        let referral_amount = referral_amount(deps.as_ref(), offer_amount, commission)?;
+        if !referral_amount.is_zero() {
+            messages.push(
+                Asset {
+                    info: offer_asset_info,
+                    amount: referral_amount,
+                }
+                .into_msg(&referrer)?,
+            );
+        }
+        attrs.push(attr("referrer", referrer));
+        attrs.push(attr("referral_amount", referral_amount));
+    }
+
+    messages.extend(
+        operations
+            .into_iter()
+            .enumerate()
+            .map(|(operation_index, op)| {
+                Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: env.contract.address.to_string(),
+                    funds: vec![],
+                    msg: to_binary(&ExecuteMsg::ExecuteSwapOperation {
+                        operation: op,
+                        to: if operation_index == operations_len - 1 {
+                            Some(to.to_string())
+                        } else {
+                            None
+                        },
+                        max_spread,
+                        single: operations_len == 1,
+                    })?,
+                }))
+            })
+            .collect::<StdResult<Vec<CosmosMsg>>>()?,
+    );
 
     // Execute minimum amount assertion
     if let Some(minimum_receive) = minimum_receive {
-        let receiver_balance = target_asset_info.query_pool(&deps.querier, &to)?;
+        messages.push(minimum_receive_msg(
+            deps.as_ref(),
+            &env,
+            target_asset_info,
+            minimum_receive,
+            &to,
+        )?);
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(attrs))
+}
+
+/// Returns the referral commission amount after checking the commission against the configured maximum.
+///
+/// * **offer_amount** amount of offer assets.
+///
+/// * **commission** referral commission.
+fn referral_amount(
+    deps: Deps,
+    offer_amount: Uint128,
+    commission: Decimal,
+) -> Result<Uint128, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if commission > config.max_referral_commission {
+        return Err(ContractError::ReferralCommissionTooHigh(
+            config.max_referral_commission,
+        ));
+    }
+
+    Ok(offer_amount * commission)
+}
+
+/// Performs swap operations so that the recipient receives at least the specified amount of ask assets.
+/// The required offer amount is calculated with a reverse simulation and leftover offer assets
+/// are refunded to the sender.
+///
+/// * **sender** address that swaps tokens.
+///
+/// * **offer_asset** asset that is sent to the router.
+///
+/// * **operations** all swap operations to perform.
+///
+/// * **ask_amount** amount of ask assets to receive.
+///
+/// * **max_offer** maximum amount of offer assets to spend.
+///
+/// * **to** recipient of the ask tokens.
+#[allow(clippy::too_many_arguments)]
+pub fn execute_swap_operations_exact_out(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    offer_asset: Asset,
+    operations: Vec<SwapOperation>,
+    ask_amount: Uint128,
+    max_offer: Option<Uint128>,
+    to: Option<String>,
+    max_spread: Option<Decimal>,
+) -> Result<Response, ContractError> {
+    let offer_amount =
+        reverse_simulate_swap_operations(deps.as_ref(), ask_amount, operations.clone())?
+            .offer_amount;
+
+    let first_offer_asset_info = operations[0].get_offer_asset_info();
+    if first_offer_asset_info != offer_asset.info {
+        return Err(StdError::generic_err(format!(
+            "Operations must start with {}",
+            offer_asset.info
+        ))
+        .into());
+    }
+
+    let max_offer = max_offer
+        .unwrap_or(offer_asset.amount)
+        .min(offer_asset.amount);
+    if offer_amount > max_offer {
+        return Err(ContractError::MaxOfferAssertion {
+            max_offer,
+            offer_amount,
+        });
+    }
+
+    let to = addr_opt_validate(deps.api, &to)?.unwrap_or_else(|| sender.clone());
+    let config = CONFIG.load(deps.storage)?;
+
+    let target_asset_info = operations.last().unwrap().get_target_asset_info();
+    let mut messages = exact_offer_route_msgs(
+        deps.as_ref(),
+        &env,
+        &config,
+        operations,
+        offer_amount,
+        &to,
+        max_spread,
+    )?;
+
+    messages.push(minimum_receive_msg(
+        deps.as_ref(),
+        &env,
+        target_asset_info,
+        ask_amount,
+        &to,
+    )?);
+
+    let refund_amount = offer_asset.amount - offer_amount;
+    if !refund_amount.is_zero() {
+        messages.push(
+            Asset {
+                info: offer_asset.info,
+                amount: refund_amount,
+            }
+            .into_msg(&sender)?,
+        );
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "execute_swap_operations_exact_out")
+        .add_attribute("offer_amount", offer_amount)
+        .add_attribute("refund_amount", refund_amount))
+}
+
+/// Splits an offer amount across parallel routes and performs swap operations along each route.
+/// The first hop of every route swaps an exact amount while the next hops swap the whole router balance
+/// of an intermediate asset thus routes are executed one after another.
+///
+/// * **sender** address that swaps tokens.
+///
+/// * **offer_asset** asset that is swapped.
+///
+/// * **routes** parallel routes along with their weights.
+///
+/// * **minimum_receive** used to guarantee that the total ask amount is above a minimum amount.
+///
+/// * **to** recipient of the ask tokens.
+#[allow(clippy::too_many_arguments)]
+pub fn execute_split_swap(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    offer_asset: Asset,
+    routes: Vec<(Decimal, Vec<SwapOperation>)>,
+    minimum_receive: Option<Uint128>,
+    to: Option<String>,
+    max_spread: Option<Decimal>,
+) -> Result<Response, ContractError> {
+    let (offer_asset_info, target_asset_info) = assert_split_routes(deps.api, &routes)?;
+    if offer_asset_info != offer_asset.info {
+        return Err(
+            StdError::generic_err(format!("Routes must start with {}", offer_asset.info)).into(),
+        );
+    }
+
+    let to = addr_opt_validate(deps.api, &to)?.unwrap_or(sender);
+    let config = CONFIG.load(deps.storage)?;
+
+    let amounts = split_offer_amount(offer_asset.amount, &routes)?;
+    let mut messages = vec![];
+    for ((_, operations), amount) in routes.into_iter().zip(amounts) {
+        if !amount.is_zero() {
+            messages.extend(exact_offer_route_msgs(
+                deps.as_ref(),
+                &env,
+                &config,
+                operations,
+                amount,
+                &to,
+                max_spread,
+            )?);
+        }
+    }
+
+    // Execute minimum amount assertion for all routes at once
+    if let Some(minimum_receive) = minimum_receive {
+        messages.push(minimum_receive_msg(
+            deps.as_ref(),
+            &env,
+            target_asset_info,
+            minimum_receive,
+            &to,
+        )?);
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "execute_split_swap"))
+}
+
+/// Creates messages which perform swap operations along a route starting with an exact offer amount.
+/// The first hop swaps exact amount of offer asset while the next hops swap the whole router balance
+/// of an intermediate asset.
+///
+/// * **operations** swap operations of the route.
+///
+/// * **amount** amount of offer assets to swap.
+///
+/// * **to** recipient of the ask tokens.
+pub(crate) fn exact_offer_route_msgs(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    operations: Vec<SwapOperation>,
+    amount: Uint128,
+    to: &Addr,
+    max_spread: Option<Decimal>,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    let operations_len = operations.len();
+    let mut operations = operations.into_iter();
+    let mut messages = vec![];
+
+    match operations.next() {
+        Some(SwapOperation::AstroSwap {
+            offer_asset_info,
+            ask_asset_info,
+            max_spread: hop_max_spread,
+            belief_price,
+        }) => {
+            let pair_info = query_pair_info(
+                &deps.querier,
+                config.astroport_factory.clone(),
+                &[offer_asset_info.clone(), ask_asset_info.clone()],
+            )?;
+            messages.push(asset_into_swap_msg(
+                pair_info.contract_addr.to_string(),
+                Asset {
+                    info: offer_asset_info,
+                    amount,
+                },
+                ask_asset_info,
+                hop_max_spread.or(max_spread),
+                belief_price,
+                if operations_len == 1 {
+                    Some(to.to_string())
+                } else {
+                    None
+                },
+                operations_len == 1 || hop_max_spread.is_some() || belief_price.is_some(),
+            )?);
+        }
+        Some(SwapOperation::NativeSwap {
+            offer_denom,
+            ask_denom,
+        }) => {
+            messages.push(native_swap_msg(
+                config,
+                offer_denom,
+                ask_denom,
+                amount,
+                if operations_len == 1 {
+                    Some(to.to_string())
+                } else {
+                    None
+                },
+            )?);
+        }
+        None => return Err(ContractError::MustProvideOperations {}),
+    }
+
+    for (operation_index, op) in operations.enumerate() {
         messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: env.contract.address.to_string(),
             funds: vec![],
-            msg: to_binary(&ExecuteMsg::AssertMinimumReceive {
-                asset_info: target_asset_info,
-                prev_balance: receiver_balance,
-                minimum_receive,
-                receiver: to.to_string(),
+            msg: to_binary(&ExecuteMsg::ExecuteSwapOperation {
+                operation: op,
+                to: if operation_index == operations_len - 2 {
+                    Some(to.to_string())
+                } else {
+                    None
+                },
+                max_spread,
+                single: false,
             })?,
         }));
     }
 
-    Ok(Response::new().add_messages(messages))
+    Ok(messages)
+}
+
+/// Creates a message which checks that the receiver got at least minimum amount of ask assets.
+pub(crate) fn minimum_receive_msg(
+    deps: Deps,
+    env: &Env,
+    asset_info: AssetInfo,
+    minimum_receive: Uint128,
+    receiver: &Addr,
+) -> StdResult<CosmosMsg> {
+    let receiver_balance = asset_info.query_pool(&deps.querier, receiver)?;
+
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: env.contract.address.to_string(),
+        funds: vec![],
+        msg: to_binary(&ExecuteMsg::AssertMinimumReceive {
+            asset_info,
+            prev_balance: receiver_balance,
+            minimum_receive,
+            receiver: receiver.to_string(),
+        })?,
+    }))
 }
 
 /// Checks if an ask amount is equal to or above a minimum amount.
@@ -245,6 +948,19 @@ fn assert_minimum_receive(
 ///             offer_amount,
 ///             operations,
 ///         }** Simulates one or multiple swap operations and returns the end result in a [`SimulateSwapOperationsResponse`] object.
+/// * **QueryMsg::ReverseSimulateSwapOperations {
+///             ask_amount,
+///             operations,
+///         }** Walks swap operations backwards and returns the required offer amount in a [`ReverseSimulateSwapOperationsResponse`] object.
+/// * **QueryMsg::SimulateSplitSwap {
+///             offer_amount,
+///             routes,
+///         }** Simulates swaps split across parallel routes and returns the result in a [`SimulateSplitSwapResponse`] object.
+/// * **QueryMsg::FindBestRoute {
+///             offer_asset,
+///             ask_asset_info,
+///             max_hops,
+///         }** Returns the route with the highest simulated output in a [`BestRouteResponse`] object.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
     match msg {
@@ -252,11 +968,55 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractErr
         QueryMsg::SimulateSwapOperations {
             offer_amount,
             operations,
-        } => Ok(to_binary(&simulate_swap_operations(
+            referral_commission,
+        } => {
+            let offer_amount = match referral_commission {
+                Some(commission) => offer_amount - referral_amount(deps, offer_amount, commission)?,
+                None => offer_amount,
+            };
+            Ok(to_binary(&simulate_swap_operations(
+                deps,
+                offer_amount,
+                operations,
+            )?)?)
+        }
+        QueryMsg::SimulateSwapOperationsDetailed {
+            offer_amount,
+            operations,
+        } => Ok(to_binary(&simulate_swap_operations_detailed(
             deps,
             offer_amount,
             operations,
         )?)?),
+        QueryMsg::ReverseSimulateSwapOperations {
+            ask_amount,
+            operations,
+        } => Ok(to_binary(&reverse_simulate_swap_operations(
+            deps, ask_amount, operations,
+        )?)?),
+        QueryMsg::SimulateSplitSwap {
+            offer_amount,
+            routes,
+        } => Ok(to_binary(&simulate_split_swap(
+            deps,
+            offer_amount,
+            routes,
+        )?)?),
+        QueryMsg::FindBestRoute {
+            offer_asset,
+            ask_asset_info,
+            max_hops,
+        } => {
+            let config = CONFIG.load(deps.storage)?;
+            let (operations, amount) = find_best_route(
+                deps,
+                &config.astroport_factory,
+                &offer_asset,
+                &ask_asset_info,
+                validate_max_hops(max_hops)?,
+            )?;
+            Ok(to_binary(&BestRouteResponse { operations, amount })?)
+        }
     }
 }
 
@@ -265,6 +1025,9 @@ pub fn query_config(deps: Deps) -> Result<ConfigResponse, ContractError> {
     let state = CONFIG.load(deps.storage)?;
     let resp = ConfigResponse {
         astroport_factory: state.astroport_factory.into_string(),
+        owner: state.owner.into_string(),
+        native_swap_handler: state.native_swap_handler.map(Addr::into_string),
+        max_referral_commission: state.max_referral_commission,
     };
 
     Ok(resp)
@@ -272,17 +1035,31 @@ pub fn query_config(deps: Deps) -> Result<ConfigResponse, ContractError> {
 
 /// Manages contract migration.
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
     let contract_version = get_contract_version(deps.storage)?;
 
     match contract_version.contract.as_ref() {
         "astroport-router" => match contract_version.version.as_ref() {
-            "1.0.0" | "1.1.0" => {}
+            "1.0.0" | "1.1.0" | "1.1.1" => {}
             _ => return Err(ContractError::MigrationError {}),
         },
         _ => return Err(ContractError::MigrationError {}),
     };
 
+    let config_v111 = CONFIG_V111.load(deps.storage)?;
+    CONFIG.save(
+        deps.storage,
+        &Config {
+            astroport_factory: config_v111.astroport_factory,
+            owner: deps.api.addr_validate(&msg.owner)?,
+            native_swap_handler: addr_opt_validate(deps.api, &msg.native_swap_handler)?,
+            max_referral_commission: validate_max_referral_commission(
+                msg.max_referral_commission
+                    .unwrap_or(DEFAULT_MAX_REFERRAL_COMMISSION),
+            )?,
+        },
+    )?;
+
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
     Ok(Response::new()
@@ -304,8 +1081,26 @@ fn simulate_swap_operations(
     offer_amount: Uint128,
     operations: Vec<SwapOperation>,
 ) -> Result<SimulateSwapOperationsResponse, ContractError> {
+    let amount = simulate_swap_operations_detailed(deps, offer_amount, operations)?.amount;
+
+    Ok(SimulateSwapOperationsResponse { amount })
+}
+
+/// Simulates swap operations hop by hop and returns the result of every hop along with the cumulative price impact
+/// using a [`SimulateSwapOperationsDetailedResponse`] object.
+/// The price impact of a hop is the share of the ideal return amount (at the pool spot price) lost to the spread.
+///
+/// * **offer_amount** amount of offer assets being swapped.
+///
+/// * **operations** is a vector that contains objects of type [`SwapOperation`].
+/// These are all the swap operations for which we perform a simulation.
+fn simulate_swap_operations_detailed(
+    deps: Deps,
+    offer_amount: Uint128,
+    operations: Vec<SwapOperation>,
+) -> Result<SimulateSwapOperationsDetailedResponse, ContractError> {
     let config = CONFIG.load(deps.storage)?;
-    let astroport_factory = config.astroport_factory;
+    let astroport_factory = &config.astroport_factory;
 
     let operations_len = operations.len();
     if operations_len == 0 {
@@ -319,11 +1114,21 @@ fn simulate_swap_operations(
     assert_operations(deps.api, &operations)?;
 
     let mut return_amount = offer_amount;
+    let mut hops = vec![];
+    // The share of the ideal return amount which remains after all hops
+    let mut retained = Decimal::one();
     for operation in operations.into_iter() {
-        match operation {
+        let offer_asset = Asset {
+            info: operation.get_offer_asset_info(),
+            amount: return_amount,
+        };
+        let ask_asset_info = operation.get_target_asset_info();
+
+        let res: SimulationResponse = match operation {
             SwapOperation::AstroSwap {
                 offer_asset_info,
                 ask_asset_info,
+                ..
             } => {
                 let pair_info = query_pair_info(
                     &deps.querier,
@@ -331,44 +1136,285 @@ fn simulate_swap_operations(
                     &[offer_asset_info.clone(), ask_asset_info.clone()],
                 )?;
 
-                let res: SimulationResponse = deps.querier.query_wasm_smart(
+                deps.querier.query_wasm_smart(
                     pair_info.contract_addr,
                     &PairQueryMsg::Simulation {
-                        offer_asset: Asset {
-                            info: offer_asset_info.clone(),
-                            amount: return_amount,
+                        offer_asset: offer_asset.clone(),
+                        ask_asset_info: Some(ask_asset_info),
+                    },
+                )?
+            }
+            SwapOperation::NativeSwap {
+                offer_denom,
+                ask_denom,
+            } => {
+                let handler = config
+                    .native_swap_handler
+                    .as_ref()
+                    .ok_or(ContractError::NativeSwapNotSupported {})?;
+
+                let res: NativeSwapSimulationResponse = deps.querier.query_wasm_smart(
+                    handler,
+                    &NativeSwapHandlerQueryMsg::Simulate {
+                        offer_coin: coin(return_amount.u128(), offer_denom),
+                        ask_denom,
+                    },
+                )?;
+
+                // The handler does not report spread and commission
+                SimulationResponse {
+                    return_amount: res.return_amount,
+                    spread_amount: Uint128::zero(),
+                    commission_amount: Uint128::zero(),
+                }
+            }
+        };
+
+        let ideal_amount = res.return_amount + res.spread_amount + res.commission_amount;
+        let price_impact = if ideal_amount.is_zero() {
+            Decimal::zero()
+        } else {
+            Decimal::from_ratio(res.spread_amount, ideal_amount)
+        };
+        retained = retained * (Decimal::one() - price_impact);
+
+        return_amount = res.return_amount;
+        hops.push(HopSimulation {
+            offer_asset,
+            return_asset: Asset {
+                info: ask_asset_info,
+                amount: res.return_amount,
+            },
+            spread_amount: res.spread_amount,
+            commission_amount: res.commission_amount,
+            price_impact,
+        });
+    }
+
+    Ok(SimulateSwapOperationsDetailedResponse {
+        amount: return_amount,
+        hops,
+        price_impact: Decimal::one() - retained,
+    })
+}
+
+/// Returns the amount of offer assets required to receive the specified amount of ask assets
+/// using a [`ReverseSimulateSwapOperationsResponse`] object. Operations are simulated backwards.
+/// As pairs round reverse simulation results down, the offer amount of each pair hop is increased by one unit.
+/// Native swaps are reverse simulated by the native swap handler which rounds the offer amount up.
+///
+/// * **ask_amount** amount of ask assets to receive.
+///
+/// * **operations** is a vector that contains objects of type [`SwapOperation`].
+/// These are all the swap operations for which we perform a reverse simulation.
+fn reverse_simulate_swap_operations(
+    deps: Deps,
+    ask_amount: Uint128,
+    operations: Vec<SwapOperation>,
+) -> Result<ReverseSimulateSwapOperationsResponse, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let astroport_factory = config.astroport_factory;
+
+    let operations_len = operations.len();
+    if operations_len == 0 {
+        return Err(ContractError::MustProvideOperations {});
+    }
+
+    if operations_len > MAX_SWAP_OPERATIONS {
+        return Err(ContractError::SwapLimitExceeded {});
+    }
+
+    assert_operations(deps.api, &operations)?;
+
+    let mut offer_amount = ask_amount;
+    for operation in operations.into_iter().rev() {
+        match operation {
+            SwapOperation::AstroSwap {
+                offer_asset_info,
+                ask_asset_info,
+                ..
+            } => {
+                let pair_info = query_pair_info(
+                    &deps.querier,
+                    astroport_factory.clone(),
+                    &[offer_asset_info.clone(), ask_asset_info.clone()],
+                )?;
+
+                let res: ReverseSimulationResponse = deps.querier.query_wasm_smart(
+                    pair_info.contract_addr,
+                    &PairQueryMsg::ReverseSimulation {
+                        offer_asset_info: Some(offer_asset_info),
+                        ask_asset: Asset {
+                            info: ask_asset_info,
+                            amount: offer_amount,
                         },
-                        ask_asset_info: Some(ask_asset_info.clone()),
                     },
                 )?;
 
-                return_amount = res.return_amount;
+                offer_amount = res.offer_amount + Uint128::one();
             }
-            SwapOperation::NativeSwap { .. } => {
-                return Err(ContractError::NativeSwapNotSupported {})
+            SwapOperation::NativeSwap {
+                offer_denom,
+                ask_denom,
+            } => {
+                let handler = config
+                    .native_swap_handler
+                    .as_ref()
+                    .ok_or(ContractError::NativeSwapNotSupported {})?;
+
+                let res: NativeSwapReverseSimulationResponse = deps.querier.query_wasm_smart(
+                    handler,
+                    &NativeSwapHandlerQueryMsg::ReverseSimulate {
+                        ask_coin: coin(offer_amount.u128(), ask_denom),
+                        offer_denom,
+                    },
+                )?;
+
+                offer_amount = res.offer_amount;
             }
         }
     }
 
-    Ok(SimulateSwapOperationsResponse {
-        amount: return_amount,
+    Ok(ReverseSimulateSwapOperationsResponse { offer_amount })
+}
+
+/// Returns the end result of a simulation for swaps split across parallel routes
+/// using a [`SimulateSplitSwapResponse`] object.
+/// Every route is simulated independently against the current pools state.
+///
+/// * **offer_amount** amount of offer assets being swapped.
+///
+/// * **routes** parallel routes along with their weights.
+fn simulate_split_swap(
+    deps: Deps,
+    offer_amount: Uint128,
+    routes: Vec<(Decimal, Vec<SwapOperation>)>,
+) -> Result<SimulateSplitSwapResponse, ContractError> {
+    assert_split_routes(deps.api, &routes)?;
+
+    let amounts = split_offer_amount(offer_amount, &routes)?;
+    let routes_amounts = routes
+        .into_iter()
+        .zip(amounts)
+        .map(|((_, operations), amount)| {
+            if amount.is_zero() {
+                Ok(Uint128::zero())
+            } else {
+                simulate_swap_operations(deps, amount, operations).map(|res| res.amount)
+            }
+        })
+        .collect::<Result<Vec<_>, ContractError>>()?;
+
+    Ok(SimulateSplitSwapResponse {
+        amount: routes_amounts.iter().sum(),
+        routes_amounts,
     })
 }
 
+/// Splits an offer amount according to route weights.
+/// The last route gets the remainder so the whole amount is always used.
+fn split_offer_amount(
+    offer_amount: Uint128,
+    routes: &[(Decimal, Vec<SwapOperation>)],
+) -> StdResult<Vec<Uint128>> {
+    let mut amounts = routes[..routes.len() - 1]
+        .iter()
+        .map(|(weight, _)| offer_amount * *weight)
+        .collect::<Vec<_>>();
+    let used_amount = amounts.iter().sum::<Uint128>();
+    amounts.push(offer_amount.checked_sub(used_amount)?);
+
+    Ok(amounts)
+}
+
+/// Validates split swap routes. Returns offer and ask assets which are common for all routes.
+///
+/// * **routes** parallel routes along with their weights. Weights must be non-zero and sum up to 1.
+fn assert_split_routes(
+    api: &dyn Api,
+    routes: &[(Decimal, Vec<SwapOperation>)],
+) -> Result<(AssetInfo, AssetInfo), ContractError> {
+    if routes.is_empty() {
+        return Err(ContractError::MustProvideOperations {});
+    }
+
+    if routes.len() > MAX_SPLIT_ROUTES {
+        return Err(ContractError::SplitRoutesLimitExceeded(MAX_SPLIT_ROUTES));
+    }
+
+    let operations_len: usize = routes.iter().map(|(_, operations)| operations.len()).sum();
+    if operations_len > MAX_SWAP_OPERATIONS {
+        return Err(ContractError::SwapLimitExceeded {});
+    }
+
+    if routes.iter().any(|(weight, _)| weight.is_zero())
+        || routes.iter().map(|(weight, _)| *weight).sum::<Decimal>() != Decimal::one()
+    {
+        return Err(ContractError::InvalidSplitWeights {});
+    }
+
+    let mut common_assets: Option<(AssetInfo, AssetInfo)> = None;
+    for (_, operations) in routes {
+        if operations.is_empty() {
+            return Err(ContractError::MustProvideOperations {});
+        }
+        assert_operations(api, operations)?;
+
+        let offer_asset_info = operations[0].get_offer_asset_info();
+        let ask_asset_info = operations.last().unwrap().get_target_asset_info();
+
+        // Intermediate hops must not return offer asset otherwise the next routes would lose their share
+        if operations
+            .iter()
+            .any(|op| op.get_target_asset_info() == offer_asset_info)
+        {
+            return Err(StdError::generic_err(format!(
+                "Routes must not swap back to {offer_asset_info}"
+            ))
+            .into());
+        }
+
+        match &common_assets {
+            Some((offer, ask)) if *offer != offer_asset_info || *ask != ask_asset_info => {
+                return Err(StdError::generic_err(
+                    "All routes must have the same offer and ask assets",
+                )
+                .into());
+            }
+            Some(_) => {}
+            None => common_assets = Some((offer_asset_info, ask_asset_info)),
+        }
+    }
+
+    Ok(common_assets.unwrap())
+}
+
 /// Validates swap operations.
 ///
 /// * **operations** is a vector that contains objects of type [`SwapOperation`]. These are all the swap operations we check.
-fn assert_operations(api: &dyn Api, operations: &[SwapOperation]) -> Result<(), ContractError> {
+pub(crate) fn assert_operations(
+    api: &dyn Api,
+    operations: &[SwapOperation],
+) -> Result<(), ContractError> {
     let mut ask_asset_map: HashSet<String> = HashSet::new();
     for operation in operations {
         let (offer_asset, ask_asset) = match operation {
             SwapOperation::AstroSwap {
                 offer_asset_info,
                 ask_asset_info,
+                ..
             } => (offer_asset_info.clone(), ask_asset_info.clone()),
-            SwapOperation::NativeSwap { .. } => {
-                return Err(ContractError::NativeSwapNotSupported {})
-            }
+            SwapOperation::NativeSwap {
+                offer_denom,
+                ask_denom,
+            } => (
+                AssetInfo::NativeToken {
+                    denom: offer_denom.clone(),
+                },
+                AssetInfo::NativeToken {
+                    denom: ask_denom.clone(),
+                },
+            ),
         };
         offer_asset.check(api)?;
         ask_asset.check(api)?;
@@ -408,6 +1454,8 @@ mod testing {
                         ask_asset_info: AssetInfo::Token {
                             contract_addr: Addr::unchecked("asset0001"),
                         },
+                        max_spread: None,
+                        belief_price: None,
                     },
                     SwapOperation::AstroSwap {
                         offer_asset_info: AssetInfo::Token {
@@ -416,6 +1464,8 @@ mod testing {
                         ask_asset_info: AssetInfo::NativeToken {
                             denom: "uluna".to_string(),
                         },
+                        max_spread: None,
+                        belief_price: None,
                     },
                 ],
             )
@@ -435,6 +1485,8 @@ mod testing {
                         ask_asset_info: AssetInfo::Token {
                             contract_addr: Addr::unchecked("asset0001"),
                         },
+                        max_spread: None,
+                        belief_price: None,
                     },
                     SwapOperation::AstroSwap {
                         offer_asset_info: AssetInfo::Token {
@@ -443,6 +1495,8 @@ mod testing {
                         ask_asset_info: AssetInfo::NativeToken {
                             denom: "uluna".to_string(),
                         },
+                        max_spread: None,
+                        belief_price: None,
                     },
                     SwapOperation::AstroSwap {
                         offer_asset_info: AssetInfo::NativeToken {
@@ -451,6 +1505,8 @@ mod testing {
                         ask_asset_info: AssetInfo::Token {
                             contract_addr: Addr::unchecked("asset0002"),
                         },
+                        max_spread: None,
+                        belief_price: None,
                     },
                 ],
             )
@@ -470,6 +1526,8 @@ mod testing {
                         ask_asset_info: AssetInfo::Token {
                             contract_addr: Addr::unchecked("asset0001"),
                         },
+                        max_spread: None,
+                        belief_price: None,
                     },
                     SwapOperation::AstroSwap {
                         offer_asset_info: AssetInfo::Token {
@@ -478,6 +1536,8 @@ mod testing {
                         ask_asset_info: AssetInfo::NativeToken {
                             denom: "uaud".to_string(),
                         },
+                        max_spread: None,
+                        belief_price: None,
                     },
                     SwapOperation::AstroSwap {
                         offer_asset_info: AssetInfo::NativeToken {
@@ -486,6 +1546,8 @@ mod testing {
                         ask_asset_info: AssetInfo::Token {
                             contract_addr: Addr::unchecked("asset0002"),
                         },
+                        max_spread: None,
+                        belief_price: None,
                     },
                 ],
             )
@@ -1,4 +1,4 @@
-use cosmwasm_std::{OverflowError, StdError, Uint128};
+use cosmwasm_std::{Decimal, OverflowError, StdError, Uint128};
 use thiserror::Error;
 
 /// This enum describes oracle contract errors
@@ -22,6 +22,39 @@ pub enum ContractError {
     #[error("Native swap operations are not supported!")]
     NativeSwapNotSupported {},
 
+    #[error("max_hops must be within [1, {0}]")]
+    InvalidMaxHops(u32),
+
+    #[error("Offer and ask assets are the same")]
+    SameAssets {},
+
+    #[error("No route found from {offer} to {ask}")]
+    RouteNotFound { offer: String, ask: String },
+
+    #[error("Required offer amount {offer_amount} exceeds max offer {max_offer}")]
+    MaxOfferAssertion {
+        max_offer: Uint128,
+        offer_amount: Uint128,
+    },
+
+    #[error("Split swap can't have more than {0} routes")]
+    SplitRoutesLimitExceeded(usize),
+
+    #[error("Route weights must be non-zero and sum up to 1")]
+    InvalidSplitWeights {},
+
+    #[error("Referral commission must be less than or equal to {0}")]
+    ReferralCommissionTooHigh(Decimal),
+
+    #[error("Max referral commission must be less than 1")]
+    InvalidMaxReferralCommission {},
+
+    #[error("{0} is not an Astroport pair with two assets")]
+    InvalidZapPair(String),
+
+    #[error("{0} is not in the pair")]
+    AssetNotInPair(String),
+
     #[error("Contract can't be migrated!")]
     MigrationError {},
 }
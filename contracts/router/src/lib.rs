@@ -4,6 +4,8 @@ pub mod state;
 pub mod error;
 
 mod operations;
+mod routes;
+mod zap;
 
 #[cfg(test)]
 mod testing;
@@ -1,14 +1,15 @@
 use astroport::asset::{Asset, AssetInfo};
 use astroport::pair::ExecuteMsg as PairExecuteMsg;
 use astroport::querier::{query_balance, query_pair_info, query_token_balance};
-use astroport::router::SwapOperation;
+use astroport::router::{NativeSwapHandlerExecuteMsg, SwapOperation};
 use cosmwasm_std::{
-    to_binary, Coin, CosmosMsg, Decimal, DepsMut, Env, MessageInfo, Response, StdResult, WasmMsg,
+    coins, to_binary, wasm_execute, Coin, CosmosMsg, Decimal, DepsMut, Env, MessageInfo, Response,
+    StdResult, Uint128, WasmMsg,
 };
 use cw20::Cw20ExecuteMsg;
 
 use crate::error::ContractError;
-use crate::state::CONFIG;
+use crate::state::{Config, CONFIG};
 
 /// Execute a swap operation.
 ///
@@ -34,6 +35,8 @@ pub fn execute_swap_operation(
         SwapOperation::AstroSwap {
             offer_asset_info,
             ask_asset_info,
+            max_spread: hop_max_spread,
+            belief_price,
         } => {
             let config = CONFIG.load(deps.storage)?;
             let pair_info = query_pair_info(
@@ -55,21 +58,61 @@ pub fn execute_swap_operation(
                 amount,
             };
 
+            // Hops with their own limits assert the spread even in multi hop routes
+            let hop_limits = hop_max_spread.is_some() || belief_price.is_some();
             asset_into_swap_msg(
                 pair_info.contract_addr.to_string(),
                 offer_asset,
                 ask_asset_info,
-                max_spread,
+                hop_max_spread.or(max_spread),
+                belief_price,
                 to,
-                single,
+                single || hop_limits,
             )?
         }
-        SwapOperation::NativeSwap { .. } => return Err(ContractError::NativeSwapNotSupported {}),
+        SwapOperation::NativeSwap {
+            offer_denom,
+            ask_denom,
+        } => {
+            let config = CONFIG.load(deps.storage)?;
+            let amount = query_balance(&deps.querier, env.contract.address, &offer_denom)?;
+
+            native_swap_msg(&config, offer_denom, ask_denom, amount, to)?
+        }
     };
 
     Ok(Response::new().add_message(message))
 }
 
+/// Creates a message which swaps native coins via the native swap handler contract.
+///
+/// * **offer_denom** denom of the native coin being swapped.
+///
+/// * **ask_denom** denom of the native coin we swap to.
+///
+/// * **amount** amount of coins to swap.
+///
+/// * **to** address that receives the ask coins. The router receives them if not set.
+pub fn native_swap_msg(
+    config: &Config,
+    offer_denom: String,
+    ask_denom: String,
+    amount: Uint128,
+    to: Option<String>,
+) -> Result<CosmosMsg, ContractError> {
+    let handler = config
+        .native_swap_handler
+        .as_ref()
+        .ok_or(ContractError::NativeSwapNotSupported {})?;
+
+    Ok(wasm_execute(
+        handler,
+        &NativeSwapHandlerExecuteMsg::Swap { ask_denom, to },
+        coins(amount.u128(), offer_denom),
+    )?
+    .into())
+}
+
 /// Creates a message of type [`CosmosMsg`] representing a swap operation.
 ///
 /// * **pair_contract** Astroport pair contract for which the swap operation is performed.
@@ -80,19 +123,26 @@ pub fn execute_swap_operation(
 ///
 /// * **max_spread** max spread enforced for the swap.
 ///
+/// * **belief_price** belief price enforced for the swap.
+///
 /// * **to** address that receives the ask assets.
 ///
-/// * **single** defines whether this swap is single or part of a multi hop route.
+/// * **single** defines whether the spread is asserted. Spread assertion is disabled for hops of a multi hop route.
 pub fn asset_into_swap_msg(
     pair_contract: String,
     offer_asset: Asset,
     ask_asset_info: AssetInfo,
     max_spread: Option<Decimal>,
+    belief_price: Option<Decimal>,
     to: Option<String>,
     single: bool,
 ) -> StdResult<CosmosMsg> {
     // Disabling spread assertion if this swap is part of a multi hop route
-    let belief_price = if single { None } else { Some(Decimal::MAX) };
+    let belief_price = if single {
+        belief_price
+    } else {
+        Some(Decimal::MAX)
+    };
 
     match &offer_asset.info {
         AssetInfo::NativeToken { denom } => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+
+use astroport::asset::{Asset, AssetInfo};
+use astroport::pair::{QueryMsg as PairQueryMsg, SimulationResponse};
+use astroport::querier::query_pairs_info;
+use astroport::router::{SwapOperation, MAX_ROUTE_HOPS};
+use cosmwasm_std::{Addr, Deps, StdResult, Uint128};
+
+use crate::error::ContractError;
+
+/// The number of pairs read from the factory in one query
+const PAIRS_QUERY_LIMIT: u32 = 30;
+/// The maximum amount of factory pairs explored during route discovery
+const MAX_ROUTE_PAIRS: usize = 300;
+/// The maximum amount of pair simulations performed during route discovery
+const MAX_ROUTE_SIMULATIONS: usize = 100;
+
+/// The best known way to get an asset: the amount received and the swap operations leading to it.
+#[derive(Clone)]
+struct RouteNode {
+    amount: Uint128,
+    operations: Vec<SwapOperation>,
+}
+
+/// Validates the maximum amount of hops in a route.
+pub fn validate_max_hops(max_hops: Option<u32>) -> Result<u32, ContractError> {
+    let max_hops = max_hops.unwrap_or(MAX_ROUTE_HOPS);
+    if max_hops == 0 || max_hops > MAX_ROUTE_HOPS {
+        return Err(ContractError::InvalidMaxHops(MAX_ROUTE_HOPS));
+    }
+
+    Ok(max_hops)
+}
+
+/// Returns up to [`MAX_ROUTE_PAIRS`] pairs registered in the factory as an adjacency list
+/// which maps each asset to the assets it can be directly swapped to.
+fn query_swap_graph(
+    deps: Deps,
+    factory: &Addr,
+) -> StdResult<HashMap<AssetInfo, Vec<(AssetInfo, Addr)>>> {
+    let mut graph: HashMap<AssetInfo, Vec<(AssetInfo, Addr)>> = HashMap::new();
+    let mut start_after = None;
+    let mut pairs_read = 0;
+
+    loop {
+        let pairs = query_pairs_info(
+            &deps.querier,
+            factory,
+            start_after.take(),
+            Some(PAIRS_QUERY_LIMIT),
+        )?
+        .pairs;
+        pairs_read += pairs.len();
+
+        for pair in &pairs {
+            for from in &pair.asset_infos {
+                for to in pair.asset_infos.iter().filter(|to| *to != from) {
+                    graph
+                        .entry(from.clone())
+                        .or_default()
+                        .push((to.clone(), pair.contract_addr.clone()));
+                }
+            }
+        }
+
+        match pairs.last() {
+            Some(last)
+                if pairs.len() == PAIRS_QUERY_LIMIT as usize && pairs_read < MAX_ROUTE_PAIRS =>
+            {
+                start_after = Some(last.asset_infos.clone())
+            }
+            _ => break,
+        }
+    }
+
+    Ok(graph)
+}
+
+/// Explores factory pairs and returns the route with the highest simulated output
+/// along with the simulated amount.
+/// As the output of a swap grows with its input, the route is built hop by hop keeping only
+/// the best amount for each intermediate asset.
+/// Exploration stops after [`MAX_ROUTE_SIMULATIONS`] simulations, the best route found so far is returned.
+///
+/// * **offer_asset** asset that is swapped.
+///
+/// * **ask_asset_info** asset that is swapped to.
+///
+/// * **max_hops** the maximum amount of swaps in the route.
+pub fn find_best_route(
+    deps: Deps,
+    factory: &Addr,
+    offer_asset: &Asset,
+    ask_asset_info: &AssetInfo,
+    max_hops: u32,
+) -> Result<(Vec<SwapOperation>, Uint128), ContractError> {
+    offer_asset.info.check(deps.api)?;
+    ask_asset_info.check(deps.api)?;
+
+    if offer_asset.info == *ask_asset_info {
+        return Err(ContractError::SameAssets {});
+    }
+
+    let graph = query_swap_graph(deps, factory)?;
+
+    let mut best: HashMap<AssetInfo, RouteNode> = HashMap::new();
+    let mut frontier = vec![offer_asset.info.clone()];
+    let mut simulations = 0;
+
+    'levels: for _ in 0..max_hops {
+        // Routes are extended from the previous level only, so a route updated within the current level
+        // is not extended by one more hop
+        let level = best.clone();
+        let mut next_frontier = vec![];
+
+        for from in frontier {
+            let edges = match graph.get(&from) {
+                Some(edges) => edges,
+                None => continue,
+            };
+            let (amount, operations) = match level.get(&from) {
+                Some(node) => (node.amount, node.operations.clone()),
+                None => (offer_asset.amount, vec![]),
+            };
+
+            for (to, pair_addr) in edges {
+                // Routes must not visit the same asset twice
+                let visited = *to == offer_asset.info
+                    || operations
+                        .iter()
+                        .any(|op| op.get_target_asset_info() == *to);
+                if visited {
+                    continue;
+                }
+
+                if simulations == MAX_ROUTE_SIMULATIONS {
+                    break 'levels;
+                }
+                simulations += 1;
+
+                // Pairs which cannot simulate the swap (e.g. empty pools) are skipped
+                let sim: SimulationResponse = match deps.querier.query_wasm_smart(
+                    pair_addr,
+                    &PairQueryMsg::Simulation {
+                        offer_asset: Asset {
+                            info: from.clone(),
+                            amount,
+                        },
+                        ask_asset_info: Some(to.clone()),
+                    },
+                ) {
+                    Ok(sim) => sim,
+                    Err(_) => continue,
+                };
+
+                if sim.return_amount.is_zero()
+                    || matches!(best.get(to), Some(node) if node.amount >= sim.return_amount)
+                {
+                    continue;
+                }
+
+                let mut route = operations.clone();
+                route.push(SwapOperation::AstroSwap {
+                    offer_asset_info: from.clone(),
+                    ask_asset_info: to.clone(),
+                    max_spread: None,
+                    belief_price: None,
+                });
+                best.insert(
+                    to.clone(),
+                    RouteNode {
+                        amount: sim.return_amount,
+                        operations: route,
+                    },
+                );
+                if *to != *ask_asset_info && !next_frontier.contains(to) {
+                    next_frontier.push(to.clone());
+                }
+            }
+        }
+
+        frontier = next_frontier;
+    }
+
+    best.remove(ask_asset_info)
+        .map(|node| (node.operations, node.amount))
+        .ok_or_else(|| ContractError::RouteNotFound {
+            offer: offer_asset.info.to_string(),
+            ask: ask_asset_info.to_string(),
+        })
+}
@@ -1,5 +1,5 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::Addr;
+use cosmwasm_std::{Addr, Decimal};
 use cw_storage_plus::Item;
 
 /// Stores the contract config at the given key
@@ -10,4 +10,20 @@ pub const CONFIG: Item<Config> = Item::new("config");
 pub struct Config {
     /// The factory contract address
     pub astroport_factory: Addr,
+    /// The address which is allowed to update the contract parameters
+    pub owner: Addr,
+    /// The contract which performs native swap operations
+    pub native_swap_handler: Option<Addr>,
+    /// The maximum commission a referrer can take from the offer amount
+    pub max_referral_commission: Decimal,
+}
+
+/// Stores the contract config of versions up to 1.1.1 at the same key as [`CONFIG`]
+pub const CONFIG_V111: Item<ConfigV111> = Item::new("config");
+
+/// This structure holds the parameters of the router up to version 1.1.1
+#[cw_serde]
+pub struct ConfigV111 {
+    /// The factory contract address
+    pub astroport_factory: Addr,
 }
@@ -1,16 +1,20 @@
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
-use cosmwasm_std::{from_binary, to_binary, Addr, Coin, ReplyOn, SubMsg, Uint128, WasmMsg};
+use cosmwasm_std::{coins, from_binary, to_binary, Addr, Coin, ReplyOn, SubMsg, Uint128, WasmMsg};
+use cw2::set_contract_version;
 
-use crate::contract::{execute, instantiate, query};
+use crate::contract::{execute, instantiate, migrate, query};
 use crate::error::ContractError;
+use crate::state::{ConfigV111, CONFIG_V111};
 use crate::testing::mock_querier::mock_dependencies;
 
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 
 use astroport::asset::{native_asset_info, AssetInfo};
+use astroport::factory::UpdateAddr;
 use astroport::router::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg,
-    SimulateSwapOperationsResponse, SwapOperation, MAX_SWAP_OPERATIONS,
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg,
+    NativeSwapHandlerExecuteMsg, QueryMsg, SimulateSwapOperationsResponse, SwapOperation,
+    DEFAULT_MAX_REFERRAL_COMMISSION, MAX_SWAP_OPERATIONS,
 };
 
 #[test]
@@ -19,6 +23,9 @@ fn proper_initialization() {
 
     let msg = InstantiateMsg {
         astroport_factory: String::from("astroportfactory"),
+        owner: String::from("owner"),
+        native_swap_handler: None,
+        max_referral_commission: None,
     };
 
     let env = mock_env();
@@ -31,6 +38,7 @@ fn proper_initialization() {
     let config: ConfigResponse =
         from_binary(&query(deps.as_ref(), env, QueryMsg::Config {}).unwrap()).unwrap();
     assert_eq!("astroportfactory", config.astroport_factory.as_str());
+    assert_eq!("owner", config.owner.as_str());
 }
 
 #[test]
@@ -38,6 +46,9 @@ fn execute_swap_operations() {
     let mut deps = mock_dependencies(&[]);
     let msg = InstantiateMsg {
         astroport_factory: String::from("astroportfactory"),
+        owner: String::from("owner"),
+        native_swap_handler: None,
+        max_referral_commission: None,
     };
 
     let env = mock_env();
@@ -51,6 +62,7 @@ fn execute_swap_operations() {
         minimum_receive: None,
         to: None,
         max_spread: None,
+        referral: None,
     };
 
     let env = mock_env();
@@ -67,6 +79,8 @@ fn execute_swap_operations() {
                 ask_asset_info: AssetInfo::Token {
                     contract_addr: Addr::unchecked("asset0001"),
                 },
+                max_spread: None,
+                belief_price: None,
             },
             SwapOperation::AstroSwap {
                 offer_asset_info: AssetInfo::Token {
@@ -75,6 +89,8 @@ fn execute_swap_operations() {
                 ask_asset_info: AssetInfo::NativeToken {
                     denom: "uluna".to_string(),
                 },
+                max_spread: None,
+                belief_price: None,
             },
             SwapOperation::AstroSwap {
                 offer_asset_info: AssetInfo::NativeToken {
@@ -83,11 +99,14 @@ fn execute_swap_operations() {
                 ask_asset_info: AssetInfo::Token {
                     contract_addr: Addr::unchecked("asset0002"),
                 },
+                max_spread: None,
+                belief_price: None,
             },
         ],
         minimum_receive: Some(Uint128::from(1000000u128)),
         to: None,
         max_spread: None,
+        referral: None,
     };
 
     let env = mock_env();
@@ -108,6 +127,8 @@ fn execute_swap_operations() {
                             ask_asset_info: AssetInfo::Token {
                                 contract_addr: Addr::unchecked("asset0001"),
                             },
+                            max_spread: None,
+                            belief_price: None,
                         },
                         to: None,
                         max_spread: None,
@@ -132,6 +153,8 @@ fn execute_swap_operations() {
                             ask_asset_info: AssetInfo::NativeToken {
                                 denom: "uluna".to_string(),
                             },
+                            max_spread: None,
+                            belief_price: None,
                         },
                         to: None,
                         max_spread: None,
@@ -156,6 +179,8 @@ fn execute_swap_operations() {
                             ask_asset_info: AssetInfo::Token {
                                 contract_addr: Addr::unchecked("asset0002"),
                             },
+                            max_spread: None,
+                            belief_price: None,
                         },
                         to: Some(String::from("addr0000")),
                         max_spread: None,
@@ -202,6 +227,8 @@ fn execute_swap_operations() {
                     ask_asset_info: AssetInfo::Token {
                         contract_addr: Addr::unchecked("asset0001"),
                     },
+                    max_spread: None,
+                    belief_price: None,
                 },
                 SwapOperation::AstroSwap {
                     offer_asset_info: AssetInfo::Token {
@@ -210,6 +237,8 @@ fn execute_swap_operations() {
                     ask_asset_info: AssetInfo::NativeToken {
                         denom: "uluna".to_string(),
                     },
+                    max_spread: None,
+                    belief_price: None,
                 },
                 SwapOperation::AstroSwap {
                     offer_asset_info: AssetInfo::NativeToken {
@@ -218,11 +247,14 @@ fn execute_swap_operations() {
                     ask_asset_info: AssetInfo::Token {
                         contract_addr: Addr::unchecked("asset0002"),
                     },
+                    max_spread: None,
+                    belief_price: None,
                 },
             ],
             minimum_receive: None,
             to: Some(String::from("addr0002")),
             max_spread: None,
+            referral: None,
         })
         .unwrap(),
     });
@@ -245,6 +277,8 @@ fn execute_swap_operations() {
                             ask_asset_info: AssetInfo::Token {
                                 contract_addr: Addr::unchecked("asset0001"),
                             },
+                            max_spread: None,
+                            belief_price: None,
                         },
                         to: None,
                         max_spread: None,
@@ -269,6 +303,8 @@ fn execute_swap_operations() {
                             ask_asset_info: AssetInfo::NativeToken {
                                 denom: "uluna".to_string(),
                             },
+                            max_spread: None,
+                            belief_price: None,
                         },
                         to: None,
                         max_spread: None,
@@ -293,6 +329,8 @@ fn execute_swap_operations() {
                             ask_asset_info: AssetInfo::Token {
                                 contract_addr: Addr::unchecked("asset0002"),
                             },
+                            max_spread: None,
+                            belief_price: None,
                         },
                         to: Some(String::from("addr0002")),
                         max_spread: None,
@@ -314,6 +352,9 @@ fn execute_swap_operation() {
     let mut deps = mock_dependencies(&[]);
     let msg = InstantiateMsg {
         astroport_factory: String::from("astroportfactory"),
+        owner: String::from("owner"),
+        native_swap_handler: None,
+        max_referral_commission: None,
     };
 
     let env = mock_env();
@@ -349,6 +390,8 @@ fn execute_swap_operation() {
             ask_asset_info: AssetInfo::NativeToken {
                 denom: "uusd".to_string(),
             },
+            max_spread: None,
+            belief_price: None,
         },
         to: Some(String::from("addr0000")),
         max_spread: None,
@@ -384,12 +427,128 @@ fn execute_swap_operation() {
     );
 }
 
+#[test]
+fn execute_native_swap_operation() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        astroport_factory: String::from("astroportfactory"),
+        owner: String::from("owner"),
+        native_swap_handler: None,
+        max_referral_commission: None,
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    deps.querier.with_balance(&[(
+        &String::from(MOCK_CONTRACT_ADDR),
+        &[Coin {
+            amount: Uint128::new(1000000u128),
+            denom: "uusd".to_string(),
+        }],
+    )]);
+
+    let msg = ExecuteMsg::ExecuteSwapOperation {
+        operation: SwapOperation::NativeSwap {
+            offer_denom: "uusd".to_string(),
+            ask_denom: "ukrw".to_string(),
+        },
+        to: Some(String::from("addr0000")),
+        max_spread: None,
+        single: true,
+    };
+
+    // Native swaps are disabled without a handler
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+    assert_eq!(res, ContractError::NativeSwapNotSupported {});
+
+    // Configs stored before 1.2.0 hold only the factory address
+    CONFIG_V111
+        .save(
+            deps.as_mut().storage,
+            &ConfigV111 {
+                astroport_factory: Addr::unchecked("astroportfactory"),
+            },
+        )
+        .unwrap();
+    set_contract_version(deps.as_mut().storage, "astroport-router", "1.1.1").unwrap();
+
+    let msg_migrate = MigrateMsg {
+        owner: String::from("owner"),
+        native_swap_handler: Some(String::from("handler")),
+        max_referral_commission: None,
+    };
+    migrate(deps.as_mut(), mock_env(), msg_migrate.clone()).unwrap();
+
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.astroport_factory, String::from("astroportfactory"));
+    assert_eq!(config.owner, String::from("owner"));
+    assert_eq!(config.native_swap_handler, Some(String::from("handler")));
+    assert_eq!(
+        config.max_referral_commission,
+        DEFAULT_MAX_REFERRAL_COMMISSION
+    );
+
+    // The current contract version can't be migrated to itself
+    let err = migrate(deps.as_mut(), mock_env(), msg_migrate).unwrap_err();
+    assert_eq!(err, ContractError::MigrationError {});
+
+    // Only the owner can update the config
+    let update_msg = ExecuteMsg::UpdateConfig {
+        native_swap_handler: Some(UpdateAddr::Set(String::from("new_handler"))),
+        max_referral_commission: None,
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        update_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        update_msg,
+    )
+    .unwrap();
+
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(
+        config.native_swap_handler,
+        Some(String::from("new_handler"))
+    );
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: String::from("new_handler"),
+            funds: coins(1000000u128, "uusd"),
+            msg: to_binary(&NativeSwapHandlerExecuteMsg::Swap {
+                ask_denom: "ukrw".to_string(),
+                to: Some(String::from("addr0000")),
+            })
+            .unwrap(),
+        })]
+    );
+}
+
 #[test]
 fn query_buy_with_routes() {
     let mut deps = mock_dependencies(&[]);
 
     let msg = InstantiateMsg {
         astroport_factory: String::from("astroportfactory"),
+        owner: String::from("owner"),
+        native_swap_handler: None,
+        max_referral_commission: None,
     };
 
     let env = mock_env();
@@ -408,6 +567,8 @@ fn query_buy_with_routes() {
                 ask_asset_info: AssetInfo::Token {
                     contract_addr: Addr::unchecked("asset0000"),
                 },
+                max_spread: None,
+                belief_price: None,
             },
             SwapOperation::AstroSwap {
                 offer_asset_info: AssetInfo::Token {
@@ -416,8 +577,11 @@ fn query_buy_with_routes() {
                 ask_asset_info: AssetInfo::NativeToken {
                     denom: "uluna".to_string(),
                 },
+                max_spread: None,
+                belief_price: None,
             },
         ],
+        referral_commission: None,
     };
     deps.querier.with_astroport_pairs(&[
         (&"ukrwasset0000".to_string(), &String::from("pair0000")),
@@ -530,6 +694,9 @@ fn assert_maximum_receive_swap_operations() {
     let mut deps = mock_dependencies(&[]);
     let msg = InstantiateMsg {
         astroport_factory: String::from("astroportfactory"),
+        owner: String::from("owner"),
+        native_swap_handler: None,
+        max_referral_commission: None,
     };
 
     let env = mock_env();
@@ -549,6 +716,7 @@ fn assert_maximum_receive_swap_operations() {
         minimum_receive: None,
         to: None,
         max_spread: None,
+        referral: None,
     };
 
     let env = mock_env();
@@ -0,0 +1,482 @@
+use astroport::asset::{addr_opt_validate, pair_info_by_pool, Asset, AssetInfo, PairInfo};
+use astroport::pair::{
+    Cw20HookMsg as PairCw20HookMsg, ExecuteMsg as PairExecuteMsg, PoolResponse,
+    QueryMsg as PairQueryMsg,
+};
+use astroport::querier::{query_pair_info, simulate};
+use astroport::router::{ExecuteMsg, SwapOperation};
+use cosmwasm_std::{
+    to_binary, wasm_execute, Addr, Coin, CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo,
+    Response, StdError, Uint128,
+};
+use cw20::Cw20ExecuteMsg;
+
+use crate::contract::{assert_operations, exact_offer_route_msgs, minimum_receive_msg};
+use crate::error::ContractError;
+use crate::operations::asset_into_swap_msg;
+use crate::state::CONFIG;
+
+/// The maximum number of simulations used to find the optimal zap swap amount
+const ZAP_SEARCH_ITERATIONS: u8 = 32;
+
+/// Returns the pair information after checking that the pair is registered in the factory.
+fn query_factory_pair(deps: Deps, pair_addr: &Addr) -> Result<PairInfo, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let pair_info: PairInfo = deps
+        .querier
+        .query_wasm_smart(pair_addr, &PairQueryMsg::Pair {})?;
+    let factory_pair = query_pair_info(
+        &deps.querier,
+        config.astroport_factory,
+        &pair_info.asset_infos,
+    )?;
+
+    if factory_pair.contract_addr != *pair_addr {
+        return Err(ContractError::InvalidZapPair(pair_addr.to_string()));
+    }
+
+    Ok(pair_info)
+}
+
+/// Returns the second asset of a pair with two assets.
+fn other_pair_asset(
+    pair_info: &PairInfo,
+    asset_info: &AssetInfo,
+) -> Result<AssetInfo, ContractError> {
+    match &pair_info.asset_infos[..] {
+        [first, second] if first == asset_info => Ok(second.clone()),
+        [first, second] if second == asset_info => Ok(first.clone()),
+        [_, _] => Err(ContractError::AssetNotInPair(asset_info.to_string())),
+        _ => Err(ContractError::InvalidZapPair(
+            pair_info.contract_addr.to_string(),
+        )),
+    }
+}
+
+/// Swaps the offer asset into one of the pair assets (if needed), then swaps a part of it into
+/// the other pair asset and provides liquidity. Every step is executed as a separate message
+/// as amounts depend on the previous swaps.
+///
+/// * **sender** address that zaps tokens.
+///
+/// * **offer_asset** asset that is sent to the router.
+///
+/// * **pair_addr** pair to provide liquidity to.
+///
+/// * **operations** swap operations which convert the offer asset into one of the pair assets.
+///
+/// * **receiver** receiver of LP tokens.
+#[allow(clippy::too_many_arguments)]
+pub fn execute_zap_in(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    offer_asset: Asset,
+    pair_addr: String,
+    operations: Vec<SwapOperation>,
+    slippage_tolerance: Option<Decimal>,
+    auto_stake: Option<bool>,
+    receiver: Option<String>,
+    max_spread: Option<Decimal>,
+) -> Result<Response, ContractError> {
+    let pair_addr = deps.api.addr_validate(&pair_addr)?;
+    let pair_info = query_factory_pair(deps.as_ref(), &pair_addr)?;
+    let receiver = addr_opt_validate(deps.api, &receiver)?.unwrap_or(sender);
+
+    let mut messages = vec![];
+    let zap_asset_info = if operations.is_empty() {
+        offer_asset.info.clone()
+    } else {
+        assert_operations(deps.api, &operations)?;
+        if operations[0].get_offer_asset_info() != offer_asset.info {
+            return Err(StdError::generic_err(format!(
+                "Operations must start with {}",
+                offer_asset.info
+            ))
+            .into());
+        }
+
+        let target_asset_info = operations.last().unwrap().get_target_asset_info();
+        let config = CONFIG.load(deps.storage)?;
+        messages.extend(exact_offer_route_msgs(
+            deps.as_ref(),
+            &env,
+            &config,
+            operations,
+            offer_asset.amount,
+            &env.contract.address,
+            max_spread,
+        )?);
+
+        target_asset_info
+    };
+    other_pair_asset(&pair_info, &zap_asset_info)?;
+
+    messages.push(
+        wasm_execute(
+            &env.contract.address,
+            &ExecuteMsg::ZapInSwap {
+                pair_addr: pair_addr.to_string(),
+                offer_asset_info: zap_asset_info,
+                max_spread,
+            },
+            vec![],
+        )?
+        .into(),
+    );
+    messages.push(
+        wasm_execute(
+            &env.contract.address,
+            &ExecuteMsg::ZapInProvide {
+                pair_addr: pair_addr.to_string(),
+                slippage_tolerance,
+                auto_stake,
+                receiver: receiver.to_string(),
+            },
+            vec![],
+        )?
+        .into(),
+    );
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "zap_in")
+        .add_attribute("pair", pair_addr)
+        .add_attribute("receiver", receiver))
+}
+
+/// Swaps a part of the router balance of one pair asset into the other pair asset
+/// so that the remaining amount and the swap result have the proportions of the pool after the swap.
+///
+/// * **pair_addr** pair to provide liquidity to.
+///
+/// * **offer_asset_info** pair asset held by the router.
+pub fn zap_in_swap(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    pair_addr: String,
+    offer_asset_info: AssetInfo,
+    max_spread: Option<Decimal>,
+) -> Result<Response, ContractError> {
+    if env.contract.address != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let pair_addr = Addr::unchecked(pair_addr);
+    let pair_info: PairInfo = deps
+        .querier
+        .query_wasm_smart(&pair_addr, &PairQueryMsg::Pair {})?;
+    let ask_asset_info = other_pair_asset(&pair_info, &offer_asset_info)?;
+
+    let amount = offer_asset_info.query_pool(&deps.querier, &env.contract.address)?;
+    let swap_amount = optimal_swap_amount(
+        deps.as_ref(),
+        &pair_addr,
+        &offer_asset_info,
+        &ask_asset_info,
+        amount,
+    )?;
+
+    let mut response = Response::new()
+        .add_attribute("action", "zap_in_swap")
+        .add_attribute("swap_amount", swap_amount);
+    if !swap_amount.is_zero() {
+        response = response.add_message(asset_into_swap_msg(
+            pair_addr.to_string(),
+            Asset {
+                info: offer_asset_info,
+                amount: swap_amount,
+            },
+            ask_asset_info,
+            max_spread,
+            None,
+            None,
+            true,
+        )?);
+    }
+
+    Ok(response)
+}
+
+/// Finds the amount of offer assets to swap using pair simulations. After the swap the ratio of
+/// the remaining offer amount to the ask amount received must match the ratio of pool reserves.
+///
+/// * **amount** total amount of offer assets.
+fn optimal_swap_amount(
+    deps: Deps,
+    pair_addr: &Addr,
+    offer_asset_info: &AssetInfo,
+    ask_asset_info: &AssetInfo,
+    amount: Uint128,
+) -> Result<Uint128, ContractError> {
+    let pool: PoolResponse = deps
+        .querier
+        .query_wasm_smart(pair_addr, &PairQueryMsg::Pool {})?;
+    let reserve = |asset_info: &AssetInfo| {
+        pool.assets
+            .iter()
+            .find(|asset| asset.info == *asset_info)
+            .map(|asset| asset.amount)
+            .unwrap_or_default()
+    };
+    let offer_reserve = reserve(offer_asset_info);
+    let ask_reserve = reserve(ask_asset_info);
+    if offer_reserve.is_zero() || ask_reserve.is_zero() {
+        return Err(StdError::generic_err("One of the pools is empty").into());
+    }
+
+    let (mut low, mut high) = (Uint128::zero(), amount);
+    for _ in 0..ZAP_SEARCH_ITERATIONS {
+        if high - low <= Uint128::one() {
+            break;
+        }
+
+        let mid = (low + high) / Uint128::new(2);
+        let return_amount = simulate(
+            &deps.querier,
+            pair_addr,
+            &Asset {
+                info: offer_asset_info.clone(),
+                amount: mid,
+            },
+        )?
+        .return_amount;
+
+        // (amount - mid) / return_amount vs (offer_reserve + mid) / (ask_reserve - return_amount)
+        let remaining_ratio = (amount - mid).full_mul(ask_reserve.saturating_sub(return_amount));
+        let pool_ratio = return_amount.full_mul(offer_reserve + mid);
+        if remaining_ratio > pool_ratio {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+
+    Ok(low)
+}
+
+/// Provides the whole router balance of the pair assets as liquidity.
+///
+/// * **pair_addr** pair to provide liquidity to.
+///
+/// * **receiver** receiver of LP tokens.
+pub fn zap_in_provide(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    pair_addr: String,
+    slippage_tolerance: Option<Decimal>,
+    auto_stake: Option<bool>,
+    receiver: String,
+) -> Result<Response, ContractError> {
+    if env.contract.address != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let pair_info: PairInfo = deps
+        .querier
+        .query_wasm_smart(&pair_addr, &PairQueryMsg::Pair {})?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut funds = vec![];
+    let mut assets = vec![];
+    for asset_info in pair_info.asset_infos {
+        let amount = asset_info.query_pool(&deps.querier, &env.contract.address)?;
+        match &asset_info {
+            // Bank module rejects zero coins
+            AssetInfo::NativeToken { .. } if amount.is_zero() => {}
+            AssetInfo::NativeToken { denom } => funds.push(Coin {
+                denom: denom.clone(),
+                amount,
+            }),
+            AssetInfo::Token { contract_addr } => messages.push(
+                wasm_execute(
+                    contract_addr,
+                    &Cw20ExecuteMsg::IncreaseAllowance {
+                        spender: pair_addr.clone(),
+                        amount,
+                        expires: None,
+                    },
+                    vec![],
+                )?
+                .into(),
+            ),
+        }
+        assets.push(Asset {
+            info: asset_info,
+            amount,
+        });
+    }
+    funds.sort_by(|a, b| a.denom.cmp(&b.denom));
+
+    messages.push(
+        wasm_execute(
+            &pair_addr,
+            &PairExecuteMsg::ProvideLiquidity {
+                assets: assets.clone(),
+                slippage_tolerance,
+                auto_stake,
+                receiver: Some(receiver),
+            },
+            funds,
+        )?
+        .into(),
+    );
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "zap_in_provide")
+        .add_attribute(
+            "assets",
+            assets
+                .iter()
+                .map(|asset| asset.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+        ))
+}
+
+/// Withdraws liquidity with LP tokens sent to the router and swaps all withdrawn assets
+/// into one of the pair assets through the same pair. Swaps are built after the withdrawal
+/// as withdrawn amounts are known only after it.
+///
+/// * **sender** address that zaps LP tokens.
+///
+/// * **lp_token** LP token contract address.
+///
+/// * **amount** amount of LP tokens to withdraw.
+///
+/// * **ask_asset_info** pair asset to receive.
+///
+/// * **minimum_receive** used to guarantee that the ask amount is above a minimum amount.
+///
+/// * **to** recipient of the ask tokens.
+#[allow(clippy::too_many_arguments)]
+pub fn execute_zap_out(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    lp_token: Addr,
+    amount: Uint128,
+    ask_asset_info: AssetInfo,
+    minimum_receive: Option<Uint128>,
+    to: Option<String>,
+    max_spread: Option<Decimal>,
+) -> Result<Response, ContractError> {
+    let pair_info = pair_info_by_pool(&deps.querier, &lp_token)
+        .map_err(|_| ContractError::InvalidZapPair(lp_token.to_string()))?;
+    if pair_info.liquidity_token != lp_token {
+        return Err(ContractError::InvalidZapPair(lp_token.to_string()));
+    }
+    query_factory_pair(deps.as_ref(), &pair_info.contract_addr)?;
+    if !pair_info.asset_infos.contains(&ask_asset_info) {
+        return Err(ContractError::AssetNotInPair(ask_asset_info.to_string()));
+    }
+
+    let to = addr_opt_validate(deps.api, &to)?.unwrap_or(sender);
+
+    let mut messages: Vec<CosmosMsg> = vec![wasm_execute(
+        &lp_token,
+        &Cw20ExecuteMsg::Send {
+            contract: pair_info.contract_addr.to_string(),
+            amount,
+            msg: to_binary(&PairCw20HookMsg::WithdrawLiquidity { assets: vec![] })?,
+        },
+        vec![],
+    )?
+    .into()];
+
+    // Minimum receive assertion must record the recipient balance before any transfers
+    let assert_msg = minimum_receive
+        .map(|minimum_receive| {
+            minimum_receive_msg(
+                deps.as_ref(),
+                &env,
+                ask_asset_info.clone(),
+                minimum_receive,
+                &to,
+            )
+        })
+        .transpose()?;
+
+    messages.push(
+        wasm_execute(
+            &env.contract.address,
+            &ExecuteMsg::ZapOutSwap {
+                pair_addr: pair_info.contract_addr.to_string(),
+                ask_asset_info,
+                to: to.to_string(),
+                max_spread,
+            },
+            vec![],
+        )?
+        .into(),
+    );
+    messages.extend(assert_msg);
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "zap_out")
+        .add_attribute("pair", pair_info.contract_addr)
+        .add_attribute("withdrawn_share", amount))
+}
+
+/// Sends the router balance of the ask asset to the recipient and swaps the router balance
+/// of other pair assets into the ask asset through the pair.
+///
+/// * **pair_addr** pair which liquidity was withdrawn from.
+///
+/// * **ask_asset_info** pair asset to receive.
+///
+/// * **to** recipient of the ask tokens.
+pub fn zap_out_swap(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    pair_addr: String,
+    ask_asset_info: AssetInfo,
+    to: String,
+    max_spread: Option<Decimal>,
+) -> Result<Response, ContractError> {
+    if env.contract.address != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let pair_info: PairInfo = deps
+        .querier
+        .query_wasm_smart(&pair_addr, &PairQueryMsg::Pair {})?;
+
+    let mut messages = vec![];
+    let mut assets = vec![];
+    for asset_info in pair_info.asset_infos {
+        let amount = asset_info.query_pool(&deps.querier, &env.contract.address)?;
+        if amount.is_zero() {
+            continue;
+        }
+
+        let asset = Asset {
+            info: asset_info,
+            amount,
+        };
+        assets.push(asset.to_string());
+        if asset.info == ask_asset_info {
+            messages.push(asset.into_msg(&to)?);
+        } else {
+            messages.push(asset_into_swap_msg(
+                pair_addr.clone(),
+                asset,
+                ask_asset_info.clone(),
+                max_spread,
+                None,
+                Some(to.clone()),
+                true,
+            )?);
+        }
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "zap_out_swap")
+        .add_attribute("assets", assets.join(", ")))
+}
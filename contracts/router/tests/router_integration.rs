@@ -1,12 +1,21 @@
 mod factory_helper;
 
 use crate::factory_helper::{instantiate_token, mint, FactoryHelper};
-use astroport::asset::token_asset_info;
+use astroport::asset::{token_asset, token_asset_info};
 use astroport::factory::PairType;
-use astroport::router::{ExecuteMsg, InstantiateMsg, SwapOperation};
-use cosmwasm_std::{to_binary, Addr, Empty};
-use cw20::Cw20ExecuteMsg;
-use cw_multi_test::{App, Contract, ContractWrapper, Executor};
+use astroport::router::{
+    BestRouteResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, NativeSwapHandlerExecuteMsg,
+    NativeSwapHandlerQueryMsg, NativeSwapReverseSimulationResponse, NativeSwapSimulationResponse,
+    QueryMsg, ReverseSimulateSwapOperationsResponse, SimulateSplitSwapResponse,
+    SimulateSwapOperationsDetailedResponse, SimulateSwapOperationsResponse, SwapOperation,
+};
+use astroport_router::error::ContractError;
+use cosmwasm_std::{
+    coin, coins, to_binary, Addr, BankMsg, Binary, Decimal, Empty, MessageInfo, Response,
+    StdResult, Uint128,
+};
+use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg};
+use cw_multi_test::{App, BankSudo, Contract, ContractWrapper, Executor, SudoMsg};
 
 fn router_contract() -> Box<dyn Contract<Empty>> {
     Box::new(ContractWrapper::new_with_empty(
@@ -45,6 +54,9 @@ fn router_does_not_enforce_spread_assertion() {
             owner.clone(),
             &InstantiateMsg {
                 astroport_factory: helper.factory.to_string(),
+                owner: owner.to_string(),
+                native_swap_handler: None,
+                max_referral_commission: None,
             },
             &[],
             "router",
@@ -65,15 +77,20 @@ fn router_does_not_enforce_spread_assertion() {
                     SwapOperation::AstroSwap {
                         offer_asset_info: token_asset_info(token_x.clone()),
                         ask_asset_info: token_asset_info(token_y.clone()),
+                        max_spread: None,
+                        belief_price: None,
                     },
                     SwapOperation::AstroSwap {
                         offer_asset_info: token_asset_info(token_y.clone()),
                         ask_asset_info: token_asset_info(token_z.clone()),
+                        max_spread: None,
+                        belief_price: None,
                     },
                 ],
                 minimum_receive: None,
                 to: None,
                 max_spread: None,
+                referral: None,
             })
             .unwrap(),
         },
@@ -94,10 +111,13 @@ fn router_does_not_enforce_spread_assertion() {
                     operations: vec![SwapOperation::AstroSwap {
                         offer_asset_info: token_asset_info(token_x.clone()),
                         ask_asset_info: token_asset_info(token_y.clone()),
+                        max_spread: None,
+                        belief_price: None,
                     }],
                     minimum_receive: None,
                     to: None,
                     max_spread: None,
+                    referral: None,
                 })
                 .unwrap(),
             },
@@ -109,3 +129,1195 @@ fn router_does_not_enforce_spread_assertion() {
         err.downcast().unwrap()
     )
 }
+
+#[test]
+fn find_and_execute_best_route() {
+    let mut app = App::default();
+
+    let owner = Addr::unchecked("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let token_x = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOX", None);
+    let token_y = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOY", None);
+    let token_z = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOZ", None);
+    let token_w = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOW", None);
+    let token_v = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOV", None);
+
+    // Direct X<>Z pool is shallow thus the route through Y gives better price
+    for (a, b, liq) in [
+        (&token_x, &token_z, 10_000000),
+        (&token_x, &token_y, 100_000_000000),
+        (&token_y, &token_z, 100_000_000000),
+        (&token_z, &token_w, 100_000_000000),
+    ] {
+        let pair = helper
+            .create_pair_with_addr(&mut app, &owner, PairType::Xyk {}, [a, b], None)
+            .unwrap();
+        mint(&mut app, &owner, a, liq, &pair).unwrap();
+        mint(&mut app, &owner, b, liq, &pair).unwrap();
+    }
+
+    let router_code = app.store_code(router_contract());
+    let router = app
+        .instantiate_contract(
+            router_code,
+            owner.clone(),
+            &InstantiateMsg {
+                astroport_factory: helper.factory.to_string(),
+                owner: owner.to_string(),
+                native_swap_handler: None,
+                max_referral_commission: None,
+            },
+            &[],
+            "router",
+            None,
+        )
+        .unwrap();
+
+    let offer_asset = token_asset(token_x.clone(), 1_000000u128.into());
+
+    let err = app
+        .wrap()
+        .query_wasm_smart::<BestRouteResponse>(
+            &router,
+            &QueryMsg::FindBestRoute {
+                offer_asset: offer_asset.clone(),
+                ask_asset_info: token_asset_info(token_z.clone()),
+                max_hops: Some(4),
+            },
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains("max_hops must be within [1, 3]"));
+
+    let err = app
+        .wrap()
+        .query_wasm_smart::<BestRouteResponse>(
+            &router,
+            &QueryMsg::FindBestRoute {
+                offer_asset: offer_asset.clone(),
+                ask_asset_info: token_asset_info(token_v.clone()),
+                max_hops: None,
+            },
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains("No route found"));
+
+    // Only the direct pool is available with 1 hop
+    let direct: BestRouteResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &router,
+            &QueryMsg::FindBestRoute {
+                offer_asset: offer_asset.clone(),
+                ask_asset_info: token_asset_info(token_z.clone()),
+                max_hops: Some(1),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        direct.operations,
+        vec![SwapOperation::AstroSwap {
+            offer_asset_info: token_asset_info(token_x.clone()),
+            ask_asset_info: token_asset_info(token_z.clone()),
+            max_spread: None,
+            belief_price: None,
+        }]
+    );
+
+    let best: BestRouteResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &router,
+            &QueryMsg::FindBestRoute {
+                offer_asset: offer_asset.clone(),
+                ask_asset_info: token_asset_info(token_z.clone()),
+                max_hops: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        best.operations,
+        vec![
+            SwapOperation::AstroSwap {
+                offer_asset_info: token_asset_info(token_x.clone()),
+                ask_asset_info: token_asset_info(token_y.clone()),
+                max_spread: None,
+                belief_price: None,
+            },
+            SwapOperation::AstroSwap {
+                offer_asset_info: token_asset_info(token_y.clone()),
+                ask_asset_info: token_asset_info(token_z.clone()),
+                max_spread: None,
+                belief_price: None,
+            },
+        ]
+    );
+    assert!(best.amount > direct.amount);
+
+    // X->Y->Z->W gives the best output but it doesn't fit into 2 hops
+    for (max_hops, expected_path) in [
+        (Some(2), vec![&token_x, &token_z, &token_w]),
+        (None, vec![&token_x, &token_y, &token_z, &token_w]),
+    ] {
+        let route: BestRouteResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &router,
+                &QueryMsg::FindBestRoute {
+                    offer_asset: offer_asset.clone(),
+                    ask_asset_info: token_asset_info(token_w.clone()),
+                    max_hops,
+                },
+            )
+            .unwrap();
+        let expected_operations: Vec<_> = expected_path
+            .windows(2)
+            .map(|pair| SwapOperation::AstroSwap {
+                offer_asset_info: token_asset_info(pair[0].clone()),
+                ask_asset_info: token_asset_info(pair[1].clone()),
+                max_spread: None,
+                belief_price: None,
+            })
+            .collect();
+        assert_eq!(route.operations, expected_operations);
+    }
+
+    let user = Addr::unchecked("user");
+    mint(&mut app, &owner, &token_x, 1_000000, &user).unwrap();
+    app.execute_contract(
+        user.clone(),
+        token_x.clone(),
+        &Cw20ExecuteMsg::Send {
+            contract: router.to_string(),
+            amount: offer_asset.amount,
+            msg: to_binary(&Cw20HookMsg::ExecuteBestRoute {
+                ask_asset_info: token_asset_info(token_z.clone()),
+                max_hops: None,
+                minimum_receive: Some(best.amount),
+                to: None,
+                max_spread: None,
+            })
+            .unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let balance: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &token_z,
+            &Cw20QueryMsg::Balance {
+                address: user.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(balance.balance, best.amount);
+    assert_ne!(balance.balance, Uint128::zero());
+}
+
+#[test]
+fn split_swap() {
+    let mut app = App::default();
+
+    let owner = Addr::unchecked("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let token_x = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOX", None);
+    let token_y = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOY", None);
+    let token_z = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOZ", None);
+
+    for (a, b) in [
+        (&token_x, &token_z),
+        (&token_x, &token_y),
+        (&token_y, &token_z),
+    ] {
+        let pair = helper
+            .create_pair_with_addr(&mut app, &owner, PairType::Xyk {}, [a, b], None)
+            .unwrap();
+        mint(&mut app, &owner, a, 100_000_000000, &pair).unwrap();
+        mint(&mut app, &owner, b, 100_000_000000, &pair).unwrap();
+    }
+
+    let router_code = app.store_code(router_contract());
+    let router = app
+        .instantiate_contract(
+            router_code,
+            owner.clone(),
+            &InstantiateMsg {
+                astroport_factory: helper.factory.to_string(),
+                owner: owner.to_string(),
+                native_swap_handler: None,
+                max_referral_commission: None,
+            },
+            &[],
+            "router",
+            None,
+        )
+        .unwrap();
+
+    let direct_route = vec![SwapOperation::AstroSwap {
+        offer_asset_info: token_asset_info(token_x.clone()),
+        ask_asset_info: token_asset_info(token_z.clone()),
+        max_spread: None,
+        belief_price: None,
+    }];
+    let bridge_route = vec![
+        SwapOperation::AstroSwap {
+            offer_asset_info: token_asset_info(token_x.clone()),
+            ask_asset_info: token_asset_info(token_y.clone()),
+            max_spread: None,
+            belief_price: None,
+        },
+        SwapOperation::AstroSwap {
+            offer_asset_info: token_asset_info(token_y.clone()),
+            ask_asset_info: token_asset_info(token_z.clone()),
+            max_spread: None,
+            belief_price: None,
+        },
+    ];
+
+    let err = app
+        .wrap()
+        .query_wasm_smart::<SimulateSplitSwapResponse>(
+            &router,
+            &QueryMsg::SimulateSplitSwap {
+                offer_amount: 10_000_000000u128.into(),
+                routes: vec![
+                    (Decimal::percent(30), direct_route.clone()),
+                    (Decimal::percent(30), bridge_route.clone()),
+                ],
+            },
+        )
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains(&ContractError::InvalidSplitWeights {}.to_string()));
+
+    let routes = vec![
+        (Decimal::percent(30), direct_route.clone()),
+        (Decimal::percent(70), bridge_route.clone()),
+    ];
+    let offer_amount = Uint128::new(10_000_000000);
+    let sim: SimulateSplitSwapResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &router,
+            &QueryMsg::SimulateSplitSwap {
+                offer_amount,
+                routes: routes.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(sim.routes_amounts.len(), 2);
+    assert_eq!(sim.amount, sim.routes_amounts.iter().sum::<Uint128>());
+
+    let user = Addr::unchecked("user");
+    mint(&mut app, &owner, &token_x, offer_amount.u128(), &user).unwrap();
+
+    // Minimum receive is checked against the total amount
+    let err = app
+        .execute_contract(
+            user.clone(),
+            token_x.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: router.to_string(),
+                amount: offer_amount,
+                msg: to_binary(&Cw20HookMsg::ExecuteSplitSwap {
+                    routes: routes.clone(),
+                    minimum_receive: Some(sim.amount + Uint128::one()),
+                    to: None,
+                    max_spread: Some(Decimal::percent(50)),
+                })
+                .unwrap(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::AssertionMinimumReceive {
+            receive: sim.amount + Uint128::one(),
+            amount: sim.amount,
+        },
+        err.downcast().unwrap()
+    );
+
+    app.execute_contract(
+        user.clone(),
+        token_x.clone(),
+        &Cw20ExecuteMsg::Send {
+            contract: router.to_string(),
+            amount: offer_amount,
+            msg: to_binary(&Cw20HookMsg::ExecuteSplitSwap {
+                routes,
+                minimum_receive: Some(sim.amount),
+                to: None,
+                max_spread: Some(Decimal::percent(50)),
+            })
+            .unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let balance: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &token_z,
+            &Cw20QueryMsg::Balance {
+                address: user.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(balance.balance, sim.amount);
+
+    // Nothing is left on the router
+    for token in [&token_x, &token_y, &token_z] {
+        let balance: BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                token,
+                &Cw20QueryMsg::Balance {
+                    address: router.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(balance.balance, Uint128::zero());
+    }
+}
+
+#[test]
+fn exact_out_swap() {
+    let mut app = App::default();
+
+    let owner = Addr::unchecked("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let token_x = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOX", None);
+    let token_y = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOY", None);
+    let token_z = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOZ", None);
+
+    for (a, b) in [(&token_x, &token_y), (&token_y, &token_z)] {
+        let pair = helper
+            .create_pair_with_addr(&mut app, &owner, PairType::Xyk {}, [a, b], None)
+            .unwrap();
+        mint(&mut app, &owner, a, 100_000_000000, &pair).unwrap();
+        mint(&mut app, &owner, b, 100_000_000000, &pair).unwrap();
+    }
+
+    let router_code = app.store_code(router_contract());
+    let router = app
+        .instantiate_contract(
+            router_code,
+            owner.clone(),
+            &InstantiateMsg {
+                astroport_factory: helper.factory.to_string(),
+                owner: owner.to_string(),
+                native_swap_handler: None,
+                max_referral_commission: None,
+            },
+            &[],
+            "router",
+            None,
+        )
+        .unwrap();
+
+    let operations = vec![
+        SwapOperation::AstroSwap {
+            offer_asset_info: token_asset_info(token_x.clone()),
+            ask_asset_info: token_asset_info(token_y.clone()),
+            max_spread: None,
+            belief_price: None,
+        },
+        SwapOperation::AstroSwap {
+            offer_asset_info: token_asset_info(token_y.clone()),
+            ask_asset_info: token_asset_info(token_z.clone()),
+            max_spread: None,
+            belief_price: None,
+        },
+    ];
+    let ask_amount = Uint128::new(1_000_000000);
+
+    let sim: ReverseSimulateSwapOperationsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &router,
+            &QueryMsg::ReverseSimulateSwapOperations {
+                ask_amount,
+                operations: operations.clone(),
+            },
+        )
+        .unwrap();
+    assert!(sim.offer_amount > ask_amount);
+
+    let user = Addr::unchecked("user");
+    let sent_amount = Uint128::new(2_000_000000);
+    mint(&mut app, &owner, &token_x, sent_amount.u128(), &user).unwrap();
+
+    let err = app
+        .execute_contract(
+            user.clone(),
+            token_x.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: router.to_string(),
+                amount: sent_amount,
+                msg: to_binary(&Cw20HookMsg::ExecuteSwapOperationsExactOut {
+                    operations: operations.clone(),
+                    ask_amount,
+                    max_offer: Some(ask_amount),
+                    to: None,
+                    max_spread: None,
+                })
+                .unwrap(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::MaxOfferAssertion {
+            max_offer: ask_amount,
+            offer_amount: sim.offer_amount,
+        },
+        err.downcast().unwrap()
+    );
+
+    app.execute_contract(
+        user.clone(),
+        token_x.clone(),
+        &Cw20ExecuteMsg::Send {
+            contract: router.to_string(),
+            amount: sent_amount,
+            msg: to_binary(&Cw20HookMsg::ExecuteSwapOperationsExactOut {
+                operations,
+                ask_amount,
+                max_offer: None,
+                to: None,
+                max_spread: None,
+            })
+            .unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let query_balance = |app: &App, token: &Addr, address: &Addr| {
+        app.wrap()
+            .query_wasm_smart::<BalanceResponse>(
+                token,
+                &Cw20QueryMsg::Balance {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap()
+            .balance
+    };
+
+    // The user received the ask amount and got leftover offer tokens back
+    assert!(query_balance(&app, &token_z, &user) >= ask_amount);
+    assert_eq!(
+        query_balance(&app, &token_x, &user),
+        sent_amount - sim.offer_amount
+    );
+    assert_eq!(query_balance(&app, &token_x, &router), Uint128::zero());
+    assert_eq!(query_balance(&app, &token_y, &router), Uint128::zero());
+}
+
+fn native_swap_handler_contract() -> Box<dyn Contract<Empty>> {
+    // Mock market module which gives two ask coins for one offer coin
+    Box::new(ContractWrapper::new_with_empty(
+        |_, _, info: MessageInfo, msg: NativeSwapHandlerExecuteMsg| -> StdResult<Response> {
+            match msg {
+                NativeSwapHandlerExecuteMsg::Swap { ask_denom, to } => {
+                    let offer_coin = &info.funds[0];
+                    Ok(Response::new().add_message(BankMsg::Send {
+                        to_address: to.unwrap_or_else(|| info.sender.to_string()),
+                        amount: coins(offer_coin.amount.u128() * 2, ask_denom),
+                    }))
+                }
+            }
+        },
+        |_, _, _, _: Empty| -> StdResult<Response> { Ok(Response::new()) },
+        |_, _, msg: NativeSwapHandlerQueryMsg| -> StdResult<Binary> {
+            match msg {
+                NativeSwapHandlerQueryMsg::Simulate { offer_coin, .. } => {
+                    to_binary(&NativeSwapSimulationResponse {
+                        return_amount: offer_coin.amount * Uint128::new(2),
+                    })
+                }
+                NativeSwapHandlerQueryMsg::ReverseSimulate { ask_coin, .. } => {
+                    to_binary(&NativeSwapReverseSimulationResponse {
+                        offer_amount: (ask_coin.amount + Uint128::one()) / Uint128::new(2),
+                    })
+                }
+            }
+        },
+    ))
+}
+
+#[test]
+fn native_swap_via_handler() {
+    let mut app = App::default();
+
+    let owner = Addr::unchecked("owner");
+    let user = Addr::unchecked("user");
+    let helper = FactoryHelper::init(&mut app, &owner);
+
+    let handler_code = app.store_code(native_swap_handler_contract());
+    let handler = app
+        .instantiate_contract(handler_code, owner.clone(), &Empty {}, &[], "handler", None)
+        .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: handler.to_string(),
+        amount: vec![coin(1_000_000000, "uluna"), coin(1_000_000000, "ukrw")],
+    }))
+    .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: user.to_string(),
+        amount: coins(300_000000, "uusd"),
+    }))
+    .unwrap();
+
+    let router_code = app.store_code(router_contract());
+    let operations = vec![
+        SwapOperation::NativeSwap {
+            offer_denom: "uusd".to_string(),
+            ask_denom: "uluna".to_string(),
+        },
+        SwapOperation::NativeSwap {
+            offer_denom: "uluna".to_string(),
+            ask_denom: "ukrw".to_string(),
+        },
+    ];
+    let swap_msg = ExecuteMsg::ExecuteSwapOperations {
+        operations: operations.clone(),
+        minimum_receive: Some(Uint128::new(400_000000)),
+        to: None,
+        max_spread: None,
+        referral: None,
+    };
+
+    // Native swaps are disabled if the handler is not set
+    let router = app
+        .instantiate_contract(
+            router_code,
+            owner.clone(),
+            &InstantiateMsg {
+                astroport_factory: helper.factory.to_string(),
+                owner: owner.to_string(),
+                native_swap_handler: None,
+                max_referral_commission: None,
+            },
+            &[],
+            "router",
+            None,
+        )
+        .unwrap();
+    let err = app
+        .execute_contract(user.clone(), router, &swap_msg, &coins(100_000000, "uusd"))
+        .unwrap_err();
+    assert_eq!(
+        ContractError::NativeSwapNotSupported {},
+        err.downcast().unwrap()
+    );
+
+    let router = app
+        .instantiate_contract(
+            router_code,
+            owner.clone(),
+            &InstantiateMsg {
+                astroport_factory: helper.factory.to_string(),
+                owner: owner.to_string(),
+                native_swap_handler: Some(handler.to_string()),
+                max_referral_commission: None,
+            },
+            &[],
+            "router",
+            None,
+        )
+        .unwrap();
+
+    let sim: SimulateSwapOperationsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &router,
+            &QueryMsg::SimulateSwapOperations {
+                offer_amount: Uint128::new(100_000000),
+                operations: operations.clone(),
+                referral_commission: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(sim.amount.u128(), 400_000000);
+
+    app.execute_contract(
+        user.clone(),
+        router.clone(),
+        &swap_msg,
+        &coins(100_000000, "uusd"),
+    )
+    .unwrap();
+    let balance = app.wrap().query_balance(&user, "ukrw").unwrap();
+    assert_eq!(balance.amount.u128(), 400_000000);
+
+    // Split swap routes the exact offer amount of the first hop via the handler
+    app.execute_contract(
+        user.clone(),
+        router.clone(),
+        &ExecuteMsg::ExecuteSplitSwap {
+            routes: vec![
+                (Decimal::percent(50), operations.clone()),
+                (Decimal::percent(50), operations.clone()),
+            ],
+            minimum_receive: Some(Uint128::new(400_000000)),
+            to: None,
+            max_spread: None,
+        },
+        &coins(100_000000, "uusd"),
+    )
+    .unwrap();
+    let balance = app.wrap().query_balance(&user, "ukrw").unwrap();
+    assert_eq!(balance.amount.u128(), 800_000000);
+
+    // Native swaps are reverse simulated by the handler thus exact out swaps are supported
+    let reverse_sim: ReverseSimulateSwapOperationsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &router,
+            &QueryMsg::ReverseSimulateSwapOperations {
+                ask_amount: Uint128::new(100_000001),
+                operations: operations.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(reverse_sim.offer_amount.u128(), 25_000001);
+
+    app.execute_contract(
+        user.clone(),
+        router,
+        &ExecuteMsg::ExecuteSwapOperationsExactOut {
+            operations,
+            ask_amount: Uint128::new(100_000000),
+            max_offer: None,
+            to: None,
+            max_spread: None,
+        },
+        &coins(100_000000, "uusd"),
+    )
+    .unwrap();
+    let balance = app.wrap().query_balance(&user, "ukrw").unwrap();
+    assert_eq!(balance.amount.u128(), 900_000000);
+    // 25 USD were swapped while the rest was refunded
+    let balance = app.wrap().query_balance(&user, "uusd").unwrap();
+    assert_eq!(balance.amount.u128(), 75_000000);
+}
+
+#[test]
+fn referral_commission() {
+    let mut app = App::default();
+
+    let owner = Addr::unchecked("owner");
+    let user = Addr::unchecked("user");
+    let referrer = Addr::unchecked("referrer");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let token_x = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOX", None);
+    let token_y = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOY", None);
+
+    let pair = helper
+        .create_pair_with_addr(
+            &mut app,
+            &owner,
+            PairType::Xyk {},
+            [&token_x, &token_y],
+            None,
+        )
+        .unwrap();
+    mint(&mut app, &owner, &token_x, 100_000_000000, &pair).unwrap();
+    mint(&mut app, &owner, &token_y, 100_000_000000, &pair).unwrap();
+
+    let router_code = app.store_code(router_contract());
+    let err = app
+        .instantiate_contract(
+            router_code,
+            owner.clone(),
+            &InstantiateMsg {
+                astroport_factory: helper.factory.to_string(),
+                owner: owner.to_string(),
+                native_swap_handler: None,
+                max_referral_commission: Some(Decimal::one()),
+            },
+            &[],
+            "router",
+            None,
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidMaxReferralCommission {},
+        err.downcast().unwrap()
+    );
+
+    let router = app
+        .instantiate_contract(
+            router_code,
+            owner.clone(),
+            &InstantiateMsg {
+                astroport_factory: helper.factory.to_string(),
+                owner: owner.to_string(),
+                native_swap_handler: None,
+                max_referral_commission: Some(Decimal::percent(5)),
+            },
+            &[],
+            "router",
+            None,
+        )
+        .unwrap();
+
+    let operations = vec![SwapOperation::AstroSwap {
+        offer_asset_info: token_asset_info(token_x.clone()),
+        ask_asset_info: token_asset_info(token_y.clone()),
+        max_spread: None,
+        belief_price: None,
+    }];
+    let swap_amount = 100_000000u128;
+    mint(&mut app, &owner, &token_x, swap_amount, &user).unwrap();
+
+    let swap_msg = |commission: Decimal| Cw20ExecuteMsg::Send {
+        contract: router.to_string(),
+        amount: swap_amount.into(),
+        msg: to_binary(&Cw20HookMsg::ExecuteSwapOperations {
+            operations: operations.clone(),
+            minimum_receive: None,
+            to: None,
+            max_spread: None,
+            referral: Some((referrer.to_string(), commission)),
+        })
+        .unwrap(),
+    };
+
+    let err = app
+        .execute_contract(
+            user.clone(),
+            token_x.clone(),
+            &swap_msg(Decimal::percent(6)),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::ReferralCommissionTooHigh(Decimal::percent(5)),
+        err.downcast().unwrap()
+    );
+
+    let sim: SimulateSwapOperationsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &router,
+            &QueryMsg::SimulateSwapOperations {
+                offer_amount: swap_amount.into(),
+                operations: operations.clone(),
+                referral_commission: Some(Decimal::percent(2)),
+            },
+        )
+        .unwrap();
+
+    app.execute_contract(
+        user.clone(),
+        token_x.clone(),
+        &swap_msg(Decimal::percent(2)),
+        &[],
+    )
+    .unwrap();
+
+    let query_balance = |app: &App, token: &Addr, address: &Addr| {
+        app.wrap()
+            .query_wasm_smart::<BalanceResponse>(
+                token,
+                &Cw20QueryMsg::Balance {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap()
+            .balance
+    };
+    assert_eq!(query_balance(&app, &token_x, &referrer).u128(), 2_000000);
+    assert_eq!(query_balance(&app, &token_y, &user), sim.amount);
+    assert_eq!(query_balance(&app, &token_x, &router).u128(), 0);
+}
+
+fn provide_liquidity(app: &mut App, owner: &Addr, pair: &Addr, assets: [(&Addr, u128); 2]) {
+    for (token, amount) in assets {
+        mint(app, owner, token, amount, owner).unwrap();
+        app.execute_contract(
+            owner.clone(),
+            token.clone(),
+            &Cw20ExecuteMsg::IncreaseAllowance {
+                spender: pair.to_string(),
+                amount: amount.into(),
+                expires: None,
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    app.execute_contract(
+        owner.clone(),
+        pair.clone(),
+        &astroport::pair::ExecuteMsg::ProvideLiquidity {
+            assets: assets
+                .iter()
+                .map(|(token, amount)| token_asset((*token).clone(), (*amount).into()))
+                .collect(),
+            slippage_tolerance: None,
+            auto_stake: None,
+            receiver: None,
+        },
+        &[],
+    )
+    .unwrap();
+}
+
+#[test]
+fn zap_in_and_out() {
+    let mut app = App::default();
+
+    let owner = Addr::unchecked("owner");
+    let user = Addr::unchecked("user");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let token_x = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOX", None);
+    let token_y = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOY", None);
+    let token_w = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOW", None);
+
+    let pair_xy = helper
+        .create_pair_with_addr(
+            &mut app,
+            &owner,
+            PairType::Xyk {},
+            [&token_x, &token_y],
+            None,
+        )
+        .unwrap();
+    provide_liquidity(
+        &mut app,
+        &owner,
+        &pair_xy,
+        [(&token_x, 1_000_000_000000), (&token_y, 2_000_000_000000)],
+    );
+    let pair_wx = helper
+        .create_pair_with_addr(
+            &mut app,
+            &owner,
+            PairType::Xyk {},
+            [&token_w, &token_x],
+            None,
+        )
+        .unwrap();
+    provide_liquidity(
+        &mut app,
+        &owner,
+        &pair_wx,
+        [(&token_w, 1_000_000_000000), (&token_x, 1_000_000_000000)],
+    );
+    let lp_token = app
+        .wrap()
+        .query_wasm_smart::<astroport::asset::PairInfo>(
+            &pair_xy,
+            &astroport::pair::QueryMsg::Pair {},
+        )
+        .unwrap()
+        .liquidity_token;
+
+    let router_code = app.store_code(router_contract());
+    let router = app
+        .instantiate_contract(
+            router_code,
+            owner.clone(),
+            &InstantiateMsg {
+                astroport_factory: helper.factory.to_string(),
+                owner: owner.to_string(),
+                native_swap_handler: None,
+                max_referral_commission: None,
+            },
+            &[],
+            "router",
+            None,
+        )
+        .unwrap();
+
+    let query_balance = |app: &App, token: &Addr, address: &Addr| {
+        app.wrap()
+            .query_wasm_smart::<BalanceResponse>(
+                token,
+                &Cw20QueryMsg::Balance {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap()
+            .balance
+            .u128()
+    };
+    let zap_in_msg = |operations: Vec<SwapOperation>| Cw20ExecuteMsg::Send {
+        contract: router.to_string(),
+        amount: 1_000_000000u128.into(),
+        msg: to_binary(&Cw20HookMsg::ZapIn {
+            pair_addr: pair_xy.to_string(),
+            operations,
+            slippage_tolerance: None,
+            auto_stake: None,
+            receiver: None,
+            max_spread: None,
+        })
+        .unwrap(),
+    };
+
+    // W is not in the X<>Y pair thus it must be swapped first
+    mint(&mut app, &owner, &token_w, 1_000_000000, &user).unwrap();
+    let err = app
+        .execute_contract(user.clone(), token_w.clone(), &zap_in_msg(vec![]), &[])
+        .unwrap_err();
+    assert_eq!(
+        ContractError::AssetNotInPair(token_w.to_string()),
+        err.downcast().unwrap()
+    );
+
+    mint(&mut app, &owner, &token_x, 1_000_000000, &user).unwrap();
+    app.execute_contract(user.clone(), token_x.clone(), &zap_in_msg(vec![]), &[])
+        .unwrap();
+
+    // The router keeps nothing and almost nothing is donated to the pool
+    assert_eq!(query_balance(&app, &token_x, &router), 0);
+    assert_eq!(query_balance(&app, &token_y, &router), 0);
+    let lp_amount = query_balance(&app, &lp_token, &user);
+    let share: Vec<astroport::asset::Asset> = app
+        .wrap()
+        .query_wasm_smart(
+            &pair_xy,
+            &astroport::pair::QueryMsg::Share {
+                amount: lp_amount.into(),
+            },
+        )
+        .unwrap();
+    // Pool price is 2 Y per X thus the share value is close to the offer amount minus swap fees
+    let share_value = share[0].amount.u128() + share[1].amount.u128() / 2;
+    assert!(share_value > 997_000000 && share_value < 1_000_000000);
+
+    // Zap in through the W -> X route
+    app.execute_contract(
+        user.clone(),
+        token_w.clone(),
+        &zap_in_msg(vec![SwapOperation::AstroSwap {
+            offer_asset_info: token_asset_info(token_w.clone()),
+            ask_asset_info: token_asset_info(token_x.clone()),
+            max_spread: None,
+            belief_price: None,
+        }]),
+        &[],
+    )
+    .unwrap();
+    assert_eq!(query_balance(&app, &token_x, &router), 0);
+    assert!(query_balance(&app, &lp_token, &user) > lp_amount);
+    let lp_amount = query_balance(&app, &lp_token, &user);
+
+    let zap_out_msg = |minimum_receive: u128| Cw20ExecuteMsg::Send {
+        contract: router.to_string(),
+        amount: lp_amount.into(),
+        msg: to_binary(&Cw20HookMsg::ZapOut {
+            ask_asset_info: token_asset_info(token_x.clone()),
+            minimum_receive: Some(minimum_receive.into()),
+            to: None,
+            max_spread: None,
+        })
+        .unwrap(),
+    };
+
+    let err = app
+        .execute_contract(
+            user.clone(),
+            lp_token.clone(),
+            &zap_out_msg(2_000_000000),
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::AssertionMinimumReceive { .. }
+    ));
+
+    app.execute_contract(
+        user.clone(),
+        lp_token.clone(),
+        &zap_out_msg(1_980_000000),
+        &[],
+    )
+    .unwrap();
+    assert_eq!(query_balance(&app, &lp_token, &user), 0);
+    assert!(query_balance(&app, &token_x, &user) > 1_980_000000);
+    assert_eq!(query_balance(&app, &token_x, &router), 0);
+    assert_eq!(query_balance(&app, &token_y, &router), 0);
+
+    // Withdrawn assets are paid out by the router itself only
+    let err = app
+        .execute_contract(
+            user.clone(),
+            router.clone(),
+            &ExecuteMsg::ZapOutSwap {
+                pair_addr: pair_xy.to_string(),
+                ask_asset_info: token_asset_info(token_x.clone()),
+                to: user.to_string(),
+                max_spread: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+    // Only Astroport LP tokens can be zapped out
+    mint(&mut app, &owner, &token_x, 1_000000, &user).unwrap();
+    let err = app
+        .execute_contract(
+            user.clone(),
+            token_x.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: router.to_string(),
+                amount: 1_000000u128.into(),
+                msg: to_binary(&Cw20HookMsg::ZapOut {
+                    ask_asset_info: token_asset_info(token_x.clone()),
+                    minimum_receive: None,
+                    to: None,
+                    max_spread: None,
+                })
+                .unwrap(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidZapPair(token_x.to_string()),
+        err.downcast().unwrap()
+    );
+}
+
+#[test]
+fn per_hop_spread_limits_and_detailed_simulation() {
+    let mut app = App::default();
+
+    let owner = Addr::unchecked("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let token_x = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOX", None);
+    let token_y = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOY", None);
+    let token_z = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOZ", None);
+
+    // X<>Y pool is shallow thus the first hop has a huge price impact
+    for (a, b, liq) in [
+        (&token_x, &token_y, 100_000_000000),
+        (&token_y, &token_z, 100_000_000_000000),
+    ] {
+        let pair = helper
+            .create_pair_with_addr(&mut app, &owner, PairType::Xyk {}, [a, b], None)
+            .unwrap();
+        mint(&mut app, &owner, a, liq, &pair).unwrap();
+        mint(&mut app, &owner, b, liq, &pair).unwrap();
+    }
+
+    let router_code = app.store_code(router_contract());
+    let router = app
+        .instantiate_contract(
+            router_code,
+            owner.clone(),
+            &InstantiateMsg {
+                astroport_factory: helper.factory.to_string(),
+                owner: owner.to_string(),
+                native_swap_handler: None,
+                max_referral_commission: None,
+            },
+            &[],
+            "router",
+            None,
+        )
+        .unwrap();
+
+    let operations = |first_hop_max_spread: Option<Decimal>| {
+        vec![
+            SwapOperation::AstroSwap {
+                offer_asset_info: token_asset_info(token_x.clone()),
+                ask_asset_info: token_asset_info(token_y.clone()),
+                max_spread: first_hop_max_spread,
+                belief_price: None,
+            },
+            SwapOperation::AstroSwap {
+                offer_asset_info: token_asset_info(token_y.clone()),
+                ask_asset_info: token_asset_info(token_z.clone()),
+                max_spread: None,
+                belief_price: None,
+            },
+        ]
+    };
+
+    let offer_amount = Uint128::new(50_000_000000);
+    let detailed: SimulateSwapOperationsDetailedResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &router,
+            &QueryMsg::SimulateSwapOperationsDetailed {
+                offer_amount,
+                operations: operations(None),
+            },
+        )
+        .unwrap();
+    let simple: SimulateSwapOperationsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &router,
+            &QueryMsg::SimulateSwapOperations {
+                offer_amount,
+                operations: operations(None),
+                referral_commission: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(detailed.amount, simple.amount);
+    assert_eq!(detailed.hops.len(), 2);
+    assert_eq!(detailed.hops[0].offer_asset.amount, offer_amount);
+    assert_eq!(
+        detailed.hops[1].offer_asset.amount,
+        detailed.hops[0].return_asset.amount
+    );
+    assert_eq!(detailed.hops[1].return_asset.amount, detailed.amount);
+    // The first hop is the one which hurts
+    assert!(detailed.hops[0].price_impact > Decimal::percent(30));
+    assert!(detailed.hops[1].price_impact < Decimal::percent(1));
+    assert!(!detailed.hops[0].commission_amount.is_zero());
+    assert!(detailed.price_impact >= detailed.hops[0].price_impact);
+
+    let swap_msg = |first_hop_max_spread: Option<Decimal>| Cw20ExecuteMsg::Send {
+        contract: router.to_string(),
+        amount: offer_amount,
+        msg: to_binary(&Cw20HookMsg::ExecuteSwapOperations {
+            operations: operations(first_hop_max_spread),
+            minimum_receive: None,
+            to: None,
+            max_spread: None,
+            referral: None,
+        })
+        .unwrap(),
+    };
+
+    // Per-hop max spread is asserted even in a multi hop route
+    mint(&mut app, &owner, &token_x, offer_amount.u128(), &owner).unwrap();
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            token_x.clone(),
+            &swap_msg(Some(Decimal::percent(10))),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        astroport_pair::error::ContractError::MaxSpreadAssertion {},
+        err.downcast().unwrap()
+    );
+
+    app.execute_contract(owner.clone(), token_x.clone(), &swap_msg(None), &[])
+        .unwrap();
+}
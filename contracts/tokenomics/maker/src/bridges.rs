@@ -0,0 +1,408 @@
+use std::collections::HashMap;
+
+use astroport::asset::{Asset, AssetInfo};
+use astroport::maker::{Config, SwapSimulation};
+use astroport::pair::{QueryMsg as PairQueryMsg, SimulationResponse};
+use astroport::querier::query_pairs_info;
+use cosmwasm_std::{Addr, Decimal, Deps, DepsMut, Env, Order, StdResult, Storage, Uint128};
+
+use crate::error::ContractError;
+use crate::state::{BRIDGES, BRIDGES_EXPIRY, BRIDGES_NOT_FOUND};
+use crate::utils::{
+    build_protected_swap_msg, get_pool, load_bridge, validate_bridge, BRIDGES_EXECUTION_MAX_DEPTH,
+    BRIDGES_INITIAL_DEPTH, BRIDGES_MAX_DEPTH,
+};
+
+/// The number of pairs read from the factory in one query
+const PAIRS_QUERY_LIMIT: u32 = 30;
+
+/// The maximum number of factory pairs read to discover bridges
+const MAX_DISCOVERY_PAIRS: usize = 300;
+
+/// The maximum number of bridge chains simulated to discover bridges
+const MAX_BRIDGE_CANDIDATES: usize = 20;
+
+/// Returns the assets which a fee token is swapped through to ASTRO.
+/// Picks the same route as a collection: the bridge set for the token, the default bridge or
+/// a direct pool with ASTRO, and then the bridges set for every next asset.
+///
+/// * **from_token** fee token to swap to ASTRO.
+///
+/// * **now** current block timestamp in seconds. Expired bridges are ignored.
+pub fn swap_route(
+    deps: Deps,
+    cfg: &Config,
+    from_token: &AssetInfo,
+    now: u64,
+) -> Result<Vec<AssetInfo>, ContractError> {
+    let first_hop = if let Some(bridge_token) = load_bridge(deps.storage, from_token, now)? {
+        validate_bridge(
+            deps,
+            &cfg.factory_contract,
+            from_token,
+            &bridge_token,
+            &cfg.astro_token,
+            BRIDGES_INITIAL_DEPTH,
+            now,
+        )?;
+        bridge_token
+    } else {
+        let default_bridge = cfg.default_bridge.as_ref().filter(|default_bridge| {
+            from_token.ne(default_bridge)
+                && get_pool(
+                    &deps.querier,
+                    &cfg.factory_contract,
+                    from_token,
+                    default_bridge,
+                )
+                .is_ok()
+        });
+
+        match default_bridge {
+            Some(default_bridge) => default_bridge.clone(),
+            None => {
+                get_pool(
+                    &deps.querier,
+                    &cfg.factory_contract,
+                    from_token,
+                    &cfg.astro_token,
+                )
+                .map_err(|_| ContractError::CannotSwap(from_token.clone()))?;
+                cfg.astro_token.clone()
+            }
+        }
+    };
+
+    complete_route(deps, cfg, vec![from_token.clone(), first_hop], now)
+}
+
+/// Extends a route with the bridges set for every next asset until it reaches ASTRO.
+///
+/// * **route** the beginning of the route which starts with the fee token.
+///
+/// * **now** current block timestamp in seconds. Expired bridges are ignored.
+fn complete_route(
+    deps: Deps,
+    cfg: &Config,
+    mut route: Vec<AssetInfo>,
+    now: u64,
+) -> Result<Vec<AssetInfo>, ContractError> {
+    let mut depth = BRIDGES_INITIAL_DEPTH + route.len().saturating_sub(2) as u64;
+    while let Some(current) = route.last().filter(|asset| **asset != cfg.astro_token) {
+        if depth >= BRIDGES_EXECUTION_MAX_DEPTH {
+            return Err(ContractError::MaxBridgeDepth(depth));
+        }
+
+        let next =
+            load_bridge(deps.storage, current, now)?.unwrap_or_else(|| cfg.astro_token.clone());
+        route.push(next);
+        depth += 1;
+    }
+
+    Ok(route)
+}
+
+/// Simulates the swap of a fee token to ASTRO along its route.
+///
+/// * **offer_asset** fee token and the amount to swap.
+///
+/// * **now** current block timestamp in seconds.
+pub fn simulate_swap(
+    deps: Deps,
+    cfg: &Config,
+    offer_asset: Asset,
+    now: u64,
+) -> Result<SwapSimulation, ContractError> {
+    let route = swap_route(deps, cfg, &offer_asset.info, now)?;
+    simulate_route(deps, cfg, offer_asset, route)
+}
+
+/// Simulates the swap of a fee token to ASTRO along the given route.
+///
+/// * **offer_asset** fee token and the amount to swap.
+///
+/// * **route** the assets which the fee token is swapped through.
+fn simulate_route(
+    deps: Deps,
+    cfg: &Config,
+    offer_asset: Asset,
+    route: Vec<AssetInfo>,
+) -> Result<SwapSimulation, ContractError> {
+    let mut amount = offer_asset.amount;
+    let mut price_ratio = Decimal::one();
+    for hop in route.windows(2) {
+        let pool = get_pool(&deps.querier, &cfg.factory_contract, &hop[0], &hop[1])?;
+
+        if cfg.swap_protection.is_some()
+            && build_protected_swap_msg(&deps.querier, cfg, &pool, &hop[0], &hop[1], amount, None)?
+                .is_none()
+        {
+            return Ok(SwapSimulation {
+                offer_asset,
+                route,
+                astro_amount: Uint128::zero(),
+                price_impact: Decimal::zero(),
+                skipped: true,
+            });
+        }
+
+        let sim: SimulationResponse = deps.querier.query_wasm_smart(
+            &pool.contract_addr,
+            &PairQueryMsg::Simulation {
+                offer_asset: Asset {
+                    info: hop[0].clone(),
+                    amount,
+                },
+                ask_asset_info: Some(hop[1].clone()),
+            },
+        )?;
+
+        // The spread is the part of the return which is lost due to the price impact of the swap
+        let total = sim.return_amount + sim.spread_amount + sim.commission_amount;
+        if !total.is_zero() {
+            price_ratio =
+                price_ratio * (Decimal::one() - Decimal::from_ratio(sim.spread_amount, total));
+        }
+        amount = sim.return_amount;
+    }
+
+    Ok(SwapSimulation {
+        offer_asset,
+        route,
+        astro_amount: amount,
+        price_impact: Decimal::one() - price_ratio,
+        skipped: false,
+    })
+}
+
+/// Returns pairs registered in the factory as an adjacency list
+/// which maps each asset to the assets it can be directly swapped to.
+/// Reads at most [`MAX_DISCOVERY_PAIRS`] pairs.
+fn query_swap_graph(deps: Deps, factory: &Addr) -> StdResult<HashMap<AssetInfo, Vec<AssetInfo>>> {
+    let mut graph: HashMap<AssetInfo, Vec<AssetInfo>> = HashMap::new();
+    let mut start_after = None;
+    let mut pairs_read = 0;
+
+    loop {
+        let pairs = query_pairs_info(
+            &deps.querier,
+            factory,
+            start_after.take(),
+            Some(PAIRS_QUERY_LIMIT),
+        )?
+        .pairs;
+        pairs_read += pairs.len();
+
+        for pair in &pairs {
+            for from in &pair.asset_infos {
+                for to in pair.asset_infos.iter().filter(|to| *to != from) {
+                    graph.entry(from.clone()).or_default().push(to.clone());
+                }
+            }
+        }
+
+        match pairs.last() {
+            Some(last)
+                if pairs.len() == PAIRS_QUERY_LIMIT as usize
+                    && pairs_read < MAX_DISCOVERY_PAIRS =>
+            {
+                start_after = Some(last.asset_infos.clone())
+            }
+            _ => break,
+        }
+    }
+
+    Ok(graph)
+}
+
+/// Saves bridges which lead from `from_token` through every asset of `chain` until they expire.
+/// Returns the saved bridges.
+fn save_chain(
+    storage: &mut dyn Storage,
+    from_token: &AssetInfo,
+    chain: &[AssetInfo],
+    expires_at: u64,
+) -> StdResult<Vec<(AssetInfo, AssetInfo)>> {
+    let mut bridges = vec![];
+    let mut asset = from_token;
+    for bridge in chain {
+        BRIDGES.save(storage, asset.to_string(), bridge)?;
+        BRIDGES_EXPIRY.save(storage, asset.to_string(), &expires_at)?;
+        bridges.push((asset.clone(), bridge.clone()));
+        asset = bridge;
+    }
+
+    Ok(bridges)
+}
+
+/// Removes the expired bridges which lead from `from_token` along with all expired bridges of the chain.
+/// Stops at the first bridge which is still valid or was added by the owner.
+fn remove_expired_chain(
+    storage: &mut dyn Storage,
+    from_token: &AssetInfo,
+    now: u64,
+) -> StdResult<()> {
+    let mut asset = from_token.clone();
+    for _ in 0..=BRIDGES_MAX_DEPTH {
+        match BRIDGES_EXPIRY.may_load(storage, asset.to_string())? {
+            Some(expires_at) if expires_at <= now => {}
+            _ => break,
+        }
+
+        let next = BRIDGES.may_load(storage, asset.to_string())?;
+        BRIDGES.remove(storage, asset.to_string());
+        BRIDGES_EXPIRY.remove(storage, asset.to_string());
+        match next {
+            Some(next) => asset = next,
+            None => break,
+        }
+    }
+
+    Ok(())
+}
+
+/// Removes all bridges which were discovered automatically along with the cached failed discoveries.
+pub fn clear_discovered_bridges(storage: &mut dyn Storage) -> StdResult<()> {
+    let discovered = BRIDGES_EXPIRY
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for asset in discovered {
+        BRIDGES.remove(storage, asset.clone());
+        BRIDGES_EXPIRY.remove(storage, asset);
+    }
+
+    let not_found = BRIDGES_NOT_FOUND
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for asset in not_found {
+        BRIDGES_NOT_FOUND.remove(storage, asset);
+    }
+
+    Ok(())
+}
+
+/// Returns whether bridges for a fee token were not found by a recent discovery
+/// which is not repeated until its cache expires.
+pub fn is_discovery_cached(
+    storage: &dyn Storage,
+    from_token: &AssetInfo,
+    now: u64,
+) -> StdResult<bool> {
+    Ok(BRIDGES_NOT_FOUND
+        .may_load(storage, from_token.to_string())?
+        .map_or(false, |expires_at| expires_at > now))
+}
+
+/// Finds bridges for a fee token which cannot be swapped to ASTRO without saving them.
+/// Candidate routes go through factory pairs and end at an asset which has a direct pool with ASTRO
+/// or its own bridge. Shorter chains are collected first and at most [`MAX_BRIDGE_CANDIDATES`] chains
+/// are simulated. Returns the bridge chain with the highest simulated ASTRO output along with its simulation.
+/// Chains which are rejected by the swap protection are never picked.
+///
+/// * **offer_asset** fee token and the amount which is going to be swapped.
+///
+/// * **now** current block timestamp in seconds. Expired bridges are ignored.
+pub fn find_bridges(
+    deps: Deps,
+    cfg: &Config,
+    offer_asset: &Asset,
+    now: u64,
+) -> Result<Option<(Vec<AssetInfo>, SwapSimulation)>, ContractError> {
+    let from_token = &offer_asset.info;
+    let graph = query_swap_graph(deps, &cfg.factory_contract)?;
+    let is_terminal = |asset: &AssetInfo| -> StdResult<bool> {
+        Ok(load_bridge(deps.storage, asset, now)?.is_some()
+            || matches!(graph.get(asset), Some(assets) if assets.contains(&cfg.astro_token)))
+    };
+
+    // Collect bridge chains which are not longer than the maximum bridge depth allows
+    let mut chains = vec![];
+    let mut paths: Vec<Vec<AssetInfo>> = vec![vec![]];
+    'levels: for _ in 0..=BRIDGES_MAX_DEPTH {
+        let mut next_paths = vec![];
+
+        for path in paths {
+            let last = path.last().unwrap_or(from_token);
+            for next in graph.get(last).into_iter().flatten() {
+                if next == from_token || *next == cfg.astro_token || path.contains(next) {
+                    continue;
+                }
+
+                let mut path = path.clone();
+                path.push(next.clone());
+                if is_terminal(next)? {
+                    chains.push(path);
+                    if chains.len() >= MAX_BRIDGE_CANDIDATES {
+                        break 'levels;
+                    }
+                } else {
+                    next_paths.push(path);
+                }
+            }
+        }
+
+        paths = next_paths;
+    }
+
+    let mut best: Option<(Vec<AssetInfo>, SwapSimulation)> = None;
+    for chain in chains {
+        let route = [vec![from_token.clone()], chain.clone()].concat();
+        let simulation = complete_route(deps, cfg, route, now)
+            .and_then(|route| simulate_route(deps, cfg, offer_asset.clone(), route));
+
+        if let Ok(simulation) = simulation {
+            let is_better = !simulation.skipped
+                && !simulation.astro_amount.is_zero()
+                && !matches!(&best, Some((_, best)) if best.astro_amount >= simulation.astro_amount);
+            if is_better {
+                best = Some((chain, simulation));
+            }
+        }
+    }
+
+    Ok(best)
+}
+
+/// Discovers bridges for a fee token which cannot be swapped to ASTRO and caches them in [`BRIDGES`]
+/// until they expire. Bridges which were added by the owner are never replaced.
+/// A discovery which finds nothing is not repeated for the fee token until the cache expires.
+/// Returns the discovered bridges or None if the fee token cannot be swapped to ASTRO.
+///
+/// * **offer_asset** fee token and the amount which is going to be swapped.
+///
+/// * **cache_ttl** for how long discovered bridges are cached in seconds.
+pub fn discover_bridges(
+    deps: DepsMut,
+    env: &Env,
+    cfg: &Config,
+    offer_asset: &Asset,
+    cache_ttl: u64,
+) -> Result<Option<Vec<(AssetInfo, AssetInfo)>>, ContractError> {
+    let from_token = &offer_asset.info;
+    let now = env.block.time.seconds();
+
+    // Expired bridges are discovered again
+    remove_expired_chain(deps.storage, from_token, now)?;
+
+    if BRIDGES.has(deps.storage, from_token.to_string())
+        || swap_route(deps.as_ref(), cfg, from_token, now).is_ok()
+    {
+        return Ok(Some(vec![]));
+    }
+
+    if is_discovery_cached(deps.storage, from_token, now)? {
+        return Ok(None);
+    }
+
+    match find_bridges(deps.as_ref(), cfg, offer_asset, now)? {
+        Some((chain, _)) => {
+            BRIDGES_NOT_FOUND.remove(deps.storage, from_token.to_string());
+            let bridges = save_chain(deps.storage, from_token, &chain, now + cache_ttl)?;
+            Ok(Some(bridges))
+        }
+        None => {
+            BRIDGES_NOT_FOUND.save(deps.storage, from_token.to_string(), &(now + cache_ttl))?;
+            Ok(None)
+        }
+    }
+}
@@ -1,27 +1,40 @@
+use crate::bridges::{
+    clear_discovered_bridges, discover_bridges, find_bridges, is_discovery_cached, simulate_swap,
+    swap_route,
+};
 use crate::error::ContractError;
-use crate::state::{BRIDGES, CONFIG, OWNERSHIP_PROPOSAL};
+use crate::state::{
+    ASTRO_STREAM, BRIDGES, BRIDGES_EXPIRY, BURNED, COLLECTED, COLLECT_LIMITS, CONFIG,
+    DEFAULT_LIMIT, DISTRIBUTIONS, KEEPER, LAST_COLLECTS, MAX_LIMIT, OWNERSHIP_PROPOSAL,
+};
 use std::cmp::min;
 
-use crate::migration::{migrate_from_v1, migrate_from_v120};
+use crate::migration::{migrate_from_v1, migrate_from_v120, migrate_from_v130};
 
 use crate::utils::{
-    build_distribute_msg, build_send_msg, build_swap_msg, try_build_swap_msg,
-    update_second_receiver_cfg, validate_bridge, BRIDGES_EXECUTION_MAX_DEPTH,
-    BRIDGES_INITIAL_DEPTH,
+    build_burn_msg, build_distribute_msg, build_fee_receiver_msg, build_protected_swap_msg,
+    build_send_msg, build_withdraw_liquidity_msg, calc_keeper_bounty, get_pool, load_bridge,
+    query_lp_pair, try_build_swap_msg, validate_astro_burn, validate_astro_streaming,
+    validate_auto_bridge, validate_bridge, validate_collect_limit, validate_fee_receivers,
+    validate_keeper_bounty, validate_swap_protection, BRIDGES_EXECUTION_MAX_DEPTH,
+    BRIDGES_INITIAL_DEPTH, MAX_STREAM_TRANCHES,
 };
 use astroport::asset::{addr_opt_validate, Asset, AssetInfo};
 use astroport::common::{claim_ownership, drop_ownership_proposal, propose_new_owner};
 use astroport::factory::UpdateAddr;
 use astroport::maker::{
-    AssetWithLimit, BalancesResponse, Config, ConfigResponse, ExecuteMsg, InstantiateMsg,
-    MigrateMsg, QueryMsg, SecondReceiverConfig, SecondReceiverParams,
+    AssetWithLimit, AstroStream, BalancesResponse, CollectLimit, Config, ConfigResponse,
+    DistributionRecord, ExecuteMsg, FeeReceiver, FeeReceiverParams, InstantiateMsg, MigrateMsg,
+    QueryMsg, SimulateCollectResponse, SkipReason, SwapSimulation, UpdateAstroBurn,
+    UpdateAstroStreaming, UpdateAutoBridge, UpdateKeeperBounty, UpdateSwapProtection,
 };
-use astroport::pair::MAX_ALLOWED_SLIPPAGE;
+use astroport::pair::{PoolResponse, QueryMsg as PairQueryMsg, MAX_ALLOWED_SLIPPAGE};
 use cosmwasm_std::{
-    attr, entry_point, to_binary, Addr, Attribute, Binary, Decimal, Deps, DepsMut, Env,
-    MessageInfo, Order, Response, StdError, StdResult, SubMsg, Uint128, Uint64,
+    attr, entry_point, to_binary, wasm_execute, Addr, Attribute, Binary, Decimal, Deps, DepsMut,
+    Env, MessageInfo, Order, Response, StdError, StdResult, Storage, SubMsg, Uint128, Uint64,
 };
 use cw2::{get_contract_version, set_contract_version};
+use cw_storage_plus::Bound;
 use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 
@@ -72,6 +85,23 @@ pub fn instantiate(
         default_bridge.check(deps.api)?
     }
 
+    if let Some(keeper_bounty) = &msg.keeper_bounty {
+        validate_keeper_bounty(keeper_bounty)?;
+    }
+
+    if let Some(auto_bridge) = &msg.auto_bridge {
+        validate_auto_bridge(auto_bridge)?;
+    }
+
+    if let Some(astro_streaming) = &msg.astro_streaming {
+        validate_astro_streaming(astro_streaming)?;
+    }
+
+    let astro_burn = msg
+        .astro_burn
+        .map(|params| validate_astro_burn(deps.api, &msg.astro_token, params))
+        .transpose()?;
+
     let mut cfg = Config {
         owner: deps.api.addr_validate(&msg.owner)?,
         default_bridge: msg.default_bridge,
@@ -86,10 +116,25 @@ pub fn instantiate(
         governance_contract,
         governance_percent,
         max_spread,
-        second_receiver_cfg: None,
+        fee_receivers: vec![],
+        swap_protection: msg.swap_protection,
+        keeper_bounty: msg.keeper_bounty,
+        auto_bridge: msg.auto_bridge,
+        astro_streaming: msg.astro_streaming,
+        astro_burn,
     };
 
-    update_second_receiver_cfg(deps.as_ref(), &mut cfg, &msg.second_receiver_params)?;
+    if let Some(fee_receivers) = msg.fee_receivers {
+        cfg.fee_receivers = validate_fee_receivers(deps.as_ref(), &cfg, fee_receivers)?;
+    }
+
+    if let Some(swap_protection) = &cfg.swap_protection {
+        validate_swap_protection(deps.as_ref(), &cfg, swap_protection)?;
+    }
+
+    if cfg.auto_bridge.is_some() && cfg.swap_protection.is_none() {
+        return Err(ContractError::AutoBridgeRequiresSwapProtection {});
+    }
 
     if cfg.staking_contract.is_none() && cfg.governance_contract.is_none() {
         return Err(
@@ -99,19 +144,6 @@ pub fn instantiate(
 
     CONFIG.save(deps.storage, &cfg)?;
 
-    let (second_fee_receiver, second_receiver_cut) = if let Some(SecondReceiverConfig {
-        second_fee_receiver,
-        second_receiver_cut,
-    }) = cfg.second_receiver_cfg
-    {
-        (
-            second_fee_receiver.to_string(),
-            second_receiver_cut.to_string(),
-        )
-    } else {
-        (String::from("none"), String::from("0"))
-    };
-
     Ok(Response::default().add_attributes([
         attr("owner", msg.owner),
         attr(
@@ -133,8 +165,7 @@ pub fn instantiate(
         ),
         attr("governance_percent", governance_percent),
         attr("max_spread", max_spread.to_string()),
-        attr("second_fee_receiver", second_fee_receiver),
-        attr("second_receiver_cut", second_receiver_cut),
+        attr("fee_receivers", fee_receivers_attr(&cfg.fee_receivers)),
     ]))
 }
 
@@ -150,15 +181,24 @@ pub fn instantiate(
 ///             governance_contract,
 ///             governance_percent,
 ///             max_spread,
-///             second_receiver_params,
+///             fee_receivers,
+///             swap_protection,
+///             keeper_bounty,
+///             auto_bridge,
+///             astro_streaming,
+///             astro_burn,
 ///         }** Updates general contract settings stores in the [`Config`].
 ///
 /// * **ExecuteMsg::UpdateBridges { add, remove }** Adds or removes bridge assets used to swap fee tokens to ASTRO.
 ///
+/// * **ExecuteMsg::UpdateCollectLimits { add, remove }** Adds or removes limits applied to collections of fee tokens.
+///
 /// * **ExecuteMsg::SwapBridgeAssets { assets }** Swap fee tokens (through bridges) to ASTRO.
 ///
 /// * **ExecuteMsg::DistributeAstro {}** Private method used by the contract to distribute ASTRO rewards.
 ///
+/// * **ExecuteMsg::ReleaseAstro {}** Releases the streamed ASTRO which is due to the xASTRO staking contract.
+///
 /// * **ExecuteMsg::ProposeNewOwner { owner, expires_in }** Creates a new request to change contract ownership.
 ///
 /// * **ExecuteMsg::DropOwnershipProposal {}** Removes a request to change contract ownership.
@@ -174,7 +214,7 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::Collect { assets } => collect(deps, env, assets),
+        ExecuteMsg::Collect { assets } => collect(deps, env, info, assets),
         ExecuteMsg::UpdateConfig {
             factory_contract,
             staking_contract,
@@ -182,7 +222,12 @@ pub fn execute(
             governance_percent,
             basic_asset,
             max_spread,
-            second_receiver_params,
+            fee_receivers,
+            swap_protection,
+            keeper_bounty,
+            auto_bridge,
+            astro_streaming,
+            astro_burn,
         } => update_config(
             deps,
             info,
@@ -192,13 +237,22 @@ pub fn execute(
             governance_percent,
             basic_asset,
             max_spread,
-            second_receiver_params,
+            fee_receivers,
+            swap_protection,
+            keeper_bounty,
+            auto_bridge,
+            astro_streaming,
+            astro_burn,
         ),
-        ExecuteMsg::UpdateBridges { add, remove } => update_bridges(deps, info, add, remove),
+        ExecuteMsg::UpdateBridges { add, remove } => update_bridges(deps, env, info, add, remove),
+        ExecuteMsg::UpdateCollectLimits { add, remove } => {
+            update_collect_limits(deps, info, add, remove)
+        }
         ExecuteMsg::SwapBridgeAssets { assets, depth } => {
             swap_bridge_assets(deps, env, info, assets, depth)
         }
         ExecuteMsg::DistributeAstro {} => distribute_astro(deps, env, info),
+        ExecuteMsg::ReleaseAstro {} => release_astro(deps, env),
         ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
             let config: Config = CONFIG.load(deps.storage)?;
 
@@ -260,44 +314,154 @@ pub fn execute(
 }
 
 /// Swaps fee tokens to ASTRO and distribute the resulting ASTRO to xASTRO and vxASTRO stakers.
+/// The sender is paid the keeper bounty if it is set.
+/// Bridges are discovered for fee tokens which cannot be swapped if the automatic bridge discovery is enabled.
 ///
 /// * **assets** array with fee tokens being swapped to ASTRO.
 fn collect(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
+    info: MessageInfo,
     assets: Vec<AssetWithLimit>,
 ) -> Result<Response, ContractError> {
     let mut cfg = CONFIG.load(deps.storage)?;
 
     let astro = cfg.astro_token.clone();
 
-    // Check for duplicate assets
-    let mut uniq = HashSet::new();
-    if !assets
-        .clone()
-        .into_iter()
-        .all(|a| uniq.insert(a.info.to_string()))
-    {
-        return Err(ContractError::DuplicatedAsset {});
+    check_duplicated_assets(&assets)?;
+
+    // A follow-up collection of unwrapped LP tokens keeps the keeper of the initial call
+    let keeper = if info.sender == env.contract.address {
+        KEEPER
+            .may_load(deps.storage)?
+            .unwrap_or_else(|| info.sender.clone())
+    } else {
+        info.sender
+    };
+
+    // Withdraw liquidity for collected LP tokens. Underlying assets are collected
+    // together with the rest of the fee tokens in a follow-up call
+    let mut withdraw_msgs = vec![];
+    let mut lp_attrs = vec![];
+    let mut fee_assets = vec![];
+    let mut unwrapped = vec![];
+    for a in assets.into_iter().filter(|a| a.info.ne(&astro)) {
+        if let Some(pair) = query_lp_pair(&deps.querier, &cfg.factory_contract, &a.info) {
+            let amount = collect_amount(deps.as_ref(), &env.contract.address, &a)?;
+            if !amount.is_zero() {
+                withdraw_msgs.push(build_withdraw_liquidity_msg(&pair, amount)?);
+                lp_attrs.push(attr("lp_unwrapped", format!("{}{}", amount, a.info)));
+                unwrapped.extend(pair.asset_infos);
+            }
+        } else {
+            fee_assets.push(a);
+        }
+    }
+
+    if !withdraw_msgs.is_empty() {
+        for info in unwrapped {
+            if info != astro && !fee_assets.iter().any(|a| a.info == info) {
+                fee_assets.push(AssetWithLimit { info, limit: None });
+            }
+        }
+
+        KEEPER.save(deps.storage, &keeper)?;
+
+        return Ok(Response::new()
+            .add_submessages(withdraw_msgs)
+            .add_message(wasm_execute(
+                &env.contract.address,
+                &ExecuteMsg::Collect { assets: fee_assets },
+                vec![],
+            )?)
+            .add_attributes(lp_attrs)
+            .add_attribute("action", "collect"));
+    }
+
+    let mut discovered_bridges = vec![];
+    let mut skipped_attrs = vec![];
+    if let Some(auto_bridge) = cfg.auto_bridge.clone() {
+        let mut no_route = vec![];
+        for a in &fee_assets {
+            let amount = collect_amount(deps.as_ref(), &env.contract.address, a)?;
+            if !amount.is_zero() {
+                let bridges = discover_bridges(
+                    deps.branch(),
+                    &env,
+                    &cfg,
+                    &Asset {
+                        info: a.info.clone(),
+                        amount,
+                    },
+                    auto_bridge.cache_ttl,
+                )?;
+                match bridges {
+                    Some(bridges) => discovered_bridges.extend(bridges),
+                    // Fee tokens without any route are skipped so the failed discovery stays cached
+                    None => no_route.push(a.info.clone()),
+                }
+            }
+        }
+
+        fee_assets.retain(|a| !no_route.contains(&a.info));
+        for info in no_route {
+            skipped_attrs.push(attr("skipped_asset", info.to_string()));
+        }
+    }
+
+    // Fee tokens which violate their collect limits are skipped
+    let mut limited_assets = vec![];
+    for a in fee_assets {
+        let info = a.info.clone();
+        match apply_collect_limit(deps.as_ref(), &env, &cfg, a)? {
+            Some(a) => limited_assets.push(a),
+            None => skipped_attrs.push(attr("skipped_asset", info.to_string())),
+        }
+    }
+    let fee_assets = limited_assets;
+
+    let mut collected = vec![];
+    for a in &fee_assets {
+        let amount = collect_amount(deps.as_ref(), &env.contract.address, a)?;
+        if !amount.is_zero() {
+            collected.push(Asset {
+                info: a.info.clone(),
+                amount,
+            });
+        }
     }
 
     // Swap all non ASTRO tokens
-    let (mut response, bridge_assets) = swap_assets(
-        deps.as_ref(),
-        &env.contract.address,
-        &cfg,
-        assets.into_iter().filter(|a| a.info.ne(&astro)).collect(),
-        true,
-    )?;
+    let (mut response, bridge_assets, skipped) =
+        swap_assets(deps.as_ref(), &env, &cfg, fee_assets, true)?;
+
+    collected.retain(|asset| !skipped.contains(&asset.info));
+    for asset in &collected {
+        // Collection timestamps are only needed to enforce cooldowns
+        let has_cooldown = COLLECT_LIMITS
+            .may_load(deps.storage, asset.info.to_string())?
+            .map_or(false, |collect_limit| collect_limit.cooldown.is_some());
+        if has_cooldown {
+            LAST_COLLECTS.save(
+                deps.storage,
+                asset.info.to_string(),
+                &env.block.time.seconds(),
+            )?;
+        }
+    }
+    save_collected(deps.storage, collected)?;
 
     // If no swap messages - send ASTRO directly to x/vxASTRO stakers
     if response.messages.is_empty() {
-        let (mut distribute_msg, attributes) = distribute(deps, env, &mut cfg)?;
+        KEEPER.remove(deps.storage);
+        let (mut distribute_msg, attributes) = distribute(deps, env, &mut cfg, Some(keeper))?;
         if !distribute_msg.is_empty() {
             response.messages.append(&mut distribute_msg);
             response = response.add_attributes(attributes);
         }
     } else {
+        // The keeper is paid once ASTRO is distributed after all swaps
+        KEEPER.save(deps.storage, &keeper)?;
         response.messages.push(build_distribute_msg(
             env,
             bridge_assets,
@@ -305,46 +469,156 @@ fn collect(
         )?);
     }
 
+    response = response.add_attributes(skipped_attrs);
+
+    for (asset, bridge) in discovered_bridges {
+        response = response.add_attribute("discovered_bridge", format!("{asset}:{bridge}"));
+    }
+
     Ok(response.add_attribute("action", "collect"))
 }
 
+/// Applies the collect limit configured for a fee token. Returns None if the token is on cooldown
+/// or its pool is empty, otherwise caps the collected amount by the configured share of the pool reserve.
+fn apply_collect_limit(
+    deps: Deps,
+    env: &Env,
+    cfg: &Config,
+    mut a: AssetWithLimit,
+) -> Result<Option<AssetWithLimit>, ContractError> {
+    let collect_limit = match COLLECT_LIMITS.may_load(deps.storage, a.info.to_string())? {
+        Some(collect_limit) => collect_limit,
+        None => return Ok(Some(a)),
+    };
+
+    if let Some(cooldown) = collect_limit.cooldown {
+        if let Some(last_collect) = LAST_COLLECTS.may_load(deps.storage, a.info.to_string())? {
+            if env.block.time.seconds() < last_collect + cooldown {
+                return Ok(None);
+            }
+        }
+    }
+
+    if let Some(max_pool_share) = collect_limit.max_pool_share {
+        // Tokens without a readable pool are skipped instead of failing the whole collection
+        let reserve = match query_route_reserve(deps, env, cfg, &a.info) {
+            Ok(reserve) => reserve,
+            Err(_) => return Ok(None),
+        };
+
+        let max_amount = reserve * max_pool_share;
+        if max_amount.is_zero() {
+            return Ok(None);
+        }
+
+        a.limit = Some(match a.limit {
+            Some(limit) if !limit.is_zero() => min(limit, max_amount),
+            _ => max_amount,
+        });
+    }
+
+    Ok(Some(a))
+}
+
+/// Returns the reserve of a fee token in the first pool of its swap route.
+fn query_route_reserve(
+    deps: Deps,
+    env: &Env,
+    cfg: &Config,
+    info: &AssetInfo,
+) -> Result<Uint128, ContractError> {
+    let route = swap_route(deps, cfg, info, env.block.time.seconds())?;
+    let pool = get_pool(&deps.querier, &cfg.factory_contract, &route[0], &route[1])?;
+    let pool: PoolResponse = deps
+        .querier
+        .query_wasm_smart(&pool.contract_addr, &PairQueryMsg::Pool {})?;
+
+    Ok(pool
+        .assets
+        .iter()
+        .find(|asset| asset.info == *info)
+        .map(|asset| asset.amount)
+        .unwrap_or_default())
+}
+
+/// Adds collected fee tokens to the ones which are recorded with the next distribution.
+fn save_collected(storage: &mut dyn Storage, collected: Vec<Asset>) -> StdResult<()> {
+    if collected.is_empty() {
+        return Ok(());
+    }
+
+    let mut pending = COLLECTED.may_load(storage)?.unwrap_or_default();
+    for asset in collected {
+        if let Some(pending_asset) = pending.iter_mut().find(|a| a.info == asset.info) {
+            pending_asset.amount = pending_asset.amount.checked_add(asset.amount)?;
+        } else {
+            pending.push(asset);
+        }
+    }
+
+    COLLECTED.save(storage, &pending)
+}
+
+/// Returns the amount of a fee token which is collected: the contract balance capped by the asset limit.
+fn collect_amount(deps: Deps, contract_addr: &Addr, a: &AssetWithLimit) -> StdResult<Uint128> {
+    let mut balance = a.info.query_pool(&deps.querier, contract_addr)?;
+    if let Some(limit) = a.limit {
+        if limit < balance && limit > Uint128::zero() {
+            balance = limit;
+        }
+    }
+
+    Ok(balance)
+}
+
+/// Checks that the assets to collect are not duplicated.
+fn check_duplicated_assets(assets: &[AssetWithLimit]) -> Result<(), ContractError> {
+    let mut uniq = HashSet::new();
+    if !assets.iter().all(|a| uniq.insert(a.info.to_string())) {
+        return Err(ContractError::DuplicatedAsset {});
+    }
+
+    Ok(())
+}
+
 /// This enum describes available token types that can be used as a SwapTarget.
 enum SwapTarget {
     Astro(SubMsg),
-    Bridge { asset: AssetInfo, msg: SubMsg },
+    Bridge {
+        asset: AssetInfo,
+        msg: SubMsg,
+    },
+    /// The swap is rejected by the TWAP protection
+    Skipped,
 }
 
 /// Swap all non ASTRO tokens to ASTRO.
-///
-/// * **contract_addr** maker contract address.
+/// Returns the response with swap messages, the bridge assets to swap next and the skipped assets.
 ///
 /// * **assets** array with assets to swap to ASTRO.
 ///
 /// * **with_validation** whether the swap operation should be validated or not.
 fn swap_assets(
     deps: Deps,
-    contract_addr: &Addr,
+    env: &Env,
     cfg: &Config,
     assets: Vec<AssetWithLimit>,
     with_validation: bool,
-) -> Result<(Response, Vec<AssetInfo>), ContractError> {
+) -> Result<(Response, Vec<AssetInfo>, Vec<AssetInfo>), ContractError> {
     let mut response = Response::default();
     let mut bridge_assets = HashMap::new();
+    let mut skipped = vec![];
 
     for a in assets {
         // Get balance
-        let mut balance = a.info.query_pool(&deps.querier, contract_addr)?;
-        if let Some(limit) = a.limit {
-            if limit < balance && limit > Uint128::zero() {
-                balance = limit;
-            }
-        }
+        let balance = collect_amount(deps, &env.contract.address, &a)?;
 
         if !balance.is_zero() {
+            let now = env.block.time.seconds();
             let swap_msg = if with_validation {
-                swap(deps, cfg, a.info, balance)?
+                swap(deps, cfg, a.info, balance, now)?
             } else {
-                swap_no_validate(deps, cfg, a.info, balance)?
+                swap_no_validate(deps, cfg, a.info, balance, now)?
             };
 
             match swap_msg {
@@ -355,11 +629,15 @@ fn swap_assets(
                     response.messages.push(msg);
                     bridge_assets.insert(asset.to_string(), asset);
                 }
+                SwapTarget::Skipped => {
+                    response = response.add_attribute("skipped_asset", a.info.to_string());
+                    skipped.push(a.info);
+                }
             }
         }
     }
 
-    Ok((response, bridge_assets.into_values().collect()))
+    Ok((response, bridge_assets.into_values().collect(), skipped))
 }
 
 /// Checks if all required pools and bridges exists and performs a swap operation to ASTRO.
@@ -367,15 +645,18 @@ fn swap_assets(
 /// * **from_token** token to swap to ASTRO.
 ///
 /// * **amount_in** amount of tokens to swap.
+///
+/// * **now** current block timestamp in seconds. Expired bridges are ignored.
 fn swap(
     deps: Deps,
     cfg: &Config,
     from_token: AssetInfo,
     amount_in: Uint128,
+    now: u64,
 ) -> Result<SwapTarget, ContractError> {
     // 1. Check if bridge tokens exist
-    let bridge_token = BRIDGES.load(deps.storage, from_token.to_string());
-    if let Ok(bridge_token) = bridge_token {
+    let bridge_token = load_bridge(deps.storage, &from_token, now)?;
+    if let Some(bridge_token) = bridge_token {
         let bridge_pool = validate_bridge(
             deps,
             &cfg.factory_contract,
@@ -383,18 +664,24 @@ fn swap(
             &bridge_token,
             &cfg.astro_token,
             BRIDGES_INITIAL_DEPTH,
+            now,
         )?;
 
-        let msg = build_swap_msg(
-            cfg.max_spread,
+        let msg = build_protected_swap_msg(
+            &deps.querier,
+            cfg,
             &bridge_pool,
             &from_token,
-            Some(&bridge_token),
+            &bridge_token,
             amount_in,
+            None,
         )?;
-        return Ok(SwapTarget::Bridge {
-            asset: bridge_token,
-            msg,
+        return Ok(match msg {
+            Some(msg) => SwapTarget::Bridge {
+                asset: bridge_token,
+                msg,
+            },
+            None => SwapTarget::Skipped,
         });
     }
 
@@ -404,9 +691,12 @@ fn swap(
             let swap_to_default =
                 try_build_swap_msg(&deps.querier, cfg, &from_token, default_bridge, amount_in);
             if let Ok(msg) = swap_to_default {
-                return Ok(SwapTarget::Bridge {
-                    asset: default_bridge.clone(),
-                    msg,
+                return Ok(match msg {
+                    Some(msg) => SwapTarget::Bridge {
+                        asset: default_bridge.clone(),
+                        msg,
+                    },
+                    None => SwapTarget::Skipped,
                 });
             }
         }
@@ -416,7 +706,7 @@ fn swap(
     let swap_to_astro =
         try_build_swap_msg(&deps.querier, cfg, &from_token, &cfg.astro_token, amount_in);
     if let Ok(msg) = swap_to_astro {
-        return Ok(SwapTarget::Astro(msg));
+        return Ok(msg.map_or(SwapTarget::Skipped, SwapTarget::Astro));
     }
 
     Err(ContractError::CannotSwap(from_token))
@@ -428,25 +718,31 @@ fn swap(
 /// * **from_token** token to swap to ASTRO.
 ///
 /// * **amount_in** amount of tokens to swap.
+///
+/// * **now** current block timestamp in seconds. Expired bridges are ignored.
 fn swap_no_validate(
     deps: Deps,
     cfg: &Config,
     from_token: AssetInfo,
     amount_in: Uint128,
+    now: u64,
 ) -> Result<SwapTarget, ContractError> {
     let astro = cfg.astro_token.clone();
 
     // Check if next level bridge exists
-    let bridge_token = BRIDGES.load(deps.storage, from_token.to_string());
-    if let Ok(asset) = bridge_token {
+    let bridge_token = load_bridge(deps.storage, &from_token, now)?;
+    if let Some(asset) = bridge_token {
         let msg = try_build_swap_msg(&deps.querier, cfg, &from_token, &asset, amount_in)?;
-        return Ok(SwapTarget::Bridge { asset, msg });
+        return Ok(match msg {
+            Some(msg) => SwapTarget::Bridge { asset, msg },
+            None => SwapTarget::Skipped,
+        });
     }
 
     // Check for a direct swap to ASTRO
     let swap_to_astro = try_build_swap_msg(&deps.querier, cfg, &from_token, &astro, amount_in);
     if let Ok(msg) = swap_to_astro {
-        return Ok(SwapTarget::Astro(msg));
+        return Ok(msg.map_or(SwapTarget::Skipped, SwapTarget::Astro));
     }
 
     Err(ContractError::CannotSwap(from_token))
@@ -490,11 +786,12 @@ fn swap_bridge_assets(
         })
         .collect();
 
-    let (response, bridge_assets) =
-        swap_assets(deps.as_ref(), &env.contract.address, &cfg, bridges, false)?;
+    let (response, bridge_assets, skipped) =
+        swap_assets(deps.as_ref(), &env, &cfg, bridges, false)?;
 
-    // There should always be some messages, if there are none - something went wrong
-    if response.messages.is_empty() {
+    // There should always be some messages unless the swaps were skipped by the TWAP protection.
+    // Otherwise something went wrong
+    if response.messages.is_empty() && skipped.is_empty() {
         return Err(ContractError::Std(StdError::generic_err(
             "Empty swap messages",
         )));
@@ -515,7 +812,9 @@ fn distribute_astro(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Respon
     }
 
     let mut cfg = CONFIG.load(deps.storage)?;
-    let (distribute_msg, attributes) = distribute(deps, env, &mut cfg)?;
+    let keeper = KEEPER.may_load(deps.storage)?;
+    KEEPER.remove(deps.storage);
+    let (distribute_msg, attributes) = distribute(deps, env, &mut cfg, keeper)?;
     if distribute_msg.is_empty() {
         return Ok(Response::default());
     }
@@ -525,20 +824,55 @@ fn distribute_astro(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Respon
         .add_attributes(attributes))
 }
 
+/// Releases the streamed ASTRO which is due to the xASTRO staking contract.
+/// Keepers can release ASTRO between collections so it reaches stakers without large jumps.
+///
+/// ## Executor
+/// Anyone can execute this.
+fn release_astro(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let (_, release_msg) = release_stream(deps.storage, &env, &cfg)?;
+
+    let mut response = Response::new().add_attribute("action", "release_astro");
+    if let Some((msg, released)) = release_msg {
+        response = response
+            .add_submessage(msg)
+            .add_attribute("astro_released", released);
+    }
+
+    Ok(response)
+}
+
 type DistributeMsgParts = (Vec<SubMsg>, Vec<Attribute>);
 
 /// Private function that performs the ASTRO token distribution to x/vxASTRO.
+///
+/// * **keeper** the address which triggered the collection and receives the keeper bounty.
 fn distribute(
     deps: DepsMut,
     env: Env,
     cfg: &mut Config,
+    keeper: Option<Addr>,
 ) -> Result<DistributeMsgParts, ContractError> {
     let mut result = vec![];
     let mut attributes = vec![];
 
+    // ASTRO which is still being streamed is not distributed again
+    let streamed_amount = ASTRO_STREAM
+        .may_load(deps.storage)?
+        .map(|stream| stream.amount)
+        .unwrap_or_default();
+
+    let (stream, release_msg) = release_stream(deps.storage, &env, cfg)?;
+    if let Some((msg, released)) = release_msg {
+        result.push(msg);
+        attributes.push(attr("astro_released", released));
+    }
+
     let mut amount = cfg
         .astro_token
-        .query_pool(&deps.querier, &env.contract.address)?;
+        .query_pool(&deps.querier, &env.contract.address)?
+        .checked_sub(streamed_amount)?;
     if amount.is_zero() {
         return Ok((result, attributes));
     }
@@ -556,14 +890,7 @@ fn distribute(
             return Ok((result, attributes));
         }
         let mut remainder_reward = cfg.remainder_reward;
-        let astro_distribution_portion = cfg
-            .pre_upgrade_astro_amount
-            .checked_div(Uint128::from(cfg.pre_upgrade_blocks))?;
-
-        current_preupgrade_distribution = min(
-            Uint128::from(blocks_passed).checked_mul(astro_distribution_portion)?,
-            remainder_reward,
-        );
+        current_preupgrade_distribution = unlocked_preupgrade_astro(cfg, env.block.height)?;
 
         // Subtract undistributed rewards
         amount = amount.checked_sub(remainder_reward)?;
@@ -580,64 +907,115 @@ fn distribute(
         CONFIG.save(deps.storage, cfg)?;
     }
 
-    let second_receiver_amount = if let Some(second_receiver_cfg) = &cfg.second_receiver_cfg {
-        let amount = amount.multiply_ratio(
-            Uint128::from(second_receiver_cfg.second_receiver_cut),
-            Uint128::new(100),
-        );
-
-        if !amount.is_zero() {
+    // Pre-upgrade ASTRO is not collected by the keeper thus it is not rewarded
+    let mut keeper_bounty = Uint128::zero();
+    if let Some(keeper) = keeper {
+        keeper_bounty = calc_keeper_bounty(cfg, pure_astro_reward);
+        if !keeper_bounty.is_zero() {
             let asset = Asset {
                 info: cfg.astro_token.clone(),
-                amount,
+                amount: keeper_bounty,
             };
-
-            result.push(SubMsg::new(
-                asset.into_msg(second_receiver_cfg.second_fee_receiver.to_string())?,
-            ))
+            result.push(SubMsg::new(asset.into_msg(keeper)?));
         }
+    }
 
-        amount
-    } else {
-        Uint128::zero()
-    };
-
-    let governance_amount = if let Some(governance_contract) = &cfg.governance_contract {
-        let amount = amount
-            .checked_sub(second_receiver_amount)?
-            .multiply_ratio(Uint128::from(cfg.governance_percent), Uint128::new(100));
+    let split = split_distribution(cfg, amount.checked_sub(keeper_bounty)?)?;
+
+    let epoch = DISTRIBUTIONS
+        .keys(deps.storage, None, None, Order::Descending)
+        .next()
+        .transpose()?
+        .map_or(0, |epoch| epoch + 1);
+    DISTRIBUTIONS.save(
+        deps.storage,
+        epoch,
+        &DistributionRecord {
+            timestamp: env.block.time.seconds(),
+            height: env.block.height,
+            collected: COLLECTED.may_load(deps.storage)?.unwrap_or_default(),
+            astro_amount: amount,
+            keeper_bounty,
+            fee_receivers: split
+                .fee_receivers
+                .iter()
+                .map(|(fee_receiver, amount)| (fee_receiver.receiver.clone(), *amount))
+                .collect(),
+            burn_amount: split.burn,
+            governance_amount: split.governance,
+            staking_amount: split.staking,
+        },
+    )?;
+    COLLECTED.remove(deps.storage);
 
+    for (fee_receiver, amount) in split.fee_receivers {
         if !amount.is_zero() {
+            result.push(build_fee_receiver_msg(
+                &deps.querier,
+                cfg,
+                fee_receiver,
+                amount,
+            )?);
+        }
+    }
+
+    if let Some(astro_burn) = &cfg.astro_burn {
+        if !split.burn.is_zero() {
+            result.push(SubMsg::new(build_burn_msg(
+                &cfg.astro_token,
+                astro_burn,
+                split.burn,
+            )?));
+
+            let burned = BURNED.may_load(deps.storage)?.unwrap_or_default();
+            BURNED.save(deps.storage, &burned.checked_add(split.burn)?)?;
+            attributes.push(attr("astro_burned", split.burn));
+        }
+    }
+
+    if let Some(governance_contract) = &cfg.governance_contract {
+        if !split.governance.is_zero() {
             result.push(SubMsg::new(build_send_msg(
                 &Asset {
                     info: cfg.astro_token.clone(),
-                    amount,
+                    amount: split.governance,
                 },
                 governance_contract.to_string(),
                 None,
             )?))
         }
-
-        amount
-    } else {
-        Uint128::zero()
-    };
+    }
 
     if let Some(staking_contract) = &cfg.staking_contract {
-        let amount = amount.checked_sub(governance_amount + second_receiver_amount)?;
-        if !amount.is_zero() {
-            let to_staking_asset = Asset {
-                info: cfg.astro_token.clone(),
-                amount,
-            };
-            result.push(SubMsg::new(to_staking_asset.into_msg(staking_contract)?));
+        if !split.staking.is_zero() {
+            if let Some(astro_streaming) = &cfg.astro_streaming {
+                let now = env.block.time.seconds();
+                let end = now + astro_streaming.duration;
+                let mut stream = stream.unwrap_or(AstroStream {
+                    amount: Uint128::zero(),
+                    last_release: now,
+                    end,
+                    tranches: vec![],
+                });
+
+                // The new ASTRO is always released over the full duration
+                stream.add_tranche(split.staking, end, MAX_STREAM_TRANCHES);
+                ASTRO_STREAM.save(deps.storage, &stream)?;
+                attributes.push(attr("astro_streamed", split.staking));
+            } else {
+                let to_staking_asset = Asset {
+                    info: cfg.astro_token.clone(),
+                    amount: split.staking,
+                };
+                result.push(SubMsg::new(to_staking_asset.into_msg(staking_contract)?));
+            }
         }
     }
 
-    attributes = vec![
+    attributes.extend([
         attr("action", "distribute_astro"),
         attr("astro_distribution", pure_astro_reward),
-    ];
+    ]);
     if !current_preupgrade_distribution.is_zero() {
         attributes.push(attr(
             "preupgrade_astro_distribution",
@@ -648,6 +1026,106 @@ fn distribute(
     Ok((result, attributes))
 }
 
+/// Releases the streamed ASTRO which is due and saves the rest of the stream.
+/// Returns the rest of the stream along with the message which sends the released ASTRO to the xASTRO staking contract.
+fn release_stream(
+    storage: &mut dyn Storage,
+    env: &Env,
+    cfg: &Config,
+) -> Result<(Option<AstroStream>, Option<(SubMsg, Uint128)>), ContractError> {
+    let mut stream = ASTRO_STREAM.may_load(storage)?;
+    let mut release_msg = None;
+
+    // ASTRO is kept in the stream until there is a staking contract to release it to
+    if let (Some(stream), Some(staking_contract)) = (&mut stream, &cfg.staking_contract) {
+        let released = stream.release(env.block.time.seconds());
+        if !released.is_zero() {
+            let released_asset = Asset {
+                info: cfg.astro_token.clone(),
+                amount: released,
+            };
+            release_msg = Some((
+                SubMsg::new(released_asset.into_msg(staking_contract)?),
+                released,
+            ));
+        }
+    }
+    save_stream(storage, &stream)?;
+
+    Ok((stream, release_msg))
+}
+
+/// Saves the ASTRO stream or removes it once it is fully released.
+fn save_stream(storage: &mut dyn Storage, stream: &Option<AstroStream>) -> StdResult<()> {
+    match stream {
+        Some(stream) if !stream.amount.is_zero() => ASTRO_STREAM.save(storage, stream),
+        _ => {
+            ASTRO_STREAM.remove(storage);
+            Ok(())
+        }
+    }
+}
+
+/// Returns the amount of pre-upgrade ASTRO which is unlocked for distribution at the specified block.
+fn unlocked_preupgrade_astro(cfg: &Config, height: u64) -> StdResult<Uint128> {
+    let blocks_passed = height - cfg.last_distribution_block;
+    let astro_distribution_portion = cfg
+        .pre_upgrade_astro_amount
+        .checked_div(Uint128::from(cfg.pre_upgrade_blocks))?;
+
+    Ok(min(
+        Uint128::from(blocks_passed).checked_mul(astro_distribution_portion)?,
+        cfg.remainder_reward,
+    ))
+}
+
+/// This structure describes how distributed ASTRO is split between its receivers.
+struct DistributionSplit<'a> {
+    fee_receivers: Vec<(&'a FeeReceiver, Uint128)>,
+    governance: Uint128,
+    burn: Uint128,
+    staking: Uint128,
+}
+
+/// Splits ASTRO between additional fee receivers, burning, the governance contract and xASTRO stakers.
+/// Fee receivers are paid first, the burn percentage of the rest is burned,
+/// governance gets its percentage of what is left and stakers get the remainder.
+fn split_distribution(cfg: &Config, amount: Uint128) -> StdResult<DistributionSplit> {
+    let mut fee_receivers = vec![];
+    let mut fee_receivers_amount = Uint128::zero();
+    for fee_receiver in &cfg.fee_receivers {
+        let share = amount.multiply_ratio(Uint128::from(fee_receiver.share), Uint128::new(100));
+        fee_receivers_amount += share;
+        fee_receivers.push((fee_receiver, share));
+    }
+
+    let mut rest = amount.checked_sub(fee_receivers_amount)?;
+    let burn = cfg
+        .astro_burn
+        .as_ref()
+        .map(|astro_burn| rest.multiply_ratio(Uint128::from(astro_burn.percent), Uint128::new(100)))
+        .unwrap_or_default();
+    rest = rest.checked_sub(burn)?;
+
+    let governance = if cfg.governance_contract.is_some() {
+        rest.multiply_ratio(Uint128::from(cfg.governance_percent), Uint128::new(100))
+    } else {
+        Uint128::zero()
+    };
+    let staking = if cfg.staking_contract.is_some() {
+        rest.checked_sub(governance)?
+    } else {
+        Uint128::zero()
+    };
+
+    Ok(DistributionSplit {
+        fee_receivers,
+        burn,
+        governance,
+        staking,
+    })
+}
+
 /// Updates general contract parameters.
 ///
 /// * **factory_contract** address of the factory contract.
@@ -662,7 +1140,17 @@ fn distribute(
 ///
 /// * **max_spread** max spread used when swapping fee tokens to ASTRO.
 ///
-/// * **second_receiver_params** describes the second receiver of fees
+/// * **fee_receivers** replaces the list of additional fee receivers.
+///
+/// * **swap_protection** sets or removes the TWAP protection of fee swaps.
+///
+/// * **keeper_bounty** sets or removes the keeper bounty.
+///
+/// * **auto_bridge** enables or disables the automatic bridge discovery.
+///
+/// * **astro_streaming** enables or disables the streaming of ASTRO to the xASTRO staking contract.
+///
+/// * **astro_burn** enables or disables burning of ASTRO.
 ///
 /// ## Executor
 /// Only the owner can execute this.
@@ -676,7 +1164,12 @@ fn update_config(
     governance_percent: Option<Uint64>,
     default_bridge_opt: Option<AssetInfo>,
     max_spread: Option<Decimal>,
-    second_receiver_params: Option<SecondReceiverParams>,
+    fee_receivers: Option<Vec<FeeReceiverParams>>,
+    swap_protection: Option<UpdateSwapProtection>,
+    keeper_bounty: Option<UpdateKeeperBounty>,
+    auto_bridge: Option<UpdateAutoBridge>,
+    astro_streaming: Option<UpdateAstroStreaming>,
+    astro_burn: Option<UpdateAstroBurn>,
 ) -> Result<Response, ContractError> {
     let mut attributes = vec![attr("action", "set_config")];
 
@@ -743,24 +1236,101 @@ fn update_config(
         attributes.push(attr("max_spread", max_spread.to_string()));
     };
 
-    update_second_receiver_cfg(deps.as_ref(), &mut config, &second_receiver_params)?;
-
-    if let Some(second_receiver_params) = second_receiver_params {
+    if let Some(fee_receivers) = fee_receivers {
+        config.fee_receivers = validate_fee_receivers(deps.as_ref(), &config, fee_receivers)?;
         attributes.push(attr(
-            "second_fee_receiver",
-            second_receiver_params.second_fee_receiver,
-        ));
-        attributes.push(attr(
-            "second_receiver_cut",
-            second_receiver_params.second_receiver_cut,
+            "fee_receivers",
+            fee_receivers_attr(&config.fee_receivers),
         ));
     }
 
+    match swap_protection {
+        Some(UpdateSwapProtection::Set(swap_protection)) => {
+            validate_swap_protection(deps.as_ref(), &config, &swap_protection)?;
+            config.swap_protection = Some(swap_protection);
+            attributes.push(attr("swap_protection", "set"));
+        }
+        Some(UpdateSwapProtection::Remove {}) => {
+            config.swap_protection = None;
+            attributes.push(attr("swap_protection", "removed"));
+        }
+        None => {}
+    }
+
+    match keeper_bounty {
+        Some(UpdateKeeperBounty::Set(keeper_bounty)) => {
+            validate_keeper_bounty(&keeper_bounty)?;
+            config.keeper_bounty = Some(keeper_bounty);
+            attributes.push(attr("keeper_bounty", "set"));
+        }
+        Some(UpdateKeeperBounty::Remove {}) => {
+            config.keeper_bounty = None;
+            attributes.push(attr("keeper_bounty", "removed"));
+        }
+        None => {}
+    }
+
+    match auto_bridge {
+        Some(UpdateAutoBridge::Set(auto_bridge)) => {
+            validate_auto_bridge(&auto_bridge)?;
+            config.auto_bridge = Some(auto_bridge);
+            attributes.push(attr("auto_bridge", "set"));
+        }
+        Some(UpdateAutoBridge::Remove {}) => {
+            clear_discovered_bridges(deps.storage)?;
+            config.auto_bridge = None;
+            attributes.push(attr("auto_bridge", "removed"));
+        }
+        None => {}
+    }
+
+    match astro_streaming {
+        Some(UpdateAstroStreaming::Set(astro_streaming)) => {
+            validate_astro_streaming(&astro_streaming)?;
+            config.astro_streaming = Some(astro_streaming);
+            attributes.push(attr("astro_streaming", "set"));
+        }
+        Some(UpdateAstroStreaming::Remove {}) => {
+            config.astro_streaming = None;
+            attributes.push(attr("astro_streaming", "removed"));
+        }
+        None => {}
+    }
+
+    match astro_burn {
+        Some(UpdateAstroBurn::Set(params)) => {
+            config.astro_burn = Some(validate_astro_burn(deps.api, &config.astro_token, params)?);
+            attributes.push(attr("astro_burn", "set"));
+        }
+        Some(UpdateAstroBurn::Remove {}) => {
+            config.astro_burn = None;
+            attributes.push(attr("astro_burn", "removed"));
+        }
+        None => {}
+    }
+
+    if config.auto_bridge.is_some() && config.swap_protection.is_none() {
+        return Err(ContractError::AutoBridgeRequiresSwapProtection {});
+    }
+
     CONFIG.save(deps.storage, &config)?;
 
     Ok(Response::new().add_attributes(attributes))
 }
 
+/// Formats fee receivers as a comma separated list of `receiver:share` for response attributes.
+fn fee_receivers_attr(fee_receivers: &[FeeReceiver]) -> String {
+    if fee_receivers.is_empty() {
+        return String::from("none");
+    }
+
+    fee_receivers
+        .iter()
+        .map(|fee_receiver| format!("{}:{}", fee_receiver.receiver, fee_receiver.share))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
 /// Adds or removes bridge tokens used to swap fee tokens to ASTRO.
 ///
 /// * **add** array of bridge tokens added to swap fee tokens with.
@@ -771,6 +1341,7 @@ fn update_config(
 /// Only the owner can execute this.
 fn update_bridges(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     add: Option<Vec<(AssetInfo, AssetInfo)>>,
     remove: Option<Vec<AssetInfo>>,
@@ -785,10 +1356,9 @@ fn update_bridges(
     // Remove old bridges
     if let Some(remove_bridges) = remove {
         for asset in remove_bridges {
-            BRIDGES.remove(
-                deps.storage,
-                deps.api.addr_validate(&asset.to_string())?.to_string(),
-            );
+            let key = deps.api.addr_validate(&asset.to_string())?.to_string();
+            BRIDGES.remove(deps.storage, key.clone());
+            BRIDGES_EXPIRY.remove(deps.storage, key);
         }
     }
 
@@ -808,15 +1378,55 @@ fn update_bridges(
                 &bridge,
                 &astro,
                 BRIDGES_INITIAL_DEPTH,
+                env.block.time.seconds(),
             )?;
 
+            // Bridges added by the owner never expire
             BRIDGES.save(deps.storage, asset.to_string(), &bridge)?;
+            BRIDGES_EXPIRY.remove(deps.storage, asset.to_string());
         }
     }
 
     Ok(Response::default().add_attribute("action", "update_bridges"))
 }
 
+/// Adds or removes limits applied to collections of fee tokens.
+///
+/// * **add** array of fee tokens along with their collect limits.
+///
+/// * **remove** array of fee tokens whose collect limits are removed.
+///
+/// ## Executor
+/// Only the owner can execute this.
+fn update_collect_limits(
+    deps: DepsMut,
+    info: MessageInfo,
+    add: Option<Vec<(AssetInfo, CollectLimit)>>,
+    remove: Option<Vec<AssetInfo>>,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+
+    // Permission check
+    if info.sender != cfg.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(remove_limits) = remove {
+        for asset in remove_limits {
+            COLLECT_LIMITS.remove(deps.storage, asset.to_string());
+        }
+    }
+
+    if let Some(add_limits) = add {
+        for (asset, collect_limit) in add_limits {
+            validate_collect_limit(&collect_limit)?;
+            COLLECT_LIMITS.save(deps.storage, asset.to_string(), &collect_limit)?;
+        }
+    }
+
+    Ok(Response::default().add_attribute("action", "update_collect_limits"))
+}
+
 /// Exposes all the queries available in the contract.
 ///
 /// ## Queries
@@ -827,12 +1437,33 @@ fn update_bridges(
 ///
 /// * **QueryMsg::Bridges {}** Returns the bridges used for swapping fee tokens
 /// using a vector of [`(String, String)`] denoting Asset -> Bridge connections.
+///
+/// * **QueryMsg::SimulateCollect { assets }** Returns the swap plan and the resulting ASTRO distribution
+/// of collecting the specified assets using a [`SimulateCollectResponse`] object.
+///
+/// * **QueryMsg::Distributions { start_after, limit }** Returns the history of ASTRO distributions
+/// using a vector of [`(u64, DistributionRecord)`] denoting Epoch -> Distribution records.
+///
+/// * **QueryMsg::AstroStream {}** Returns the ASTRO which is being streamed to the xASTRO staking contract
+/// using an [`AstroStream`] object.
+///
+/// * **QueryMsg::CollectLimits {}** Returns the limits applied to collections of fee tokens
+/// using a vector of [`(String, CollectLimit)`] denoting Asset -> Collect limit connections.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_get_config(deps)?),
         QueryMsg::Balances { assets } => to_binary(&query_get_balances(deps, env, assets)?),
-        QueryMsg::Bridges {} => to_binary(&query_bridges(deps)?),
+        QueryMsg::Bridges {} => to_binary(&query_bridges(deps, env)?),
+        QueryMsg::SimulateCollect { assets } => to_binary(
+            &query_simulate_collect(deps, env, assets)
+                .map_err(|err| StdError::generic_err(err.to_string()))?,
+        ),
+        QueryMsg::Distributions { start_after, limit } => {
+            to_binary(&query_distributions(deps, start_after, limit)?)
+        }
+        QueryMsg::AstroStream {} => to_binary(&ASTRO_STREAM.may_load(deps.storage)?),
+        QueryMsg::CollectLimits {} => to_binary(&query_collect_limits(deps)?),
     }
 }
 
@@ -850,7 +1481,13 @@ fn query_get_config(deps: Deps) -> StdResult<ConfigResponse> {
         remainder_reward: config.remainder_reward,
         pre_upgrade_astro_amount: config.pre_upgrade_astro_amount,
         default_bridge: config.default_bridge,
-        second_receiver_cfg: config.second_receiver_cfg,
+        fee_receivers: config.fee_receivers,
+        swap_protection: config.swap_protection,
+        keeper_bounty: config.keeper_bounty,
+        auto_bridge: config.auto_bridge,
+        astro_streaming: config.astro_streaming,
+        astro_burn: config.astro_burn,
+        burned_amount: BURNED.may_load(deps.storage)?.unwrap_or_default(),
     })
 }
 
@@ -874,17 +1511,184 @@ fn query_get_balances(deps: Deps, env: Env, assets: Vec<AssetInfo>) -> StdResult
     Ok(resp)
 }
 
-/// Returns bridge tokens used for swapping fee tokens to ASTRO.
-fn query_bridges(deps: Deps) -> StdResult<Vec<(String, String)>> {
+/// Returns bridge tokens used for swapping fee tokens to ASTRO. Expired bridges are omitted.
+fn query_bridges(deps: Deps, env: Env) -> StdResult<Vec<(String, String)>> {
+    let now = env.block.time.seconds();
     BRIDGES
         .range(deps.storage, None, None, Order::Ascending)
-        .map(|bridge| {
-            let (bridge, asset) = bridge?;
-            Ok((bridge, asset.to_string()))
+        .filter_map(|bridge| {
+            let (bridge, asset) = match bridge {
+                Ok(bridge) => bridge,
+                Err(err) => return Some(Err(err)),
+            };
+            match BRIDGES_EXPIRY.may_load(deps.storage, bridge.clone()) {
+                Ok(Some(expires_at)) if expires_at <= now => None,
+                Ok(_) => Some(Ok((bridge, asset.to_string()))),
+                Err(err) => Some(Err(err)),
+            }
         })
         .collect()
 }
 
+/// Returns limits applied to collections of fee tokens.
+fn query_collect_limits(deps: Deps) -> StdResult<Vec<(String, CollectLimit)>> {
+    COLLECT_LIMITS
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect()
+}
+
+/// Simulates the collection of fee tokens using a [`SimulateCollectResponse`] object.
+/// Every fee token is simulated independently against the current pool reserves.
+///
+/// * **assets** array with fee tokens to collect.
+fn query_simulate_collect(
+    deps: Deps,
+    env: Env,
+    assets: Vec<AssetWithLimit>,
+) -> Result<SimulateCollectResponse, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+
+    check_duplicated_assets(&assets)?;
+
+    let mut swaps = vec![];
+    let mut skipped = vec![];
+    let mut unwrapped_astro = Uint128::zero();
+    for a in assets.into_iter().filter(|a| a.info.ne(&cfg.astro_token)) {
+        let info = a.info.clone();
+        let a = match apply_collect_limit(deps, &env, &cfg, a)? {
+            Some(a) => a,
+            None => {
+                skipped.push((info, SkipReason::CollectLimit));
+                continue;
+            }
+        };
+        let balance = collect_amount(deps, &env.contract.address, &a)?;
+
+        if balance.is_zero() {
+            continue;
+        }
+
+        if let Some(pair) = query_lp_pair(&deps.querier, &cfg.factory_contract, &a.info) {
+            // LP tokens are simulated as swaps of their share of the pool
+            let share: Vec<Asset> = deps.querier.query_wasm_smart(
+                &pair.contract_addr,
+                &PairQueryMsg::Share { amount: balance },
+            )?;
+            for asset in share.into_iter().filter(|asset| !asset.amount.is_zero()) {
+                if asset.info == cfg.astro_token {
+                    unwrapped_astro += asset.amount;
+                } else {
+                    let info = asset.info.clone();
+                    match simulate_collected_swap(deps, &env, &cfg, asset)? {
+                        Some(swap) => swaps.push(swap),
+                        None => skipped.push((info, SkipReason::NoRoute)),
+                    }
+                }
+            }
+        } else {
+            let asset = Asset {
+                info: a.info.clone(),
+                amount: balance,
+            };
+            match simulate_collected_swap(deps, &env, &cfg, asset)? {
+                Some(swap) => swaps.push(swap),
+                None => skipped.push((a.info, SkipReason::NoRoute)),
+            }
+        }
+    }
+
+    let streamed_amount = ASTRO_STREAM
+        .may_load(deps.storage)?
+        .map(|stream| stream.amount)
+        .unwrap_or_default();
+    let balance = cfg
+        .astro_token
+        .query_pool(&deps.querier, &env.contract.address)?
+        .checked_sub(streamed_amount)?
+        + unwrapped_astro
+        + swaps.iter().map(|swap| swap.astro_amount).sum::<Uint128>();
+
+    // Mirrors the pre-upgrade ASTRO handling of the distribution
+    let (astro_amount, pure_astro_reward) = if !cfg.rewards_enabled {
+        (Uint128::zero(), Uint128::zero())
+    } else if !cfg.remainder_reward.is_zero() {
+        let pure_astro_reward = balance.checked_sub(cfg.remainder_reward)?;
+        (
+            pure_astro_reward + unlocked_preupgrade_astro(&cfg, env.block.height)?,
+            pure_astro_reward,
+        )
+    } else {
+        (balance, balance)
+    };
+
+    let keeper_bounty = calc_keeper_bounty(&cfg, pure_astro_reward);
+    let below_min_astro_amount = matches!(
+        &cfg.keeper_bounty,
+        Some(bounty) if !pure_astro_reward.is_zero() && pure_astro_reward < bounty.min_astro_amount
+    );
+    let split = split_distribution(&cfg, astro_amount.checked_sub(keeper_bounty)?)?;
+
+    Ok(SimulateCollectResponse {
+        astro_amount,
+        keeper_bounty,
+        fee_receivers: split
+            .fee_receivers
+            .into_iter()
+            .map(|(fee_receiver, amount)| (fee_receiver.receiver.clone(), amount))
+            .collect(),
+        burn_amount: split.burn,
+        governance_amount: split.governance,
+        staking_amount: split.staking,
+        swaps,
+        skipped,
+        below_min_astro_amount,
+    })
+}
+
+/// Simulates the swap of a fee token the same way a collection swaps it,
+/// including bridges which the collection discovers when [`Config::auto_bridge`] is set.
+/// Returns None if the fee token cannot be swapped to ASTRO.
+///
+/// * **offer_asset** fee token and the amount to swap.
+fn simulate_collected_swap(
+    deps: Deps,
+    env: &Env,
+    cfg: &Config,
+    offer_asset: Asset,
+) -> Result<Option<SwapSimulation>, ContractError> {
+    let now = env.block.time.seconds();
+    match simulate_swap(deps, cfg, offer_asset.clone(), now) {
+        Ok(simulation) => Ok(Some(simulation)),
+        Err(ContractError::CannotSwap(_))
+            if cfg.auto_bridge.is_some()
+                && !is_discovery_cached(deps.storage, &offer_asset.info, now)? =>
+        {
+            Ok(find_bridges(deps, cfg, &offer_asset, now)?.map(|(_, simulation)| simulation))
+        }
+        Err(ContractError::CannotSwap(_)) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Returns the history of ASTRO distributions ordered by epoch.
+///
+/// * **start_after** the epoch after which to start reading.
+///
+/// * **limit** the maximum amount of distributions to read.
+fn query_distributions(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(u64, DistributionRecord)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    DISTRIBUTIONS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect()
+}
+
 /// Manages contract migration.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn migrate(mut deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
@@ -896,7 +1700,7 @@ pub fn migrate(mut deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response
                 migrate_from_v1(deps.branch(), &msg)?;
             }
             "1.2.0" => migrate_from_v120(deps.branch(), msg)?,
-            "1.3.0" => {}
+            "1.3.0" | "1.3.1" => migrate_from_v130(deps.branch(), msg)?,
             _ => return Err(ContractError::MigrationError {}),
         },
         _ => return Err(ContractError::MigrationError {}),
@@ -1,5 +1,5 @@
 use astroport::asset::AssetInfo;
-use cosmwasm_std::{DivideByZeroError, OverflowError, StdError};
+use cosmwasm_std::{DivideByZeroError, OverflowError, StdError, Uint64};
 use thiserror::Error;
 
 /// This enum describes maker contract errors
@@ -35,6 +35,42 @@ pub enum ContractError {
     #[error("Incorrect max spread")]
     IncorrectMaxSpread {},
 
+    #[error("Incorrect max deviation from the TWAP")]
+    IncorrectMaxDeviation {},
+
+    #[error("Pair {0} doesn't support price observations")]
+    ObservationsNotSupported(String),
+
+    #[error("Incorrect fee receivers share. Each share should be positive and their total should not exceed {0}")]
+    IncorrectFeeReceiversShare(Uint64),
+
+    #[error("Too many fee receivers. The maximum is {0}")]
+    TooManyFeeReceivers(usize),
+
+    #[error("Duplicated fee receiver {0}")]
+    DuplicatedFeeReceiver(String),
+
+    #[error("Incorrect keeper bounty share")]
+    IncorrectKeeperBountyShare {},
+
+    #[error("Automatic bridge discovery requires the swap protection")]
+    AutoBridgeRequiresSwapProtection {},
+
+    #[error("Bridge cache TTL must be positive")]
+    InvalidBridgeCacheTtl {},
+
+    #[error("ASTRO streaming duration must be positive")]
+    InvalidStreamingDuration {},
+
+    #[error("Incorrect burn percent. It should be positive and not greater than 100")]
+    IncorrectBurnPercent {},
+
+    #[error("A burn address is required to burn native ASTRO")]
+    BurnAddressRequired {},
+
+    #[error("Collect limit pool share must be positive and not greater than 1")]
+    InvalidCollectLimit {},
+
     #[error("Cannot collect. Remove duplicate asset")]
     DuplicatedAsset {},
 
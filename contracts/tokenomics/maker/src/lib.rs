@@ -1,6 +1,7 @@
 extern crate core;
 extern crate cosmwasm_std;
 
+pub mod bridges;
 pub mod contract;
 pub mod error;
 pub mod state;
@@ -1,13 +1,31 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Decimal, DepsMut, StdResult, Uint128, Uint64};
+use cosmwasm_std::{Addr, Decimal, DepsMut, StdError, Uint128, Uint64};
 use cw_storage_plus::Item;
 
+use crate::error::ContractError;
 use crate::state::CONFIG;
-use crate::utils::update_second_receiver_cfg;
+use crate::utils::validate_fee_receivers;
 use astroport::asset::{token_asset_info, AssetInfo};
-use astroport::maker::{Config, MigrateMsg};
+use astroport::maker::{Config, FeeReceiver, FeeReceiverParams, MigrateMsg};
 
-pub(crate) fn migrate_from_v1(deps: DepsMut, msg: &MigrateMsg) -> StdResult<()> {
+/// Returns the fee receivers set in the migration message.
+/// The deprecated second receiver parameters are converted to the only fee receiver paid in ASTRO.
+fn fee_receivers_params(msg: &MigrateMsg) -> Result<Option<Vec<FeeReceiverParams>>, ContractError> {
+    match (&msg.fee_receivers, &msg.second_receiver_params) {
+        (Some(_), Some(_)) => Err(StdError::generic_err(
+            "Either fee_receivers or second_receiver_params can be set",
+        )
+        .into()),
+        (_, Some(params)) => Ok(Some(vec![FeeReceiverParams {
+            receiver: params.second_fee_receiver.clone(),
+            share: params.second_receiver_cut,
+            payout_asset: None,
+        }])),
+        (fee_receivers, None) => Ok(fee_receivers.clone()),
+    }
+}
+
+pub(crate) fn migrate_from_v1(deps: DepsMut, msg: &MigrateMsg) -> Result<(), ContractError> {
     #[cw_serde]
     struct OldConfig {
         pub owner: Addr,
@@ -43,15 +61,25 @@ pub(crate) fn migrate_from_v1(deps: DepsMut, msg: &MigrateMsg) -> StdResult<()>
         last_distribution_block: old_config.last_distribution_block,
         remainder_reward: old_config.remainder_reward,
         pre_upgrade_astro_amount: old_config.pre_upgrade_astro_amount,
-        second_receiver_cfg: None,
+        fee_receivers: vec![],
+        swap_protection: None,
+        keeper_bounty: None,
+        auto_bridge: None,
+        astro_streaming: None,
+        astro_burn: None,
     };
 
-    update_second_receiver_cfg(deps.as_ref(), &mut new_config, &msg.second_receiver_params)?;
+    if let Some(fee_receivers) = fee_receivers_params(msg)? {
+        new_config.fee_receivers =
+            validate_fee_receivers(deps.as_ref(), &new_config, fee_receivers)?;
+    }
+
+    CONFIG.save(deps.storage, &new_config)?;
 
-    CONFIG.save(deps.storage, &new_config)
+    Ok(())
 }
 
-pub(crate) fn migrate_from_v120(deps: DepsMut, msg: MigrateMsg) -> StdResult<()> {
+pub(crate) fn migrate_from_v120(deps: DepsMut, msg: MigrateMsg) -> Result<(), ContractError> {
     #[cw_serde]
     struct ConfigV120 {
         pub owner: Addr,
@@ -84,10 +112,87 @@ pub(crate) fn migrate_from_v120(deps: DepsMut, msg: MigrateMsg) -> StdResult<()>
         last_distribution_block: cfg_v120.last_distribution_block,
         remainder_reward: cfg_v120.remainder_reward,
         pre_upgrade_astro_amount: cfg_v120.pre_upgrade_astro_amount,
-        second_receiver_cfg: None,
+        fee_receivers: vec![],
+        swap_protection: None,
+        keeper_bounty: None,
+        auto_bridge: None,
+        astro_streaming: None,
+        astro_burn: None,
+    };
+
+    if let Some(fee_receivers) = fee_receivers_params(&msg)? {
+        new_config.fee_receivers =
+            validate_fee_receivers(deps.as_ref(), &new_config, fee_receivers)?;
+    }
+
+    CONFIG.save(deps.storage, &new_config)?;
+
+    Ok(())
+}
+
+pub(crate) fn migrate_from_v130(deps: DepsMut, msg: MigrateMsg) -> Result<(), ContractError> {
+    #[cw_serde]
+    struct SecondReceiverConfig {
+        pub second_fee_receiver: Addr,
+        pub second_receiver_cut: Uint64,
+    }
+
+    #[cw_serde]
+    struct ConfigV130 {
+        pub owner: Addr,
+        pub factory_contract: Addr,
+        pub staking_contract: Option<Addr>,
+        pub default_bridge: Option<AssetInfo>,
+        pub governance_contract: Option<Addr>,
+        pub governance_percent: Uint64,
+        pub astro_token: AssetInfo,
+        pub max_spread: Decimal,
+        pub rewards_enabled: bool,
+        pub pre_upgrade_blocks: u64,
+        pub last_distribution_block: u64,
+        pub remainder_reward: Uint128,
+        pub pre_upgrade_astro_amount: Uint128,
+        pub second_receiver_cfg: Option<SecondReceiverConfig>,
+    }
+    let cfg_v130: ConfigV130 = Item::new("config").load(deps.storage)?;
+
+    let mut new_config = Config {
+        owner: cfg_v130.owner,
+        factory_contract: cfg_v130.factory_contract,
+        staking_contract: cfg_v130.staking_contract,
+        default_bridge: cfg_v130.default_bridge,
+        governance_contract: cfg_v130.governance_contract,
+        governance_percent: cfg_v130.governance_percent,
+        astro_token: cfg_v130.astro_token,
+        max_spread: cfg_v130.max_spread,
+        rewards_enabled: cfg_v130.rewards_enabled,
+        pre_upgrade_blocks: cfg_v130.pre_upgrade_blocks,
+        last_distribution_block: cfg_v130.last_distribution_block,
+        remainder_reward: cfg_v130.remainder_reward,
+        pre_upgrade_astro_amount: cfg_v130.pre_upgrade_astro_amount,
+        // The second receiver becomes the only additional fee receiver and keeps being paid in ASTRO
+        fee_receivers: cfg_v130
+            .second_receiver_cfg
+            .into_iter()
+            .map(|second_receiver| FeeReceiver {
+                receiver: second_receiver.second_fee_receiver,
+                share: second_receiver.second_receiver_cut,
+                payout_asset: None,
+            })
+            .collect(),
+        swap_protection: None,
+        keeper_bounty: None,
+        auto_bridge: None,
+        astro_streaming: None,
+        astro_burn: None,
     };
 
-    update_second_receiver_cfg(deps.as_ref(), &mut new_config, &msg.second_receiver_params)?;
+    if let Some(fee_receivers) = fee_receivers_params(&msg)? {
+        new_config.fee_receivers =
+            validate_fee_receivers(deps.as_ref(), &new_config, fee_receivers)?;
+    }
+
+    CONFIG.save(deps.storage, &new_config)?;
 
-    CONFIG.save(deps.storage, &new_config)
+    Ok(())
 }
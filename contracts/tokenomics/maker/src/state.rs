@@ -1,6 +1,7 @@
-use astroport::asset::AssetInfo;
+use astroport::asset::{Asset, AssetInfo};
 use astroport::common::OwnershipProposal;
-use astroport::maker::Config;
+use astroport::maker::{AstroStream, CollectLimit, Config, DistributionRecord};
+use cosmwasm_std::{Addr, Uint128};
 use cw_storage_plus::{Item, Map};
 
 /// Stores the contract configuration at the given key
@@ -11,3 +12,37 @@ pub const OWNERSHIP_PROPOSAL: Item<OwnershipProposal> = Item::new("ownership_pro
 
 /// Stores bridge tokens used to swap fee tokens to ASTRO
 pub const BRIDGES: Map<String, AssetInfo> = Map::new("bridges");
+
+/// Stores expiration timestamps of bridges which were discovered automatically
+pub const BRIDGES_EXPIRY: Map<String, u64> = Map::new("bridges_expiry");
+
+/// Stores timestamps until which fee tokens without any discovered bridges are not discovered again
+pub const BRIDGES_NOT_FOUND: Map<String, u64> = Map::new("bridges_not_found");
+
+/// Stores limits applied to collections of fee tokens
+pub const COLLECT_LIMITS: Map<String, CollectLimit> = Map::new("collect_limits");
+
+/// Stores timestamps of the last collections of fee tokens
+pub const LAST_COLLECTS: Map<String, u64> = Map::new("last_collects");
+
+/// Stores the address which triggered the current collection until its ASTRO is distributed
+pub const KEEPER: Item<Addr> = Item::new("keeper");
+
+/// Stores ASTRO which is being streamed to the xASTRO staking contract
+pub const ASTRO_STREAM: Item<AstroStream> = Item::new("astro_stream");
+
+/// Stores the total amount of ASTRO burned by the contract
+pub const BURNED: Item<Uint128> = Item::new("burned");
+
+/// Stores fee tokens which were collected and swapped to ASTRO but not recorded in a distribution yet
+pub const COLLECTED: Item<Vec<Asset>> = Item::new("collected");
+
+/// Stores the history of ASTRO distributions by epoch
+pub const DISTRIBUTIONS: Map<u64, DistributionRecord> = Map::new("distributions");
+
+/// ## Pagination settings
+/// The maximum amount of distributions that can be read at once from [`DISTRIBUTIONS`]
+pub const MAX_LIMIT: u32 = 30;
+
+/// The default amount of distributions to read from [`DISTRIBUTIONS`]
+pub const DEFAULT_LIMIT: u32 = 10;
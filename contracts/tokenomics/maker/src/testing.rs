@@ -1,10 +1,23 @@
-use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-use cosmwasm_std::{from_binary, Addr, Decimal, Uint128, Uint64};
+use cosmwasm_std::testing::{
+    mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+};
+use cosmwasm_std::{
+    from_binary, to_binary, Addr, ContractResult, CosmosMsg, Decimal, OwnedDeps, QuerierWrapper,
+    SystemError, SystemResult, Uint128, Uint64, WasmMsg, WasmQuery,
+};
 
 use crate::contract::{execute, instantiate, query};
+use crate::error::ContractError;
 use crate::state::CONFIG;
-use astroport::asset::{native_asset_info, token_asset_info};
-use astroport::maker::{Config, ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
+use crate::utils::try_build_swap_msg;
+use astroport::asset::{native_asset_info, token_asset_info, Asset, PairInfo};
+use astroport::factory::{PairType, QueryMsg as FactoryQueryMsg};
+use astroport::maker::{
+    Config, ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg, SwapProtection, TwapSource,
+    UpdateSwapProtection,
+};
+use astroport::pair::SimulationResponse;
+use astroport::pair_concentrated_inj::{OracleObservation, QueryMsg as PairQueryMsg};
 use std::str::FromStr;
 
 #[test]
@@ -29,7 +42,12 @@ fn proper_initialization() {
         astro_token: token_asset_info(astro_token_contract.clone()),
         default_bridge: Some(native_asset_info("uluna".to_string())),
         max_spread: None,
-        second_receiver_params: None,
+        fee_receivers: None,
+        swap_protection: None,
+        keeper_bounty: None,
+        auto_bridge: None,
+        astro_streaming: None,
+        astro_burn: None,
     };
     let res = instantiate(deps.as_mut(), env, info, instantiate_msg).unwrap();
     assert_eq!(0, res.messages.len());
@@ -51,7 +69,12 @@ fn proper_initialization() {
             last_distribution_block: 0,
             remainder_reward: Uint128::zero(),
             pre_upgrade_astro_amount: Uint128::zero(),
-            second_receiver_cfg: None
+            fee_receivers: vec![],
+            swap_protection: None,
+            keeper_bounty: None,
+            auto_bridge: None,
+            astro_streaming: None,
+            astro_burn: None,
         }
     )
 }
@@ -77,7 +100,12 @@ fn update_owner() {
         astro_token: token_asset_info(astro_token_contract),
         default_bridge: Some(native_asset_info("uluna".to_string())),
         max_spread: None,
-        second_receiver_params: None,
+        fee_receivers: None,
+        swap_protection: None,
+        keeper_bounty: None,
+        auto_bridge: None,
+        astro_streaming: None,
+        astro_burn: None,
     };
 
     let env = mock_env();
@@ -142,3 +170,223 @@ fn update_owner() {
         from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap()).unwrap();
     assert_eq!(new_owner, config.owner);
 }
+
+/// Mocks a factory with a single ULUNA<>ASTRO pool of the given type. The pool returns 49 ASTRO for every 100 ULUNA
+/// and observes ASTRO prices of 1.9, 2, 2 and 2.1 ULUNA over the last 600 seconds.
+fn mock_observation_pool(pair_type: PairType) -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+    let mut deps = mock_dependencies();
+    let pair_info = PairInfo {
+        asset_infos: vec![
+            native_asset_info("uluna".to_string()),
+            token_asset_info(Addr::unchecked("astro-token")),
+        ],
+        contract_addr: Addr::unchecked("pool"),
+        liquidity_token: Addr::unchecked("lp-token"),
+        pair_type,
+    };
+
+    deps.querier.update_wasm(move |query| match query {
+        WasmQuery::Smart { contract_addr, msg } if contract_addr == "factory" => {
+            match from_binary(msg).unwrap() {
+                FactoryQueryMsg::Pair { .. } => {
+                    SystemResult::Ok(ContractResult::Ok(to_binary(&pair_info).unwrap()))
+                }
+                _ => unimplemented!(),
+            }
+        }
+        WasmQuery::Smart { contract_addr, msg } if contract_addr == "pool" => {
+            match from_binary(msg).unwrap() {
+                PairQueryMsg::Observe { seconds_ago } => {
+                    let price = match seconds_ago {
+                        150 => "1.9",
+                        300 | 450 => "2",
+                        600 => "2.1",
+                        _ => {
+                            return SystemResult::Ok(ContractResult::Err(format!(
+                                "Unexpected observation {seconds_ago} seconds ago"
+                            )))
+                        }
+                    };
+                    SystemResult::Ok(ContractResult::Ok(
+                        to_binary(&OracleObservation {
+                            timestamp: 0,
+                            price: Decimal::from_str(price).unwrap(),
+                        })
+                        .unwrap(),
+                    ))
+                }
+                PairQueryMsg::Simulation { offer_asset, .. } => {
+                    SystemResult::Ok(ContractResult::Ok(
+                        to_binary(&SimulationResponse {
+                            return_amount: offer_asset.amount * Decimal::percent(49),
+                            spread_amount: Uint128::zero(),
+                            commission_amount: Uint128::zero(),
+                        })
+                        .unwrap(),
+                    ))
+                }
+                _ => unimplemented!(),
+            }
+        }
+        _ => SystemResult::Err(SystemError::NoSuchContract {
+            addr: format!("{query:?}"),
+        }),
+    });
+
+    deps
+}
+
+#[test]
+fn pair_observation_twap_source() {
+    let swap_protection = SwapProtection {
+        twap_source: TwapSource::PairObservation { seconds_ago: 600 },
+        max_deviation: Decimal::percent(5),
+    };
+    let update_config = |swap_protection: SwapProtection| ExecuteMsg::UpdateConfig {
+        factory_contract: None,
+        staking_contract: None,
+        governance_contract: None,
+        governance_percent: None,
+        basic_asset: None,
+        max_spread: None,
+        fee_receivers: None,
+        swap_protection: Some(UpdateSwapProtection::Set(swap_protection)),
+        keeper_bounty: None,
+        auto_bridge: None,
+        astro_streaming: None,
+        astro_burn: None,
+    };
+    let instantiate_msg = InstantiateMsg {
+        owner: "owner".to_string(),
+        factory_contract: "factory".to_string(),
+        staking_contract: Some("staking".to_string()),
+        governance_contract: None,
+        governance_percent: None,
+        astro_token: token_asset_info(Addr::unchecked("astro-token")),
+        default_bridge: Some(native_asset_info("uluna".to_string())),
+        max_spread: None,
+        fee_receivers: None,
+        swap_protection: None,
+        keeper_bounty: None,
+        auto_bridge: None,
+        astro_streaming: None,
+        astro_burn: None,
+    };
+
+    // The default bridge pool doesn't store observations
+    let mut deps = mock_observation_pool(PairType::Xyk {});
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        instantiate_msg.clone(),
+    )
+    .unwrap();
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        update_config(swap_protection.clone()),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        ContractError::ObservationsNotSupported("pool".to_string()).to_string()
+    );
+
+    // Swaps through pools without observations are skipped
+    let cfg = Config {
+        swap_protection: Some(swap_protection.clone()),
+        ..CONFIG.load(deps.as_ref().storage).unwrap()
+    };
+    let swap_msg = try_build_swap_msg(
+        &QuerierWrapper::new(&deps.querier),
+        &cfg,
+        &native_asset_info("uluna".to_string()),
+        &cfg.astro_token,
+        Uint128::new(100_000000),
+    )
+    .unwrap();
+    assert_eq!(swap_msg, None);
+
+    let mut deps =
+        mock_observation_pool(PairType::Custom("concentrated_inj_orderbook".to_string()));
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        instantiate_msg,
+    )
+    .unwrap();
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        update_config(SwapProtection {
+            twap_source: TwapSource::PairObservation { seconds_ago: 3 },
+            ..swap_protection.clone()
+        }),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Generic error: Observation window must be at least 4 seconds"
+    );
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        update_config(swap_protection.clone()),
+    )
+    .unwrap();
+    let cfg = CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(cfg.swap_protection, Some(swap_protection.clone()));
+
+    // Averaged observed price is 2 ULUNA per ASTRO thus the TWAP return is 50 ASTRO
+    // while the spot return of 49 ASTRO is within the max deviation
+    let swap_msg = try_build_swap_msg(
+        &QuerierWrapper::new(&deps.querier),
+        &cfg,
+        &native_asset_info("uluna".to_string()),
+        &cfg.astro_token,
+        Uint128::new(100_000000),
+    )
+    .unwrap()
+    .unwrap();
+    let expected_msg = astroport::pair::ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: native_asset_info("uluna".to_string()),
+            amount: Uint128::new(100_000000),
+        },
+        ask_asset_info: Some(cfg.astro_token.clone()),
+        belief_price: Some(Decimal::from_ratio(2u8, 1u8)),
+        max_spread: Some(Decimal::percent(5)),
+        to: None,
+    };
+    match swap_msg.msg {
+        CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => {
+            assert_eq!(msg, to_binary(&expected_msg).unwrap())
+        }
+        _ => panic!("Unexpected swap message"),
+    }
+
+    // Spot return deviates from the TWAP more than allowed
+    let cfg = Config {
+        swap_protection: Some(SwapProtection {
+            max_deviation: Decimal::percent(1),
+            ..swap_protection
+        }),
+        ..cfg
+    };
+    let swap_msg = try_build_swap_msg(
+        &QuerierWrapper::new(&deps.querier),
+        &cfg,
+        &native_asset_info("uluna".to_string()),
+        &cfg.astro_token,
+        Uint128::new(100_000000),
+    )
+    .unwrap();
+    assert_eq!(swap_msg, None);
+}
@@ -1,17 +1,26 @@
 use crate::error::ContractError;
-use crate::state::BRIDGES;
-use astroport::asset::{Asset, AssetInfo, PairInfo};
+use crate::state::{BRIDGES, BRIDGES_EXPIRY};
+use astroport::asset::{addr_opt_validate, pair_info_by_pool, Asset, AssetInfo, PairInfo};
+use astroport::factory::PairType;
 use astroport::maker::{
-    Config, ExecuteMsg, SecondReceiverConfig, SecondReceiverParams, MAX_SECOND_RECEIVER_CUT,
+    AstroBurn, AstroBurnParams, AstroStreaming, AutoBridge, CollectLimit, Config, ExecuteMsg,
+    FeeReceiver, FeeReceiverParams, KeeperBounty, SwapProtection, TwapSource, MAX_FEE_RECEIVERS,
+    MAX_FEE_RECEIVERS_SHARE, MAX_KEEPER_BOUNTY_SHARE,
 };
-use astroport::pair::Cw20HookMsg;
+use astroport::oracle::{ConsultResponse, PriceSource, QueryMsg as OracleQueryMsg};
+use astroport::pair::{
+    Cw20HookMsg, QueryMsg as PairQueryMsg, SimulationResponse, MAX_ALLOWED_SLIPPAGE,
+};
+use astroport::pair_concentrated_inj::{OracleObservation, QueryMsg as ObservationQueryMsg};
 use astroport::querier::query_pair_info;
 
 use cosmwasm_std::{
-    coins, to_binary, wasm_execute, Addr, Binary, CosmosMsg, Decimal, Deps, Env, QuerierWrapper,
-    StdError, StdResult, SubMsg, Uint128, WasmMsg,
+    coins, to_binary, wasm_execute, Addr, Api, Binary, CosmosMsg, Decimal, Deps, Env,
+    QuerierWrapper, StdError, StdResult, Storage, SubMsg, Uint128, Uint64, WasmMsg,
 };
 use cw20::Cw20ExecuteMsg;
+use std::collections::HashSet;
+use std::str::FromStr;
 
 /// The default bridge depth for a fee token
 pub const BRIDGES_INITIAL_DEPTH: u64 = 0;
@@ -19,8 +28,23 @@ pub const BRIDGES_INITIAL_DEPTH: u64 = 0;
 pub const BRIDGES_MAX_DEPTH: u64 = 2;
 /// Swap execution depth limit
 pub const BRIDGES_EXECUTION_MAX_DEPTH: u64 = 3;
+/// The number of pair observations averaged by [`TwapSource::PairObservation`]
+pub const OBSERVATION_SAMPLES: u64 = 4;
+/// The maximum number of tranches of the ASTRO stream
+pub const MAX_STREAM_TRANCHES: usize = 10;
+/// Pair types which support the `Observe` query
+pub const OBSERVATION_PAIR_TYPES: &[&str] = &["concentrated_inj_orderbook"];
+
+/// Returns whether the pool stores price observations which can be used by [`TwapSource::PairObservation`].
+pub fn supports_observations(pool: &PairInfo) -> bool {
+    match &pool.pair_type {
+        PairType::Custom(pair_type) => OBSERVATION_PAIR_TYPES.contains(&pair_type.as_str()),
+        _ => false,
+    }
+}
 
 /// The function checks from<>to pool exists and creates swap message.
+/// Returns None if the swap is rejected by the TWAP protection.
 ///
 /// * **from** asset we want to swap.
 ///
@@ -33,16 +57,302 @@ pub fn try_build_swap_msg(
     from: &AssetInfo,
     to: &AssetInfo,
     amount_in: Uint128,
-) -> Result<SubMsg, ContractError> {
+) -> Result<Option<SubMsg>, ContractError> {
     let pool = get_pool(querier, &cfg.factory_contract, from, to)?;
-    let msg = build_swap_msg(cfg.max_spread, &pool, from, Some(to), amount_in)?;
-    Ok(msg)
+    build_protected_swap_msg(querier, cfg, &pool, from, to, amount_in, None)
+}
+
+/// This function creates swap message which is protected by the TWAP if [`Config::swap_protection`] is set.
+/// Returns None if the TWAP is not available or the swap return deviates from it more than allowed.
+///
+/// * **pool** pool's information.
+///
+/// * **from**  asset we want to swap.
+///
+/// * **to** asset we want to swap to.
+///
+/// * **amount_in** amount of tokens to swap.
+///
+/// * **receiver** the recipient of the swapped tokens. The Maker itself if not set.
+pub fn build_protected_swap_msg(
+    querier: &QuerierWrapper,
+    cfg: &Config,
+    pool: &PairInfo,
+    from: &AssetInfo,
+    to: &AssetInfo,
+    amount_in: Uint128,
+    receiver: Option<&Addr>,
+) -> Result<Option<SubMsg>, ContractError> {
+    let protection = match &cfg.swap_protection {
+        Some(protection) => protection,
+        None => {
+            return build_swap_msg(
+                cfg.max_spread,
+                None,
+                pool,
+                from,
+                Some(to),
+                amount_in,
+                receiver,
+            )
+            .map(Some)
+        }
+    };
+
+    let twap_return =
+        match query_twap_return(querier, &protection.twap_source, pool, from, to, amount_in) {
+            Ok(amount) if !amount.is_zero() => amount,
+            _ => return Ok(None),
+        };
+
+    let spot_return = querier
+        .query_wasm_smart::<SimulationResponse>(
+            &pool.contract_addr,
+            &PairQueryMsg::Simulation {
+                offer_asset: Asset {
+                    info: from.clone(),
+                    amount: amount_in,
+                },
+                ask_asset_info: Some(to.clone()),
+            },
+        )?
+        .return_amount;
+
+    let deviation = if spot_return > twap_return {
+        spot_return - twap_return
+    } else {
+        twap_return - spot_return
+    };
+    if Decimal::from_ratio(deviation, twap_return) > protection.max_deviation {
+        return Ok(None);
+    }
+
+    // The pair fails the swap if it returns less than the TWAP amount minus the max deviation
+    let belief_price = Decimal::from_ratio(amount_in, twap_return);
+    build_swap_msg(
+        protection.max_deviation,
+        Some(belief_price),
+        pool,
+        from,
+        Some(to),
+        amount_in,
+        receiver,
+    )
+    .map(Some)
+}
+
+/// Returns the amount of `to` tokens which `amount_in` of `from` tokens are worth according to the TWAP.
+///
+/// * **twap_source** where the TWAP is taken from.
+///
+/// * **pool** the pool which is used for the swap.
+fn query_twap_return(
+    querier: &QuerierWrapper,
+    twap_source: &TwapSource,
+    pool: &PairInfo,
+    from: &AssetInfo,
+    to: &AssetInfo,
+    amount_in: Uint128,
+) -> StdResult<Uint128> {
+    match twap_source {
+        TwapSource::Oracle { contract, max_age } => {
+            let response: ConsultResponse = querier.query_wasm_smart(
+                contract,
+                &OracleQueryMsg::Consult {
+                    pair: Some(pool.contract_addr.to_string()),
+                    token: from.clone(),
+                    amount: amount_in,
+                    max_age: *max_age,
+                },
+            )?;
+
+            // The spot price fallback of the oracle is exactly what the protection guards against
+            if response.source != PriceSource::Twap {
+                return Err(StdError::generic_err("Oracle TWAP is not available"));
+            }
+
+            let (_, amount) = response
+                .prices
+                .into_iter()
+                .find(|(info, _)| info.equal(to))
+                .ok_or_else(|| StdError::generic_err(format!("Oracle has no price for {to}")))?;
+
+            Ok(amount.try_into()?)
+        }
+        TwapSource::PairObservation { seconds_ago } => {
+            if !supports_observations(pool) {
+                return Err(StdError::generic_err(format!(
+                    "Pair {} doesn't support price observations",
+                    pool.contract_addr
+                )));
+            }
+
+            // A single observation is the price of one trade thus observations are averaged over the window
+            let mut price_sum = Decimal::zero();
+            for sample in 1..=OBSERVATION_SAMPLES {
+                let observation: OracleObservation = querier.query_wasm_smart(
+                    &pool.contract_addr,
+                    &ObservationQueryMsg::Observe {
+                        seconds_ago: seconds_ago * sample / OBSERVATION_SAMPLES,
+                    },
+                )?;
+                price_sum += observation.price;
+            }
+            let observed_price = price_sum / Decimal::from_ratio(OBSERVATION_SAMPLES, 1u8);
+
+            // The observed price is the amount of the first pool asset per one unit of the second one
+            let price = if from.equal(&pool.asset_infos[0]) {
+                observed_price
+                    .inv()
+                    .ok_or_else(|| StdError::generic_err("Observed price is zero"))?
+            } else {
+                observed_price
+            };
+
+            Ok(amount_in * price)
+        }
+    }
+}
+
+/// Validates the TWAP protection parameters of fee swaps.
+/// Pair observations require the default bridge pool with ASTRO to support them.
+pub fn validate_swap_protection(
+    deps: Deps,
+    cfg: &Config,
+    protection: &SwapProtection,
+) -> Result<(), ContractError> {
+    if protection.max_deviation.is_zero()
+        || protection.max_deviation > Decimal::from_str(MAX_ALLOWED_SLIPPAGE)?
+    {
+        return Err(ContractError::IncorrectMaxDeviation {});
+    }
+
+    match &protection.twap_source {
+        TwapSource::Oracle { contract, .. } => {
+            deps.api.addr_validate(contract)?;
+        }
+        TwapSource::PairObservation { seconds_ago } => {
+            if *seconds_ago < OBSERVATION_SAMPLES {
+                return Err(StdError::generic_err(format!(
+                    "Observation window must be at least {OBSERVATION_SAMPLES} seconds"
+                ))
+                .into());
+            }
+
+            if let Some(default_bridge) = &cfg.default_bridge {
+                let pool = get_pool(
+                    &deps.querier,
+                    &cfg.factory_contract,
+                    default_bridge,
+                    &cfg.astro_token,
+                )?;
+                if !supports_observations(&pool) {
+                    return Err(ContractError::ObservationsNotSupported(
+                        pool.contract_addr.to_string(),
+                    ));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Validates the keeper bounty parameters.
+pub fn validate_keeper_bounty(keeper_bounty: &KeeperBounty) -> Result<(), ContractError> {
+    if keeper_bounty.share.is_zero() || keeper_bounty.share > MAX_KEEPER_BOUNTY_SHARE {
+        return Err(ContractError::IncorrectKeeperBountyShare {});
+    }
+
+    Ok(())
+}
+
+/// Validates the automatic bridge discovery parameters.
+pub fn validate_auto_bridge(auto_bridge: &AutoBridge) -> Result<(), ContractError> {
+    if auto_bridge.cache_ttl == 0 {
+        return Err(ContractError::InvalidBridgeCacheTtl {});
+    }
+
+    Ok(())
+}
+
+/// Validates the ASTRO streaming parameters.
+pub fn validate_astro_streaming(astro_streaming: &AstroStreaming) -> Result<(), ContractError> {
+    if astro_streaming.duration == 0 {
+        return Err(ContractError::InvalidStreamingDuration {});
+    }
+
+    Ok(())
+}
+
+/// Validates the ASTRO burn parameters.
+pub fn validate_astro_burn(
+    api: &dyn Api,
+    astro_token: &AssetInfo,
+    params: AstroBurnParams,
+) -> Result<AstroBurn, ContractError> {
+    if params.percent.is_zero() || params.percent > Uint64::new(100) {
+        return Err(ContractError::IncorrectBurnPercent {});
+    }
+
+    let burn_address = addr_opt_validate(api, &params.burn_address)?;
+    if burn_address.is_none() && astro_token.is_native_token() {
+        return Err(ContractError::BurnAddressRequired {});
+    }
+
+    Ok(AstroBurn {
+        percent: params.percent,
+        burn_address,
+    })
+}
+
+/// Builds the message which burns ASTRO. Cw20 ASTRO is burned directly unless a burn address is set.
+pub fn build_burn_msg(
+    astro_token: &AssetInfo,
+    astro_burn: &AstroBurn,
+    amount: Uint128,
+) -> Result<CosmosMsg, ContractError> {
+    match (&astro_burn.burn_address, astro_token) {
+        (Some(burn_address), _) => Ok(Asset {
+            info: astro_token.clone(),
+            amount,
+        }
+        .into_msg(burn_address)?),
+        (None, AssetInfo::Token { contract_addr }) => {
+            Ok(wasm_execute(contract_addr, &Cw20ExecuteMsg::Burn { amount }, vec![])?.into())
+        }
+        (None, AssetInfo::NativeToken { .. }) => Err(ContractError::BurnAddressRequired {}),
+    }
+}
+
+/// Validates the limits applied to collections of a fee token.
+pub fn validate_collect_limit(collect_limit: &CollectLimit) -> Result<(), ContractError> {
+    if let Some(max_pool_share) = collect_limit.max_pool_share {
+        if max_pool_share.is_zero() || max_pool_share > Decimal::one() {
+            return Err(ContractError::InvalidCollectLimit {});
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the amount of ASTRO paid to the keeper out of the `amount` collected from fees.
+/// No bounty is paid if `amount` is less than the minimum amount a collection must distribute.
+pub fn calc_keeper_bounty(cfg: &Config, amount: Uint128) -> Uint128 {
+    match &cfg.keeper_bounty {
+        Some(keeper_bounty) if amount >= keeper_bounty.min_astro_amount => {
+            std::cmp::min(amount * keeper_bounty.share, keeper_bounty.max_bounty)
+        }
+        _ => Uint128::zero(),
+    }
 }
 
 /// This function creates swap message.
 ///
 /// * **max_spread** max allowed spread.
 ///
+/// * **belief_price** the price which the swap return is checked against.
+///
 /// * **pool** pool's information.
 ///
 /// * **from**  asset we want to swap.
@@ -50,12 +360,16 @@ pub fn try_build_swap_msg(
 /// * **to** asset we want to swap to.
 ///
 /// * **amount_in** amount of tokens to swap.
+///
+/// * **receiver** the recipient of the swapped tokens. The Maker itself if not set.
 pub fn build_swap_msg(
     max_spread: Decimal,
+    belief_price: Option<Decimal>,
     pool: &PairInfo,
     from: &AssetInfo,
     to: Option<&AssetInfo>,
     amount_in: Uint128,
+    receiver: Option<&Addr>,
 ) -> Result<SubMsg, ContractError> {
     if from.is_native_token() {
         let offer_asset = Asset {
@@ -68,9 +382,9 @@ pub fn build_swap_msg(
             msg: to_binary(&astroport::pair::ExecuteMsg::Swap {
                 offer_asset: offer_asset.clone(),
                 ask_asset_info: to.cloned(),
-                belief_price: None,
+                belief_price,
                 max_spread: Some(max_spread),
-                to: None,
+                to: receiver.map(|addr| addr.to_string()),
             })?,
             funds: vec![offer_asset.as_coin()?],
         }))
@@ -82,9 +396,9 @@ pub fn build_swap_msg(
                 amount: amount_in,
                 msg: to_binary(&Cw20HookMsg::Swap {
                     ask_asset_info: to.cloned(),
-                    belief_price: None,
+                    belief_price,
                     max_spread: Some(max_spread),
-                    to: None,
+                    to: receiver.map(|addr| addr.to_string()),
                 })?,
             })?,
             funds: vec![],
@@ -124,6 +438,43 @@ pub fn build_distribute_msg(
     Ok(msg)
 }
 
+/// Returns the pair of an Astroport LP token or None if the asset is not an LP token
+/// of a pair registered in the factory.
+pub fn query_lp_pair(
+    querier: &QuerierWrapper,
+    factory_contract: &Addr,
+    asset: &AssetInfo,
+) -> Option<PairInfo> {
+    match asset {
+        AssetInfo::Token { contract_addr } => {
+            let pair = pair_info_by_pool(querier, contract_addr).ok()?;
+            let registered = query_pair_info(querier, factory_contract, &pair.asset_infos).ok()?;
+
+            if pair.liquidity_token == *contract_addr
+                && registered.contract_addr == pair.contract_addr
+            {
+                Some(pair)
+            } else {
+                None
+            }
+        }
+        AssetInfo::NativeToken { .. } => None,
+    }
+}
+
+/// Builds the message which withdraws liquidity by sending LP tokens back to their pair.
+pub fn build_withdraw_liquidity_msg(pair: &PairInfo, amount: Uint128) -> StdResult<SubMsg> {
+    Ok(SubMsg::new(WasmMsg::Execute {
+        contract_addr: pair.liquidity_token.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Send {
+            contract: pair.contract_addr.to_string(),
+            amount,
+            msg: to_binary(&Cw20HookMsg::WithdrawLiquidity { assets: vec![] })?,
+        })?,
+        funds: vec![],
+    }))
+}
+
 /// This function checks that there is a direct pool to swap to $ASTRO.
 /// Otherwise it looks for an intermediate token to swap to $ASTRO.
 ///
@@ -137,7 +488,7 @@ pub fn build_distribute_msg(
 ///
 /// * **depth** current recursion depth of the validation.
 ///
-/// * **amount** is an amount of from_token.
+/// * **now** current block time in seconds which expired bridges are checked against.
 pub fn validate_bridge(
     deps: Deps,
     factory_contract: &Addr,
@@ -145,6 +496,7 @@ pub fn validate_bridge(
     bridge_token: &AssetInfo,
     astro_token: &AssetInfo,
     depth: u64,
+    now: u64,
 ) -> Result<PairInfo, ContractError> {
     // Check if the bridge pool exists
     let bridge_pool = get_pool(&deps.querier, factory_contract, from_token, bridge_token)?;
@@ -157,9 +509,8 @@ pub fn validate_bridge(
         }
 
         // Check if next level of bridge exists
-        let next_bridge_token = BRIDGES
-            .load(deps.storage, bridge_token.to_string())
-            .map_err(|_| ContractError::InvalidBridgeDestination(from_token.to_string()))?;
+        let next_bridge_token = load_bridge(deps.storage, bridge_token, now)?
+            .ok_or_else(|| ContractError::InvalidBridgeDestination(from_token.to_string()))?;
 
         validate_bridge(
             deps,
@@ -168,12 +519,30 @@ pub fn validate_bridge(
             &next_bridge_token,
             astro_token,
             depth + 1,
+            now,
         )?;
     }
 
     Ok(bridge_pool)
 }
 
+/// Returns the bridge token set for an asset. Discovered bridges which have expired are ignored.
+///
+/// * **now** current block time in seconds.
+pub fn load_bridge(
+    storage: &dyn Storage,
+    asset: &AssetInfo,
+    now: u64,
+) -> StdResult<Option<AssetInfo>> {
+    if let Some(expires_at) = BRIDGES_EXPIRY.may_load(storage, asset.to_string())? {
+        if expires_at <= now {
+            return Ok(None);
+        }
+    }
+
+    BRIDGES.may_load(storage, asset.to_string())
+}
+
 /// This function checks that there is a pool to swap between `from` and `to`. In case of success
 /// returns [`PairInfo`] of selected pool.
 ///
@@ -225,29 +594,95 @@ pub fn build_send_msg(
     }
 }
 
-/// Updates the parameters that describe the second receiver of fees
-pub fn update_second_receiver_cfg(
+/// Validates additional fee receivers. Every payout asset other than ASTRO
+/// must have a direct pool with ASTRO.
+pub fn validate_fee_receivers(
     deps: Deps,
-    cfg: &mut Config,
-    params: &Option<SecondReceiverParams>,
-) -> StdResult<()> {
-    if let Some(params) = params {
-        if params.second_receiver_cut > MAX_SECOND_RECEIVER_CUT
-            || params.second_receiver_cut.is_zero()
-        {
-            return Err(StdError::generic_err(format!(
-                "Incorrect second receiver percent of its share. Should be in range: 0 < {} <= {}",
-                params.second_receiver_cut, MAX_SECOND_RECEIVER_CUT
-            )));
-        };
+    cfg: &Config,
+    params: Vec<FeeReceiverParams>,
+) -> Result<Vec<FeeReceiver>, ContractError> {
+    if params.len() > MAX_FEE_RECEIVERS {
+        return Err(ContractError::TooManyFeeReceivers(MAX_FEE_RECEIVERS));
+    }
+
+    let mut total_share = Uint64::zero();
+    let mut uniq = HashSet::new();
+    params
+        .into_iter()
+        .map(|params| {
+            let receiver = deps.api.addr_validate(&params.receiver)?;
+            if !uniq.insert(receiver.clone()) {
+                return Err(ContractError::DuplicatedFeeReceiver(params.receiver));
+            }
+
+            total_share = total_share.checked_add(params.share)?;
+            if params.share.is_zero() || total_share > MAX_FEE_RECEIVERS_SHARE {
+                return Err(ContractError::IncorrectFeeReceiversShare(
+                    MAX_FEE_RECEIVERS_SHARE,
+                ));
+            }
+
+            if let Some(payout_asset) = &params.payout_asset {
+                payout_asset.check(deps.api)?;
+                if !payout_asset.equal(&cfg.astro_token) {
+                    get_pool(
+                        &deps.querier,
+                        &cfg.factory_contract,
+                        &cfg.astro_token,
+                        payout_asset,
+                    )?;
+                }
+            }
+
+            Ok(FeeReceiver {
+                receiver,
+                share: params.share,
+                payout_asset: params.payout_asset,
+            })
+        })
+        .collect()
+}
 
-        cfg.second_receiver_cfg = Some(SecondReceiverConfig {
-            second_fee_receiver: deps
-                .api
-                .addr_validate(params.second_fee_receiver.as_str())?,
-            second_receiver_cut: params.second_receiver_cut,
-        });
+/// Builds the message which pays ASTRO to an additional fee receiver.
+/// If the receiver is paid in another asset, ASTRO is swapped to it and sent to the receiver directly.
+/// The receiver gets ASTRO if the swap cannot be built or is rejected by the TWAP protection.
+///
+/// * **amount** the amount of ASTRO which belongs to the receiver.
+pub fn build_fee_receiver_msg(
+    querier: &QuerierWrapper,
+    cfg: &Config,
+    fee_receiver: &FeeReceiver,
+    amount: Uint128,
+) -> StdResult<SubMsg> {
+    if let Some(payout_asset) = &fee_receiver.payout_asset {
+        if !payout_asset.equal(&cfg.astro_token) {
+            let swap_msg = get_pool(
+                querier,
+                &cfg.factory_contract,
+                &cfg.astro_token,
+                payout_asset,
+            )
+            .and_then(|pool| {
+                build_protected_swap_msg(
+                    querier,
+                    cfg,
+                    &pool,
+                    &cfg.astro_token,
+                    payout_asset,
+                    amount,
+                    Some(&fee_receiver.receiver),
+                )
+            });
+            if let Ok(Some(msg)) = swap_msg {
+                return Ok(msg);
+            }
+        }
     }
 
-    Ok(())
+    let asset = Asset {
+        info: cfg.astro_token.clone(),
+        amount,
+    };
+
+    Ok(SubMsg::new(asset.into_msg(&fee_receiver.receiver)?))
 }
@@ -3,13 +3,16 @@ use astroport::asset::{
 };
 use astroport::factory::{PairConfig, PairType, UpdateAddr};
 use astroport::maker::{
-    AssetWithLimit, BalancesResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg,
-    SecondReceiverConfig, SecondReceiverParams,
+    AssetWithLimit, AstroBurn, AstroBurnParams, AstroStream, AstroStreaming, AstroTranche,
+    AutoBridge, BalancesResponse, CollectLimit, ConfigResponse, DistributionRecord, ExecuteMsg,
+    FeeReceiver, FeeReceiverParams, InstantiateMsg, KeeperBounty, QueryMsg,
+    SimulateCollectResponse, SkipReason, SwapProtection, TwapSource, UpdateAstroBurn,
+    UpdateAstroStreaming, UpdateAutoBridge, UpdateKeeperBounty, UpdateSwapProtection,
 };
 use astroport::token::InstantiateMsg as TokenInstantiateMsg;
 use astroport_governance::utils::EPOCH_START;
 use cosmwasm_std::{
-    attr, coin, to_binary, Addr, Coin, Decimal, QueryRequest, Uint128, Uint64, WasmQuery,
+    attr, coin, to_binary, Addr, Coin, Decimal, Event, QueryRequest, Uint128, Uint64, WasmQuery,
 };
 use cw20::{BalanceResponse, Cw20QueryMsg, MinterResponse};
 use cw_multi_test::{next_block, App, ContractWrapper, Executor};
@@ -90,7 +93,7 @@ fn instantiate_contracts(
     governance_percent: Uint64,
     max_spread: Option<Decimal>,
     pair_type: Option<PairType>,
-    second_receiver_params: Option<SecondReceiverParams>,
+    fee_receivers: Option<Vec<FeeReceiverParams>>,
 ) -> (Addr, Addr, Addr, Addr) {
     let astro_token_contract = Box::new(ContractWrapper::new_with_empty(
         astroport_token::contract::execute,
@@ -235,7 +238,12 @@ fn instantiate_contracts(
         astro_token: token_asset_info(astro_token_instance.clone()),
         default_bridge: Some(native_asset_info("uluna".to_string())),
         max_spread,
-        second_receiver_params,
+        fee_receivers,
+        swap_protection: None,
+        keeper_bounty: None,
+        auto_bridge: None,
+        astro_streaming: None,
+        astro_burn: None,
     };
     let maker_instance = router
         .instantiate_contract(
@@ -505,7 +513,12 @@ fn update_config() {
         factory_contract: Some(new_factory.to_string()),
         basic_asset: None,
         max_spread: Some(new_max_spread),
-        second_receiver_params: None,
+        fee_receivers: None,
+        swap_protection: None,
+        keeper_bounty: None,
+        auto_bridge: None,
+        astro_streaming: None,
+        astro_burn: None,
     };
 
     // Assert cannot update with improper owner
@@ -543,16 +556,93 @@ fn update_config() {
         factory_contract: None,
         basic_asset: None,
         max_spread: None,
-        second_receiver_params: Some(SecondReceiverParams {
-            second_fee_receiver: "second_fee_receiver".to_string(),
-            second_receiver_cut: Default::default(),
-        }),
+        fee_receivers: Some(vec![FeeReceiverParams {
+            receiver: "treasury".to_string(),
+            share: Uint64::zero(),
+            payout_asset: None,
+        }]),
+        swap_protection: None,
+        keeper_bounty: None,
+        auto_bridge: None,
+        astro_streaming: None,
+        astro_burn: None,
+    };
+
+    let err = router
+        .execute_contract(owner.clone(), maker_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        "Incorrect fee receivers share. Each share should be positive and their total should not exceed 50",
+        err.root_cause().to_string()
+    );
+
+    let msg = ExecuteMsg::UpdateConfig {
+        governance_percent: None,
+        governance_contract: None,
+        staking_contract: None,
+        factory_contract: None,
+        basic_asset: None,
+        max_spread: None,
+        fee_receivers: Some(vec![
+            FeeReceiverParams {
+                receiver: "treasury".to_string(),
+                share: Uint64::new(30),
+                payout_asset: None,
+            },
+            FeeReceiverParams {
+                receiver: "insurance_fund".to_string(),
+                share: Uint64::new(21),
+                payout_asset: None,
+            },
+        ]),
+        swap_protection: None,
+        keeper_bounty: None,
+        auto_bridge: None,
+        astro_streaming: None,
+        astro_burn: None,
+    };
+
+    let err = router
+        .execute_contract(owner.clone(), maker_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        "Incorrect fee receivers share. Each share should be positive and their total should not exceed 50",
+        err.root_cause().to_string()
+    );
+
+    let msg = ExecuteMsg::UpdateConfig {
+        governance_percent: None,
+        governance_contract: None,
+        staking_contract: None,
+        factory_contract: None,
+        basic_asset: None,
+        max_spread: None,
+        fee_receivers: Some(vec![
+            FeeReceiverParams {
+                receiver: "treasury".to_string(),
+                share: Uint64::new(10),
+                payout_asset: None,
+            },
+            FeeReceiverParams {
+                receiver: "treasury".to_string(),
+                share: Uint64::new(10),
+                payout_asset: None,
+            },
+        ]),
+        swap_protection: None,
+        keeper_bounty: None,
+        auto_bridge: None,
+        astro_streaming: None,
+        astro_burn: None,
     };
 
     let err = router
         .execute_contract(owner.clone(), maker_instance.clone(), &msg, &[])
         .unwrap_err();
-    assert_eq!("Generic error: Incorrect second receiver percent of its share. Should be in range: 0 < 0 <= 50", err.root_cause().to_string());
+    assert_eq!(
+        "Duplicated fee receiver treasury",
+        err.root_cause().to_string()
+    );
 
     let msg = ExecuteMsg::UpdateConfig {
         governance_percent: None,
@@ -561,10 +651,16 @@ fn update_config() {
         factory_contract: None,
         basic_asset: None,
         max_spread: None,
-        second_receiver_params: Some(SecondReceiverParams {
-            second_fee_receiver: "second_fee_receiver".to_string(),
-            second_receiver_cut: Uint64::new(10),
-        }),
+        fee_receivers: Some(vec![FeeReceiverParams {
+            receiver: "treasury".to_string(),
+            share: Uint64::new(10),
+            payout_asset: None,
+        }]),
+        swap_protection: None,
+        keeper_bounty: None,
+        auto_bridge: None,
+        astro_streaming: None,
+        astro_burn: None,
     };
 
     router
@@ -578,11 +674,12 @@ fn update_config() {
         .unwrap();
     assert_eq!(res.governance_contract, None);
     assert_eq!(
-        res.second_receiver_cfg,
-        Some(SecondReceiverConfig {
-            second_fee_receiver: Addr::unchecked("second_fee_receiver"),
-            second_receiver_cut: Uint64::new(10)
-        })
+        res.fee_receivers,
+        vec![FeeReceiver {
+            receiver: Addr::unchecked("treasury"),
+            share: Uint64::new(10),
+            payout_asset: None,
+        }]
     );
 }
 
@@ -1606,7 +1703,7 @@ fn collect_with_asset_limit() {
 }
 
 #[test]
-fn collect_with_second_receiver() {
+fn collect_with_fee_receivers() {
     let uusd_asset = String::from("uusd");
     let uluna_asset = String::from("uluna");
     let owner = Addr::unchecked("owner");
@@ -1636,10 +1733,18 @@ fn collect_with_second_receiver() {
             governance_percent,
             Some(max_spread),
             None,
-            Some(SecondReceiverParams {
-                second_fee_receiver: "second_receiver".to_string(),
-                second_receiver_cut: Uint64::new(50),
-            }),
+            Some(vec![
+                FeeReceiverParams {
+                    receiver: "treasury".to_string(),
+                    share: Uint64::new(30),
+                    payout_asset: None,
+                },
+                FeeReceiverParams {
+                    receiver: "partner".to_string(),
+                    share: Uint64::new(20),
+                    payout_asset: Some(native_asset_info(uusd_asset.clone())),
+                },
+            ]),
         );
 
     let usdc_token_instance = instantiate_token(
@@ -1836,21 +1941,33 @@ fn collect_with_second_receiver() {
 
     // Check balances
     let amount = Uint128::new(40u128);
-    let second_receiver_amount = amount.multiply_ratio(Uint128::from(50u64), Uint128::new(100));
+    let treasury_amount = amount.multiply_ratio(Uint128::from(30u64), Uint128::new(100));
+    let partner_amount = amount.multiply_ratio(Uint128::from(20u64), Uint128::new(100));
     let governance_amount = amount
-        .checked_sub(second_receiver_amount)
+        .checked_sub(treasury_amount + partner_amount)
         .unwrap()
         .multiply_ratio(Uint128::from(governance_percent), Uint128::new(100));
-    let staking_amount = amount - governance_amount - second_receiver_amount;
+    let staking_amount = amount - governance_amount - treasury_amount - partner_amount;
 
-    // Check the second receiver contract's balance for the ASTRO token
+    // Check the treasury's balance for the ASTRO token
     check_balance(
         &mut router,
-        Addr::unchecked("second_receiver"),
+        Addr::unchecked("treasury"),
         astro_token_instance.clone(),
-        second_receiver_amount,
+        treasury_amount,
     );
 
+    // The partner's share is swapped to UUSD in the ASTRO/UUSD pool
+    check_balance(
+        &mut router,
+        Addr::unchecked("partner"),
+        astro_token_instance.clone(),
+        Uint128::zero(),
+    );
+    let partner_uusd = router.wrap().query_balance("partner", "uusd").unwrap();
+    assert_eq!(partner_amount, Uint128::new(8));
+    assert_eq!(partner_uusd.amount, Uint128::new(7));
+
     // Check the governance contract's balance for the ASTRO token
     check_balance(
         &mut router,
@@ -2572,3 +2689,2530 @@ fn collect_3pools() {
     assert_eq!(balances[0].amount.u128(), 100_000);
     assert_eq!(balances[1].amount.u128(), 100_000);
 }
+
+#[test]
+fn collect_with_twap_protection() {
+    let owner = Addr::unchecked("owner");
+    let mut router = mock_app(owner.clone(), vec![]);
+    let staking = Addr::unchecked("staking");
+    let user = Addr::unchecked("user0000");
+    let attacker = Addr::unchecked("attacker");
+
+    let (astro_token_instance, factory_instance, maker_instance, _) = instantiate_contracts(
+        &mut router,
+        owner.clone(),
+        staking.clone(),
+        Uint64::new(0),
+        None,
+        None,
+        None,
+    );
+
+    let usdc_token_instance = instantiate_token(
+        &mut router,
+        owner.clone(),
+        "Usdc token".to_string(),
+        "USDC".to_string(),
+    );
+
+    let pair_info = create_pair(
+        &mut router,
+        owner.clone(),
+        user,
+        &factory_instance,
+        vec![
+            token_asset(usdc_token_instance.clone(), Uint128::from(1_000_000_u128)),
+            token_asset(astro_token_instance.clone(), Uint128::from(1_000_000_u128)),
+        ],
+        None,
+    );
+
+    let oracle_code_id = router.store_code(Box::new(ContractWrapper::new_with_empty(
+        astroport_oracle::contract::execute,
+        astroport_oracle::contract::instantiate,
+        astroport_oracle::contract::query,
+    )));
+    let oracle_instance = router
+        .instantiate_contract(
+            oracle_code_id,
+            owner.clone(),
+            &astroport::oracle::InstantiateMsg {
+                factory_contract: factory_instance.to_string(),
+                asset_infos: pair_info.asset_infos.clone(),
+                period: None,
+                max_snapshots: None,
+            },
+            &[],
+            String::from("ORACLE"),
+            None,
+        )
+        .unwrap();
+
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::EnableRewards { blocks: 1 },
+            &[],
+        )
+        .unwrap();
+
+    let mut update_config_msg = ExecuteMsg::UpdateConfig {
+        factory_contract: None,
+        staking_contract: None,
+        governance_contract: None,
+        governance_percent: None,
+        basic_asset: None,
+        max_spread: None,
+        fee_receivers: None,
+        swap_protection: Some(UpdateSwapProtection::Set(SwapProtection {
+            twap_source: TwapSource::Oracle {
+                contract: oracle_instance.to_string(),
+                max_age: None,
+            },
+            max_deviation: Decimal::zero(),
+        })),
+        keeper_bounty: None,
+        auto_bridge: None,
+        astro_streaming: None,
+        astro_burn: None,
+    };
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &update_config_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Incorrect max deviation from the TWAP"
+    );
+
+    if let ExecuteMsg::UpdateConfig {
+        swap_protection: Some(UpdateSwapProtection::Set(protection)),
+        ..
+    } = &mut update_config_msg
+    {
+        protection.max_deviation = Decimal::percent(1);
+    }
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &update_config_msg,
+            &[],
+        )
+        .unwrap();
+
+    mint_some_token(
+        &mut router,
+        owner.clone(),
+        usdc_token_instance.clone(),
+        maker_instance.clone(),
+        Uint128::new(1000),
+    );
+    let collect_msg = ExecuteMsg::Collect {
+        assets: vec![AssetWithLimit {
+            info: token_asset_info(usdc_token_instance.clone()),
+            limit: None,
+        }],
+    };
+
+    // The oracle has no TWAP yet thus fees are not swapped
+    let res = router
+        .execute_contract(
+            Addr::unchecked("anyone"),
+            maker_instance.clone(),
+            &collect_msg,
+            &[],
+        )
+        .unwrap();
+    assert!(res.has_event(
+        &Event::new("wasm").add_attribute("skipped_asset", usdc_token_instance.to_string())
+    ));
+    check_balance(
+        &mut router,
+        maker_instance.clone(),
+        usdc_token_instance.clone(),
+        Uint128::new(1000),
+    );
+
+    router.update_block(|block| {
+        block.time = block.time.plus_seconds(86400);
+        block.height += 17280;
+    });
+    router
+        .execute_contract(
+            owner.clone(),
+            oracle_instance.clone(),
+            &astroport::oracle::ExecuteMsg::Update { pair: None },
+            &[],
+        )
+        .unwrap();
+
+    // The attacker moves the pool price right before the collection
+    let swap = |router: &mut App, token: &Addr, amount: Uint128| {
+        router
+            .execute_contract(
+                attacker.clone(),
+                token.clone(),
+                &cw20::Cw20ExecuteMsg::Send {
+                    contract: pair_info.contract_addr.to_string(),
+                    amount,
+                    msg: to_binary(&astroport::pair::Cw20HookMsg::Swap {
+                        ask_asset_info: None,
+                        belief_price: None,
+                        max_spread: Some(Decimal::percent(50)),
+                        to: None,
+                    })
+                    .unwrap(),
+                },
+                &[],
+            )
+            .unwrap();
+    };
+    mint_some_token(
+        &mut router,
+        owner.clone(),
+        usdc_token_instance.clone(),
+        attacker.clone(),
+        Uint128::new(100_000),
+    );
+    swap(&mut router, &usdc_token_instance, Uint128::new(100_000));
+
+    let res = router
+        .execute_contract(
+            Addr::unchecked("anyone"),
+            maker_instance.clone(),
+            &collect_msg,
+            &[],
+        )
+        .unwrap();
+    assert!(res.has_event(
+        &Event::new("wasm").add_attribute("skipped_asset", usdc_token_instance.to_string())
+    ));
+    check_balance(
+        &mut router,
+        maker_instance.clone(),
+        usdc_token_instance.clone(),
+        Uint128::new(1000),
+    );
+
+    // Once the price is back the fees are swapped
+    let attacker_astro: BalanceResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &astro_token_instance,
+            &Cw20QueryMsg::Balance {
+                address: attacker.to_string(),
+            },
+        )
+        .unwrap();
+    swap(&mut router, &astro_token_instance, attacker_astro.balance);
+
+    router
+        .execute_contract(
+            Addr::unchecked("anyone"),
+            maker_instance.clone(),
+            &collect_msg,
+            &[],
+        )
+        .unwrap();
+    check_balance(
+        &mut router,
+        maker_instance.clone(),
+        usdc_token_instance,
+        Uint128::zero(),
+    );
+    check_balance(
+        &mut router,
+        staking,
+        astro_token_instance,
+        Uint128::new(999),
+    );
+}
+
+#[test]
+fn simulate_collect() {
+    let owner = Addr::unchecked("owner");
+    let mut router = mock_app(owner.clone(), vec![]);
+    let staking = Addr::unchecked("staking");
+    let user = Addr::unchecked("user0000");
+    let governance_percent = Uint64::new(10);
+
+    let (astro_token_instance, factory_instance, maker_instance, governance_instance) =
+        instantiate_contracts(
+            &mut router,
+            owner.clone(),
+            staking.clone(),
+            governance_percent,
+            Some(Decimal::percent(50)),
+            None,
+            Some(vec![FeeReceiverParams {
+                receiver: "treasury".to_string(),
+                share: Uint64::new(20),
+                payout_asset: None,
+            }]),
+        );
+
+    let usdc_token_instance = instantiate_token(
+        &mut router,
+        owner.clone(),
+        "Usdc token".to_string(),
+        "USDC".to_string(),
+    );
+    let test_token_instance = instantiate_token(
+        &mut router,
+        owner.clone(),
+        "Test token".to_string(),
+        "TEST".to_string(),
+    );
+    let foo_token_instance = instantiate_token(
+        &mut router,
+        owner.clone(),
+        "Foo token".to_string(),
+        "FOO".to_string(),
+    );
+
+    for assets in [
+        vec![
+            token_asset(usdc_token_instance.clone(), Uint128::from(100_000_u128)),
+            token_asset(test_token_instance.clone(), Uint128::from(100_000_u128)),
+        ],
+        vec![
+            token_asset(test_token_instance.clone(), Uint128::from(100_000_u128)),
+            token_asset(astro_token_instance.clone(), Uint128::from(100_000_u128)),
+        ],
+        vec![
+            token_asset(foo_token_instance.clone(), Uint128::from(100_000_u128)),
+            token_asset(astro_token_instance.clone(), Uint128::from(100_000_u128)),
+        ],
+    ] {
+        create_pair(
+            &mut router,
+            owner.clone(),
+            user.clone(),
+            &factory_instance,
+            assets,
+            None,
+        );
+    }
+
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::UpdateBridges {
+                add: Some(vec![(
+                    token_asset_info(usdc_token_instance.clone()),
+                    token_asset_info(test_token_instance.clone()),
+                )]),
+                remove: None,
+            },
+            &[],
+        )
+        .unwrap();
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::EnableRewards { blocks: 1 },
+            &[],
+        )
+        .unwrap();
+
+    for (token, amount) in [
+        (astro_token_instance.clone(), 10u128),
+        (usdc_token_instance.clone(), 1000u128),
+        (foo_token_instance.clone(), 500u128),
+    ] {
+        mint_some_token(
+            &mut router,
+            owner.clone(),
+            token,
+            maker_instance.clone(),
+            Uint128::from(amount),
+        );
+    }
+
+    let usdc = AssetWithLimit {
+        info: token_asset_info(usdc_token_instance.clone()),
+        limit: None,
+    };
+    let foo = AssetWithLimit {
+        info: token_asset_info(foo_token_instance.clone()),
+        limit: Some(Uint128::new(100)),
+    };
+
+    let err = router
+        .wrap()
+        .query_wasm_smart::<SimulateCollectResponse>(
+            &maker_instance,
+            &QueryMsg::SimulateCollect {
+                assets: vec![usdc.clone(), usdc.clone()],
+            },
+        )
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Cannot collect. Remove duplicate asset"));
+
+    let res: SimulateCollectResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &maker_instance,
+            &QueryMsg::SimulateCollect {
+                assets: vec![usdc.clone(), foo],
+            },
+        )
+        .unwrap();
+    assert_eq!(res.swaps.len(), 2);
+    assert_eq!(
+        res.swaps[0].route,
+        vec![
+            token_asset_info(usdc_token_instance.clone()),
+            token_asset_info(test_token_instance.clone()),
+            token_asset_info(astro_token_instance.clone()),
+        ]
+    );
+    assert_eq!(
+        res.swaps[1].route,
+        vec![
+            token_asset_info(foo_token_instance),
+            token_asset_info(astro_token_instance.clone()),
+        ]
+    );
+    assert_eq!(res.swaps[1].offer_asset.amount, Uint128::new(100));
+    assert!(!res.swaps[0].price_impact.is_zero());
+    assert!(res.swaps[0].price_impact > res.swaps[1].price_impact);
+
+    // A single asset is simulated exactly as it is collected
+    let res: SimulateCollectResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &maker_instance,
+            &QueryMsg::SimulateCollect {
+                assets: vec![usdc.clone()],
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res.astro_amount,
+        Uint128::new(10) + res.swaps[0].astro_amount
+    );
+    assert!(!res.swaps[0].skipped);
+
+    router
+        .execute_contract(
+            Addr::unchecked("anyone"),
+            maker_instance.clone(),
+            &ExecuteMsg::Collect { assets: vec![usdc] },
+            &[],
+        )
+        .unwrap();
+
+    check_balance(
+        &mut router,
+        maker_instance,
+        astro_token_instance.clone(),
+        Uint128::zero(),
+    );
+    check_balance(
+        &mut router,
+        Addr::unchecked("treasury"),
+        astro_token_instance.clone(),
+        res.fee_receivers[0].1,
+    );
+    check_balance(
+        &mut router,
+        governance_instance,
+        astro_token_instance.clone(),
+        res.governance_amount,
+    );
+    check_balance(
+        &mut router,
+        staking,
+        astro_token_instance,
+        res.staking_amount,
+    );
+    assert_eq!(res.fee_receivers[0].0, Addr::unchecked("treasury"));
+    assert_eq!(
+        res.fee_receivers[0].1 + res.governance_amount + res.staking_amount,
+        res.astro_amount
+    );
+}
+
+#[test]
+fn collect_with_keeper_bounty() {
+    let owner = Addr::unchecked("owner");
+    let mut router = mock_app(owner.clone(), vec![]);
+    let staking = Addr::unchecked("staking");
+    let user = Addr::unchecked("user0000");
+    let keeper = Addr::unchecked("keeper");
+
+    let (astro_token_instance, factory_instance, maker_instance, _) = instantiate_contracts(
+        &mut router,
+        owner.clone(),
+        staking.clone(),
+        Uint64::new(0),
+        None,
+        None,
+        None,
+    );
+
+    let usdc_token_instance = instantiate_token(
+        &mut router,
+        owner.clone(),
+        "Usdc token".to_string(),
+        "USDC".to_string(),
+    );
+
+    create_pair(
+        &mut router,
+        owner.clone(),
+        user,
+        &factory_instance,
+        vec![
+            token_asset(usdc_token_instance.clone(), Uint128::from(100_000_u128)),
+            token_asset(astro_token_instance.clone(), Uint128::from(100_000_u128)),
+        ],
+        None,
+    );
+
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::EnableRewards { blocks: 1 },
+            &[],
+        )
+        .unwrap();
+
+    let mut keeper_bounty = KeeperBounty {
+        share: Decimal::zero(),
+        max_bounty: Uint128::new(10),
+        min_astro_amount: Uint128::new(50),
+    };
+    let update_config_msg = |keeper_bounty: KeeperBounty| ExecuteMsg::UpdateConfig {
+        factory_contract: None,
+        staking_contract: None,
+        governance_contract: None,
+        governance_percent: None,
+        basic_asset: None,
+        max_spread: None,
+        fee_receivers: None,
+        swap_protection: None,
+        keeper_bounty: Some(UpdateKeeperBounty::Set(keeper_bounty)),
+        auto_bridge: None,
+        astro_streaming: None,
+        astro_burn: None,
+    };
+
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &update_config_msg(keeper_bounty.clone()),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Incorrect keeper bounty share"
+    );
+
+    keeper_bounty.share = Decimal::percent(5);
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &update_config_msg(keeper_bounty.clone()),
+            &[],
+        )
+        .unwrap();
+
+    let res: ConfigResponse = router
+        .wrap()
+        .query_wasm_smart(&maker_instance, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(res.keeper_bounty, Some(keeper_bounty));
+
+    let collect_msg = ExecuteMsg::Collect {
+        assets: vec![AssetWithLimit {
+            info: token_asset_info(usdc_token_instance.clone()),
+            limit: None,
+        }],
+    };
+
+    // Collections which produce too little ASTRO pay no bounty
+    mint_some_token(
+        &mut router,
+        owner.clone(),
+        usdc_token_instance.clone(),
+        maker_instance.clone(),
+        Uint128::new(20),
+    );
+    let res: SimulateCollectResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &maker_instance,
+            &QueryMsg::SimulateCollect {
+                assets: vec![AssetWithLimit {
+                    info: token_asset_info(usdc_token_instance.clone()),
+                    limit: None,
+                }],
+            },
+        )
+        .unwrap();
+    assert_eq!(res.astro_amount, Uint128::new(19));
+    assert!(res.below_min_astro_amount);
+    assert_eq!(res.keeper_bounty, Uint128::zero());
+
+    router
+        .execute_contract(keeper.clone(), maker_instance.clone(), &collect_msg, &[])
+        .unwrap();
+
+    check_balance(
+        &mut router,
+        keeper.clone(),
+        astro_token_instance.clone(),
+        Uint128::zero(),
+    );
+    check_balance(
+        &mut router,
+        staking.clone(),
+        astro_token_instance.clone(),
+        Uint128::new(19),
+    );
+
+    mint_some_token(
+        &mut router,
+        owner.clone(),
+        usdc_token_instance.clone(),
+        maker_instance.clone(),
+        Uint128::new(980),
+    );
+
+    let res: SimulateCollectResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &maker_instance,
+            &QueryMsg::SimulateCollect {
+                assets: vec![AssetWithLimit {
+                    info: token_asset_info(usdc_token_instance.clone()),
+                    limit: None,
+                }],
+            },
+        )
+        .unwrap();
+    assert_eq!(res.astro_amount, Uint128::new(970));
+    assert!(!res.below_min_astro_amount);
+    // 5% of the distributed ASTRO is capped by the max bounty
+    assert_eq!(res.keeper_bounty, Uint128::new(10));
+    assert_eq!(res.staking_amount, Uint128::new(960));
+
+    router
+        .execute_contract(keeper.clone(), maker_instance.clone(), &collect_msg, &[])
+        .unwrap();
+
+    check_balance(
+        &mut router,
+        keeper,
+        astro_token_instance.clone(),
+        Uint128::new(10),
+    );
+    check_balance(
+        &mut router,
+        staking,
+        astro_token_instance.clone(),
+        Uint128::new(979),
+    );
+    check_balance(
+        &mut router,
+        maker_instance,
+        astro_token_instance,
+        Uint128::zero(),
+    );
+}
+
+#[test]
+fn keeper_bounty_excludes_preupgrade_astro() {
+    let owner = Addr::unchecked("owner");
+    let mut router = mock_app(owner.clone(), vec![]);
+    let staking = Addr::unchecked("staking");
+    let user = Addr::unchecked("user0000");
+    let keeper = Addr::unchecked("keeper");
+
+    let (astro_token_instance, factory_instance, maker_instance, _) = instantiate_contracts(
+        &mut router,
+        owner.clone(),
+        staking.clone(),
+        Uint64::new(0),
+        None,
+        None,
+        None,
+    );
+
+    let usdc_token_instance = instantiate_token(
+        &mut router,
+        owner.clone(),
+        "Usdc token".to_string(),
+        "USDC".to_string(),
+    );
+
+    create_pair(
+        &mut router,
+        owner.clone(),
+        user,
+        &factory_instance,
+        vec![
+            token_asset(usdc_token_instance.clone(), Uint128::from(100_000_u128)),
+            token_asset(astro_token_instance.clone(), Uint128::from(100_000_u128)),
+        ],
+        None,
+    );
+
+    let collect_msg = ExecuteMsg::Collect {
+        assets: vec![AssetWithLimit {
+            info: token_asset_info(usdc_token_instance.clone()),
+            limit: None,
+        }],
+    };
+
+    // ASTRO accrued before rewards are enabled is distributed as pre-upgrade ASTRO
+    mint_some_token(
+        &mut router,
+        owner.clone(),
+        astro_token_instance.clone(),
+        maker_instance.clone(),
+        Uint128::new(1000),
+    );
+    router
+        .execute_contract(keeper.clone(), maker_instance.clone(), &collect_msg, &[])
+        .unwrap();
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::EnableRewards { blocks: 1 },
+            &[],
+        )
+        .unwrap();
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::UpdateConfig {
+                factory_contract: None,
+                staking_contract: None,
+                governance_contract: None,
+                governance_percent: None,
+                basic_asset: None,
+                max_spread: None,
+                fee_receivers: None,
+                swap_protection: None,
+                keeper_bounty: Some(UpdateKeeperBounty::Set(KeeperBounty {
+                    share: Decimal::percent(5),
+                    max_bounty: Uint128::new(100),
+                    min_astro_amount: Uint128::new(50),
+                })),
+                auto_bridge: None,
+                astro_streaming: None,
+                astro_burn: None,
+            },
+            &[],
+        )
+        .unwrap();
+    router.update_block(next_block);
+
+    // Unlocked pre-upgrade ASTRO doesn't count toward the minimum
+    mint_some_token(
+        &mut router,
+        owner.clone(),
+        usdc_token_instance.clone(),
+        maker_instance.clone(),
+        Uint128::new(20),
+    );
+    let res: SimulateCollectResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &maker_instance,
+            &QueryMsg::SimulateCollect {
+                assets: vec![AssetWithLimit {
+                    info: token_asset_info(usdc_token_instance.clone()),
+                    limit: None,
+                }],
+            },
+        )
+        .unwrap();
+    assert_eq!(res.astro_amount, Uint128::new(1019));
+    assert!(res.below_min_astro_amount);
+    assert_eq!(res.keeper_bounty, Uint128::zero());
+
+    mint_some_token(
+        &mut router,
+        owner,
+        usdc_token_instance.clone(),
+        maker_instance.clone(),
+        Uint128::new(980),
+    );
+    let res: SimulateCollectResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &maker_instance,
+            &QueryMsg::SimulateCollect {
+                assets: vec![AssetWithLimit {
+                    info: token_asset_info(usdc_token_instance),
+                    limit: None,
+                }],
+            },
+        )
+        .unwrap();
+    assert_eq!(res.astro_amount, Uint128::new(1990));
+    // The bounty is 5% of the 990 ASTRO collected from fees
+    assert_eq!(res.keeper_bounty, Uint128::new(49));
+    assert_eq!(res.staking_amount, Uint128::new(1941));
+
+    router
+        .execute_contract(keeper.clone(), maker_instance.clone(), &collect_msg, &[])
+        .unwrap();
+
+    check_balance(
+        &mut router,
+        keeper,
+        astro_token_instance.clone(),
+        Uint128::new(49),
+    );
+    check_balance(
+        &mut router,
+        staking,
+        astro_token_instance.clone(),
+        Uint128::new(1941),
+    );
+    check_balance(
+        &mut router,
+        maker_instance,
+        astro_token_instance,
+        Uint128::zero(),
+    );
+}
+
+#[test]
+fn collect_with_auto_bridge() {
+    let owner = Addr::unchecked("owner");
+    let mut router = mock_app(owner.clone(), vec![]);
+    let staking = Addr::unchecked("staking");
+    let user = Addr::unchecked("user0000");
+
+    let (astro_token_instance, factory_instance, maker_instance, _) = instantiate_contracts(
+        &mut router,
+        owner.clone(),
+        staking.clone(),
+        Uint64::new(0),
+        Some(Decimal::percent(50)),
+        None,
+        None,
+    );
+
+    let foo_token_instance = instantiate_token(
+        &mut router,
+        owner.clone(),
+        "Foo token".to_string(),
+        "FOO".to_string(),
+    );
+    let bar_token_instance = instantiate_token(
+        &mut router,
+        owner.clone(),
+        "Bar token".to_string(),
+        "BAR".to_string(),
+    );
+    let usdc_token_instance = instantiate_token(
+        &mut router,
+        owner.clone(),
+        "Usdc token".to_string(),
+        "USDC".to_string(),
+    );
+    let baz_token_instance = instantiate_token(
+        &mut router,
+        owner.clone(),
+        "Baz token".to_string(),
+        "BAZ".to_string(),
+    );
+
+    // FOO can be swapped to ASTRO either through the deep BAR pool or the shallow USDC pool.
+    // BAZ can be swapped to ASTRO through BAR only
+    let pairs: Vec<PairInfo> = [
+        vec![
+            token_asset(foo_token_instance.clone(), Uint128::from(100_000_u128)),
+            token_asset(bar_token_instance.clone(), Uint128::from(100_000_u128)),
+        ],
+        vec![
+            token_asset(bar_token_instance.clone(), Uint128::from(100_000_u128)),
+            token_asset(astro_token_instance.clone(), Uint128::from(100_000_u128)),
+        ],
+        vec![
+            token_asset(foo_token_instance.clone(), Uint128::from(100_000_u128)),
+            token_asset(usdc_token_instance.clone(), Uint128::from(100_000_u128)),
+        ],
+        vec![
+            token_asset(usdc_token_instance.clone(), Uint128::from(5_000_u128)),
+            token_asset(astro_token_instance.clone(), Uint128::from(5_000_u128)),
+        ],
+        vec![
+            token_asset(baz_token_instance.clone(), Uint128::from(100_000_u128)),
+            token_asset(bar_token_instance.clone(), Uint128::from(100_000_u128)),
+        ],
+    ]
+    .into_iter()
+    .map(|assets| {
+        create_pair(
+            &mut router,
+            owner.clone(),
+            user.clone(),
+            &factory_instance,
+            assets,
+            None,
+        )
+    })
+    .collect();
+
+    let oracle_code_id = router.store_code(Box::new(ContractWrapper::new_with_empty(
+        astroport_oracle::contract::execute,
+        astroport_oracle::contract::instantiate,
+        astroport_oracle::contract::query,
+    )));
+    let oracle_instance = router
+        .instantiate_contract(
+            oracle_code_id,
+            owner.clone(),
+            &astroport::oracle::InstantiateMsg {
+                factory_contract: factory_instance.to_string(),
+                asset_infos: pairs[0].asset_infos.clone(),
+                period: None,
+                max_snapshots: None,
+            },
+            &[],
+            String::from("ORACLE"),
+            None,
+        )
+        .unwrap();
+    for pair in &pairs[1..] {
+        router
+            .execute_contract(
+                owner.clone(),
+                oracle_instance.clone(),
+                &astroport::oracle::ExecuteMsg::RegisterPair {
+                    asset_infos: pair.asset_infos.clone(),
+                },
+                &[],
+            )
+            .unwrap();
+    }
+
+    // The BAZ pool has no TWAP until later
+    router.update_block(|block| {
+        block.time = block.time.plus_seconds(86400);
+        block.height += 17280;
+    });
+    router
+        .execute_contract(
+            owner.clone(),
+            oracle_instance.clone(),
+            &astroport::oracle::ExecuteMsg::Update { pair: None },
+            &[],
+        )
+        .unwrap();
+    for pair in &pairs[1..4] {
+        router
+            .execute_contract(
+                owner.clone(),
+                oracle_instance.clone(),
+                &astroport::oracle::ExecuteMsg::Update {
+                    pair: Some(pair.contract_addr.to_string()),
+                },
+                &[],
+            )
+            .unwrap();
+    }
+
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::EnableRewards { blocks: 1 },
+            &[],
+        )
+        .unwrap();
+
+    mint_some_token(
+        &mut router,
+        owner.clone(),
+        foo_token_instance.clone(),
+        maker_instance.clone(),
+        Uint128::new(1000),
+    );
+    let collect_msg = |token: &Addr| ExecuteMsg::Collect {
+        assets: vec![AssetWithLimit {
+            info: token_asset_info(token.clone()),
+            limit: None,
+        }],
+    };
+
+    let err = router
+        .execute_contract(
+            Addr::unchecked("anyone"),
+            maker_instance.clone(),
+            &collect_msg(&foo_token_instance),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        format!("Cannot swap {foo_token_instance}. No swap destinations")
+    );
+
+    let simulate_msg = |token: &Addr| QueryMsg::SimulateCollect {
+        assets: vec![AssetWithLimit {
+            info: token_asset_info(token.clone()),
+            limit: None,
+        }],
+    };
+    let res: SimulateCollectResponse = router
+        .wrap()
+        .query_wasm_smart(&maker_instance, &simulate_msg(&foo_token_instance))
+        .unwrap();
+    assert_eq!(res.swaps, vec![]);
+    assert_eq!(
+        res.skipped,
+        vec![(
+            token_asset_info(foo_token_instance.clone()),
+            SkipReason::NoRoute
+        )]
+    );
+
+    let update_config_msg = |swap_protection: Option<UpdateSwapProtection>,
+                             auto_bridge: Option<UpdateAutoBridge>| {
+        ExecuteMsg::UpdateConfig {
+            factory_contract: None,
+            staking_contract: None,
+            governance_contract: None,
+            governance_percent: None,
+            basic_asset: None,
+            max_spread: None,
+            fee_receivers: None,
+            swap_protection,
+            keeper_bounty: None,
+            auto_bridge,
+            astro_streaming: None,
+            astro_burn: None,
+        }
+    };
+    let set_auto_bridge = |cache_ttl: u64| Some(UpdateAutoBridge::Set(AutoBridge { cache_ttl }));
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &update_config_msg(None, set_auto_bridge(0)),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Bridge cache TTL must be positive"
+    );
+
+    // Discovered routes are only used if every swap is close to its TWAP
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &update_config_msg(None, set_auto_bridge(86400)),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Automatic bridge discovery requires the swap protection"
+    );
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &update_config_msg(
+                Some(UpdateSwapProtection::Set(SwapProtection {
+                    twap_source: TwapSource::Oracle {
+                        contract: oracle_instance.to_string(),
+                        max_age: None,
+                    },
+                    max_deviation: Decimal::percent(10),
+                })),
+                set_auto_bridge(86400),
+            ),
+            &[],
+        )
+        .unwrap();
+
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &update_config_msg(Some(UpdateSwapProtection::Remove {}), None),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Automatic bridge discovery requires the swap protection"
+    );
+
+    // The simulation goes through the bridge which the collection discovers.
+    // The USDC route deviates too much from the TWAP in the shallow USDC pool
+    let res: SimulateCollectResponse = router
+        .wrap()
+        .query_wasm_smart(&maker_instance, &simulate_msg(&foo_token_instance))
+        .unwrap();
+    assert_eq!(res.skipped, vec![]);
+    assert_eq!(
+        res.swaps[0].route,
+        vec![
+            token_asset_info(foo_token_instance.clone()),
+            token_asset_info(bar_token_instance.clone()),
+            token_asset_info(astro_token_instance.clone()),
+        ]
+    );
+    assert_eq!(res.staking_amount, Uint128::new(980));
+
+    let discovered_bridge = Event::new("wasm").add_attribute(
+        "discovered_bridge",
+        format!("{foo_token_instance}:{bar_token_instance}"),
+    );
+
+    let res = router
+        .execute_contract(
+            Addr::unchecked("anyone"),
+            maker_instance.clone(),
+            &collect_msg(&foo_token_instance),
+            &[],
+        )
+        .unwrap();
+    assert!(res.has_event(&discovered_bridge));
+
+    let bridges: Vec<(String, String)> = router
+        .wrap()
+        .query_wasm_smart(&maker_instance, &QueryMsg::Bridges {})
+        .unwrap();
+    assert_eq!(
+        bridges,
+        vec![(
+            foo_token_instance.to_string(),
+            bar_token_instance.to_string()
+        )]
+    );
+    check_balance(
+        &mut router,
+        maker_instance.clone(),
+        foo_token_instance.clone(),
+        Uint128::zero(),
+    );
+    check_balance(
+        &mut router,
+        staking.clone(),
+        astro_token_instance.clone(),
+        Uint128::new(980),
+    );
+
+    // The cached bridge is used until it expires
+    mint_some_token(
+        &mut router,
+        owner.clone(),
+        foo_token_instance.clone(),
+        maker_instance.clone(),
+        Uint128::new(1000),
+    );
+    let res = router
+        .execute_contract(
+            Addr::unchecked("anyone"),
+            maker_instance.clone(),
+            &collect_msg(&foo_token_instance),
+            &[],
+        )
+        .unwrap();
+    assert!(!res.has_event(&discovered_bridge));
+
+    // BAZ is skipped as its only route has no TWAP and the failed discovery is cached
+    mint_some_token(
+        &mut router,
+        owner.clone(),
+        baz_token_instance.clone(),
+        maker_instance.clone(),
+        Uint128::new(1000),
+    );
+    let res = router
+        .execute_contract(
+            Addr::unchecked("anyone"),
+            maker_instance.clone(),
+            &collect_msg(&baz_token_instance),
+            &[],
+        )
+        .unwrap();
+    assert!(res.has_event(
+        &Event::new("wasm").add_attribute("skipped_asset", baz_token_instance.to_string())
+    ));
+    check_balance(
+        &mut router,
+        maker_instance.clone(),
+        baz_token_instance.clone(),
+        Uint128::new(1000),
+    );
+
+    router
+        .execute_contract(
+            owner.clone(),
+            oracle_instance.clone(),
+            &astroport::oracle::ExecuteMsg::Update {
+                pair: Some(pairs[4].contract_addr.to_string()),
+            },
+            &[],
+        )
+        .unwrap();
+    let res: SimulateCollectResponse = router
+        .wrap()
+        .query_wasm_smart(&maker_instance, &simulate_msg(&baz_token_instance))
+        .unwrap();
+    assert_eq!(
+        res.skipped,
+        vec![(
+            token_asset_info(baz_token_instance.clone()),
+            SkipReason::NoRoute
+        )]
+    );
+
+    // Expired bridges are ignored and discovered again
+    router.update_block(|block| {
+        block.time = block.time.plus_seconds(86400);
+        block.height += 17280;
+    });
+    let bridges: Vec<(String, String)> = router
+        .wrap()
+        .query_wasm_smart(&maker_instance, &QueryMsg::Bridges {})
+        .unwrap();
+    assert_eq!(bridges, vec![]);
+
+    let res: SimulateCollectResponse = router
+        .wrap()
+        .query_wasm_smart(&maker_instance, &simulate_msg(&baz_token_instance))
+        .unwrap();
+    assert_eq!(res.skipped, vec![]);
+    assert_eq!(
+        res.swaps[0].route,
+        vec![
+            token_asset_info(baz_token_instance.clone()),
+            token_asset_info(bar_token_instance.clone()),
+            token_asset_info(astro_token_instance.clone()),
+        ]
+    );
+
+    mint_some_token(
+        &mut router,
+        owner.clone(),
+        foo_token_instance.clone(),
+        maker_instance.clone(),
+        Uint128::new(1000),
+    );
+    let res = router
+        .execute_contract(
+            Addr::unchecked("anyone"),
+            maker_instance.clone(),
+            &collect_msg(&foo_token_instance),
+            &[],
+        )
+        .unwrap();
+    assert!(res.has_event(&discovered_bridge));
+    check_balance(
+        &mut router,
+        maker_instance.clone(),
+        foo_token_instance,
+        Uint128::zero(),
+    );
+
+    // Discovered bridges are removed along with the automatic bridge discovery
+    router
+        .execute_contract(
+            owner,
+            maker_instance.clone(),
+            &update_config_msg(None, Some(UpdateAutoBridge::Remove {})),
+            &[],
+        )
+        .unwrap();
+    let bridges: Vec<(String, String)> = router
+        .wrap()
+        .query_wasm_smart(&maker_instance, &QueryMsg::Bridges {})
+        .unwrap();
+    assert_eq!(bridges, vec![]);
+}
+
+#[test]
+fn collect_lp_tokens() {
+    let owner = Addr::unchecked("owner");
+    let mut router = mock_app(owner.clone(), vec![]);
+    let staking = Addr::unchecked("staking");
+    let user = Addr::unchecked("user0000");
+
+    let (astro_token_instance, factory_instance, maker_instance, _) = instantiate_contracts(
+        &mut router,
+        owner.clone(),
+        staking.clone(),
+        Uint64::new(0),
+        None,
+        None,
+        None,
+    );
+
+    let usdc_token_instance = instantiate_token(
+        &mut router,
+        owner.clone(),
+        "Usdc token".to_string(),
+        "USDC".to_string(),
+    );
+
+    let pair_info = create_pair(
+        &mut router,
+        owner.clone(),
+        user.clone(),
+        &factory_instance,
+        vec![
+            token_asset(usdc_token_instance.clone(), Uint128::from(100_000_u128)),
+            token_asset(astro_token_instance.clone(), Uint128::from(100_000_u128)),
+        ],
+        None,
+    );
+
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::EnableRewards { blocks: 1 },
+            &[],
+        )
+        .unwrap();
+
+    // The user sends LP tokens to the Maker as fees
+    router
+        .execute_contract(
+            user,
+            pair_info.liquidity_token.clone(),
+            &cw20::Cw20ExecuteMsg::Transfer {
+                recipient: maker_instance.to_string(),
+                amount: Uint128::new(1_000),
+            },
+            &[],
+        )
+        .unwrap();
+
+    let assets = vec![AssetWithLimit {
+        info: token_asset_info(pair_info.liquidity_token.clone()),
+        limit: None,
+    }];
+
+    // The share of the pool is simulated as separate swaps of the underlying assets
+    let res: SimulateCollectResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &maker_instance,
+            &QueryMsg::SimulateCollect {
+                assets: assets.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.swaps.len(), 1);
+    assert_eq!(
+        res.swaps[0].offer_asset,
+        token_asset(usdc_token_instance.clone(), Uint128::new(1_000))
+    );
+    assert_eq!(res.astro_amount, Uint128::new(1_990));
+    assert_eq!(res.staking_amount, Uint128::new(1_990));
+
+    let res = router
+        .execute_contract(
+            owner,
+            maker_instance.clone(),
+            &ExecuteMsg::Collect { assets },
+            &[],
+        )
+        .unwrap();
+    assert!(res
+        .events
+        .iter()
+        .any(|event| event.attributes.contains(&attr(
+            "lp_unwrapped",
+            format!("1000{}", pair_info.liquidity_token)
+        ))));
+
+    // 1000 ASTRO are withdrawn and 1000 USDC are swapped to 990 ASTRO
+    check_balance(
+        &mut router,
+        staking,
+        astro_token_instance.clone(),
+        Uint128::new(1_990),
+    );
+    check_balance(
+        &mut router,
+        maker_instance.clone(),
+        pair_info.liquidity_token,
+        Uint128::zero(),
+    );
+    check_balance(
+        &mut router,
+        maker_instance.clone(),
+        usdc_token_instance,
+        Uint128::zero(),
+    );
+    check_balance(
+        &mut router,
+        maker_instance,
+        astro_token_instance,
+        Uint128::zero(),
+    );
+}
+
+#[test]
+fn query_distribution_history() {
+    let owner = Addr::unchecked("owner");
+    let mut router = mock_app(owner.clone(), vec![]);
+    let staking = Addr::unchecked("staking");
+    let user = Addr::unchecked("user0000");
+
+    let (astro_token_instance, factory_instance, maker_instance, governance_instance) =
+        instantiate_contracts(
+            &mut router,
+            owner.clone(),
+            staking.clone(),
+            Uint64::new(50),
+            None,
+            None,
+            None,
+        );
+
+    let usdc_token_instance = instantiate_token(
+        &mut router,
+        owner.clone(),
+        "Usdc token".to_string(),
+        "USDC".to_string(),
+    );
+
+    create_pair(
+        &mut router,
+        owner.clone(),
+        user,
+        &factory_instance,
+        vec![
+            token_asset(usdc_token_instance.clone(), Uint128::from(100_000_u128)),
+            token_asset(astro_token_instance.clone(), Uint128::from(100_000_u128)),
+        ],
+        None,
+    );
+
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::EnableRewards { blocks: 1 },
+            &[],
+        )
+        .unwrap();
+
+    let collect_msg = ExecuteMsg::Collect {
+        assets: vec![AssetWithLimit {
+            info: token_asset_info(usdc_token_instance.clone()),
+            limit: None,
+        }],
+    };
+
+    for _ in 0..2 {
+        mint_some_token(
+            &mut router,
+            owner.clone(),
+            usdc_token_instance.clone(),
+            maker_instance.clone(),
+            Uint128::new(1_000),
+        );
+        router
+            .execute_contract(owner.clone(), maker_instance.clone(), &collect_msg, &[])
+            .unwrap();
+        router.update_block(|block| {
+            block.height += 1;
+            block.time = block.time.plus_seconds(5);
+        });
+    }
+
+    let res: Vec<(u64, DistributionRecord)> = router
+        .wrap()
+        .query_wasm_smart(
+            &maker_instance,
+            &QueryMsg::Distributions {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(res.len(), 2);
+
+    let (epoch, record) = &res[0];
+    assert_eq!(*epoch, 0);
+    assert_eq!(
+        record.collected,
+        vec![token_asset(
+            usdc_token_instance.clone(),
+            Uint128::new(1_000)
+        )]
+    );
+    assert_eq!(record.astro_amount, Uint128::new(990));
+    assert_eq!(record.keeper_bounty, Uint128::zero());
+    assert_eq!(record.fee_receivers, vec![]);
+    assert_eq!(record.governance_amount, Uint128::new(495));
+    assert_eq!(record.staking_amount, Uint128::new(495));
+
+    // Pagination
+    let res: Vec<(u64, DistributionRecord)> = router
+        .wrap()
+        .query_wasm_smart(
+            &maker_instance,
+            &QueryMsg::Distributions {
+                start_after: Some(0),
+                limit: Some(1),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.len(), 1);
+
+    let (epoch, record) = &res[0];
+    assert_eq!(*epoch, 1);
+    assert_eq!(
+        record.collected,
+        vec![token_asset(usdc_token_instance, Uint128::new(1_000))]
+    );
+    assert_eq!(
+        record.astro_amount,
+        record.governance_amount + record.staking_amount
+    );
+
+    check_balance(
+        &mut router,
+        governance_instance,
+        astro_token_instance.clone(),
+        res[0].1.governance_amount + Uint128::new(495),
+    );
+    check_balance(
+        &mut router,
+        staking,
+        astro_token_instance,
+        res[0].1.staking_amount + Uint128::new(495),
+    );
+}
+
+#[test]
+fn collect_with_astro_streaming() {
+    let owner = Addr::unchecked("owner");
+    let mut router = mock_app(owner.clone(), vec![]);
+    let staking = Addr::unchecked("staking");
+    let user = Addr::unchecked("user0000");
+
+    let (astro_token_instance, factory_instance, maker_instance, _) = instantiate_contracts(
+        &mut router,
+        owner.clone(),
+        staking.clone(),
+        Uint64::new(0),
+        None,
+        None,
+        None,
+    );
+
+    let usdc_token_instance = instantiate_token(
+        &mut router,
+        owner.clone(),
+        "Usdc token".to_string(),
+        "USDC".to_string(),
+    );
+
+    create_pair(
+        &mut router,
+        owner.clone(),
+        user,
+        &factory_instance,
+        vec![
+            token_asset(usdc_token_instance.clone(), Uint128::from(100_000_u128)),
+            token_asset(astro_token_instance.clone(), Uint128::from(100_000_u128)),
+        ],
+        None,
+    );
+
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::EnableRewards { blocks: 1 },
+            &[],
+        )
+        .unwrap();
+
+    let update_config_msg = |duration: u64| ExecuteMsg::UpdateConfig {
+        factory_contract: None,
+        staking_contract: None,
+        governance_contract: None,
+        governance_percent: None,
+        basic_asset: None,
+        max_spread: None,
+        fee_receivers: None,
+        swap_protection: None,
+        keeper_bounty: None,
+        auto_bridge: None,
+        astro_streaming: Some(UpdateAstroStreaming::Set(AstroStreaming { duration })),
+        astro_burn: None,
+    };
+
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &update_config_msg(0),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "ASTRO streaming duration must be positive"
+    );
+
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &update_config_msg(100),
+            &[],
+        )
+        .unwrap();
+
+    mint_some_token(
+        &mut router,
+        owner.clone(),
+        usdc_token_instance.clone(),
+        maker_instance.clone(),
+        Uint128::new(1_000),
+    );
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::Collect {
+                assets: vec![AssetWithLimit {
+                    info: token_asset_info(usdc_token_instance.clone()),
+                    limit: None,
+                }],
+            },
+            &[],
+        )
+        .unwrap();
+
+    // Swapped ASTRO is not sent to stakers at once
+    let start = router.block_info().time.seconds();
+    let res: Option<AstroStream> = router
+        .wrap()
+        .query_wasm_smart(&maker_instance, &QueryMsg::AstroStream {})
+        .unwrap();
+    assert_eq!(
+        res,
+        Some(AstroStream {
+            amount: Uint128::new(990),
+            last_release: start,
+            end: start + 100,
+            tranches: vec![AstroTranche {
+                amount: Uint128::new(990),
+                end: start + 100,
+            }],
+        })
+    );
+    check_balance(
+        &mut router,
+        staking.clone(),
+        astro_token_instance.clone(),
+        Uint128::zero(),
+    );
+
+    // Streamed ASTRO is excluded from new distributions
+    let res: SimulateCollectResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &maker_instance,
+            &QueryMsg::SimulateCollect { assets: vec![] },
+        )
+        .unwrap();
+    assert_eq!(res.astro_amount, Uint128::zero());
+
+    router.update_block(|block| {
+        block.height += 1;
+        block.time = block.time.plus_seconds(50);
+    });
+    router
+        .execute_contract(
+            Addr::unchecked("anyone"),
+            maker_instance.clone(),
+            &ExecuteMsg::ReleaseAstro {},
+            &[],
+        )
+        .unwrap();
+    check_balance(
+        &mut router,
+        staking.clone(),
+        astro_token_instance.clone(),
+        Uint128::new(495),
+    );
+
+    // ASTRO collected while the stream is active is released over its own duration
+    mint_some_token(
+        &mut router,
+        owner.clone(),
+        usdc_token_instance.clone(),
+        maker_instance.clone(),
+        Uint128::new(1_000),
+    );
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::Collect {
+                assets: vec![AssetWithLimit {
+                    info: token_asset_info(usdc_token_instance),
+                    limit: None,
+                }],
+            },
+            &[],
+        )
+        .unwrap();
+
+    let stream: AstroStream = router
+        .wrap()
+        .query_wasm_smart::<Option<AstroStream>>(&maker_instance, &QueryMsg::AstroStream {})
+        .unwrap()
+        .unwrap();
+    let new_amount = stream.amount - Uint128::new(495);
+    assert_eq!(
+        stream,
+        AstroStream {
+            amount: Uint128::new(495) + new_amount,
+            last_release: start + 50,
+            end: start + 150,
+            tranches: vec![
+                AstroTranche {
+                    amount: Uint128::new(495),
+                    end: start + 100,
+                },
+                AstroTranche {
+                    amount: new_amount,
+                    end: start + 150,
+                },
+            ],
+        }
+    );
+
+    // The remainder of the first collection is not stretched by the second one
+    router.update_block(|block| {
+        block.height += 1;
+        block.time = block.time.plus_seconds(50);
+    });
+    router
+        .execute_contract(
+            Addr::unchecked("anyone"),
+            maker_instance.clone(),
+            &ExecuteMsg::ReleaseAstro {},
+            &[],
+        )
+        .unwrap();
+    check_balance(
+        &mut router,
+        staking.clone(),
+        astro_token_instance.clone(),
+        Uint128::new(990) + new_amount.multiply_ratio(50u128, 100u128),
+    );
+
+    router.update_block(|block| {
+        block.height += 1;
+        block.time = block.time.plus_seconds(100);
+    });
+    router
+        .execute_contract(
+            owner,
+            maker_instance.clone(),
+            &ExecuteMsg::Collect { assets: vec![] },
+            &[],
+        )
+        .unwrap();
+    check_balance(
+        &mut router,
+        staking,
+        astro_token_instance.clone(),
+        Uint128::new(990) + new_amount,
+    );
+    check_balance(
+        &mut router,
+        maker_instance.clone(),
+        astro_token_instance,
+        Uint128::zero(),
+    );
+
+    let res: Option<AstroStream> = router
+        .wrap()
+        .query_wasm_smart(&maker_instance, &QueryMsg::AstroStream {})
+        .unwrap();
+    assert_eq!(res, None);
+}
+
+#[test]
+fn astro_stream_tranches_are_capped() {
+    let owner = Addr::unchecked("owner");
+    let mut router = mock_app(owner.clone(), vec![]);
+    let staking = Addr::unchecked("staking");
+    let user = Addr::unchecked("user0000");
+
+    let (astro_token_instance, factory_instance, maker_instance, _) = instantiate_contracts(
+        &mut router,
+        owner.clone(),
+        staking,
+        Uint64::new(0),
+        None,
+        None,
+        None,
+    );
+
+    let usdc_token_instance = instantiate_token(
+        &mut router,
+        owner.clone(),
+        "Usdc token".to_string(),
+        "USDC".to_string(),
+    );
+
+    create_pair(
+        &mut router,
+        owner.clone(),
+        user,
+        &factory_instance,
+        vec![
+            token_asset(usdc_token_instance.clone(), Uint128::from(100_000_u128)),
+            token_asset(astro_token_instance, Uint128::from(100_000_u128)),
+        ],
+        None,
+    );
+
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::EnableRewards { blocks: 1 },
+            &[],
+        )
+        .unwrap();
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::UpdateConfig {
+                factory_contract: None,
+                staking_contract: None,
+                governance_contract: None,
+                governance_percent: None,
+                basic_asset: None,
+                max_spread: None,
+                fee_receivers: None,
+                swap_protection: None,
+                keeper_bounty: None,
+                auto_bridge: None,
+                astro_streaming: Some(UpdateAstroStreaming::Set(AstroStreaming { duration: 100 })),
+                astro_burn: None,
+            },
+            &[],
+        )
+        .unwrap();
+
+    let collect_msg = ExecuteMsg::Collect {
+        assets: vec![AssetWithLimit {
+            info: token_asset_info(usdc_token_instance.clone()),
+            limit: None,
+        }],
+    };
+
+    // Every collection streams its ASTRO as a new tranche
+    let mut streamed = Uint128::zero();
+    for _ in 0..11 {
+        router.update_block(|block| {
+            block.height += 1;
+            block.time = block.time.plus_seconds(1);
+        });
+        mint_some_token(
+            &mut router,
+            owner.clone(),
+            usdc_token_instance.clone(),
+            maker_instance.clone(),
+            Uint128::new(100),
+        );
+        let res = router
+            .execute_contract(owner.clone(), maker_instance.clone(), &collect_msg, &[])
+            .unwrap();
+        streamed = res
+            .events
+            .iter()
+            .flat_map(|event| &event.attributes)
+            .find(|attr| attr.key == "astro_streamed")
+            .map(|attr| Uint128::from_str(&attr.value).unwrap())
+            .unwrap();
+    }
+
+    // The two oldest tranches are merged while the 11th one is released over the full duration
+    let now = router.block_info().time.seconds();
+    let stream: AstroStream = router
+        .wrap()
+        .query_wasm_smart::<Option<AstroStream>>(&maker_instance, &QueryMsg::AstroStream {})
+        .unwrap()
+        .unwrap();
+    assert_eq!(stream.tranches.len(), 10);
+    assert_eq!(
+        stream.tranches.last(),
+        Some(&AstroTranche {
+            amount: streamed,
+            end: now + 100,
+        })
+    );
+    assert_eq!(stream.end, now + 100);
+
+    router.update_block(|block| {
+        block.height += 1;
+        block.time = block.time.plus_seconds(99);
+    });
+    router
+        .execute_contract(
+            Addr::unchecked("anyone"),
+            maker_instance.clone(),
+            &ExecuteMsg::ReleaseAstro {},
+            &[],
+        )
+        .unwrap();
+    let stream: AstroStream = router
+        .wrap()
+        .query_wasm_smart::<Option<AstroStream>>(&maker_instance, &QueryMsg::AstroStream {})
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        stream.tranches,
+        vec![AstroTranche {
+            amount: streamed - streamed.multiply_ratio(99u128, 100u128),
+            end: now + 100,
+        }]
+    );
+}
+
+#[test]
+fn collect_with_limits() {
+    let owner = Addr::unchecked("owner");
+    let mut router = mock_app(owner.clone(), vec![]);
+    let staking = Addr::unchecked("staking");
+    let user = Addr::unchecked("user0000");
+
+    let (astro_token_instance, factory_instance, maker_instance, _) = instantiate_contracts(
+        &mut router,
+        owner.clone(),
+        staking.clone(),
+        Uint64::new(0),
+        None,
+        None,
+        None,
+    );
+
+    let usdc_token_instance = instantiate_token(
+        &mut router,
+        owner.clone(),
+        "Usdc token".to_string(),
+        "USDC".to_string(),
+    );
+
+    create_pair(
+        &mut router,
+        owner.clone(),
+        user,
+        &factory_instance,
+        vec![
+            token_asset(usdc_token_instance.clone(), Uint128::from(100_000_u128)),
+            token_asset(astro_token_instance.clone(), Uint128::from(100_000_u128)),
+        ],
+        None,
+    );
+
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::EnableRewards { blocks: 1 },
+            &[],
+        )
+        .unwrap();
+
+    let mut collect_limit = CollectLimit {
+        max_pool_share: Some(Decimal::zero()),
+        cooldown: Some(100),
+    };
+    let update_limits_msg = |collect_limit: CollectLimit| ExecuteMsg::UpdateCollectLimits {
+        add: Some(vec![(
+            token_asset_info(usdc_token_instance.clone()),
+            collect_limit,
+        )]),
+        remove: None,
+    };
+
+    let err = router
+        .execute_contract(
+            Addr::unchecked("not_owner"),
+            maker_instance.clone(),
+            &update_limits_msg(collect_limit.clone()),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "Unauthorized");
+
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &update_limits_msg(collect_limit.clone()),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Collect limit pool share must be positive and not greater than 1"
+    );
+
+    collect_limit.max_pool_share = Some(Decimal::percent(1));
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &update_limits_msg(collect_limit.clone()),
+            &[],
+        )
+        .unwrap();
+
+    let res: Vec<(String, CollectLimit)> = router
+        .wrap()
+        .query_wasm_smart(&maker_instance, &QueryMsg::CollectLimits {})
+        .unwrap();
+    assert_eq!(
+        res,
+        vec![(usdc_token_instance.to_string(), collect_limit.clone())]
+    );
+
+    mint_some_token(
+        &mut router,
+        owner.clone(),
+        usdc_token_instance.clone(),
+        maker_instance.clone(),
+        Uint128::new(3_000),
+    );
+
+    let collect_msg = ExecuteMsg::Collect {
+        assets: vec![AssetWithLimit {
+            info: token_asset_info(usdc_token_instance.clone()),
+            limit: None,
+        }],
+    };
+
+    // Only 1% of the pool reserve is swapped
+    router
+        .execute_contract(owner.clone(), maker_instance.clone(), &collect_msg, &[])
+        .unwrap();
+    check_balance(
+        &mut router,
+        maker_instance.clone(),
+        usdc_token_instance.clone(),
+        Uint128::new(2_000),
+    );
+    check_balance(
+        &mut router,
+        staking.clone(),
+        astro_token_instance.clone(),
+        Uint128::new(990),
+    );
+
+    // The token is skipped until the cooldown passes
+    let res: SimulateCollectResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &maker_instance,
+            &QueryMsg::SimulateCollect {
+                assets: vec![AssetWithLimit {
+                    info: token_asset_info(usdc_token_instance.clone()),
+                    limit: None,
+                }],
+            },
+        )
+        .unwrap();
+    assert_eq!(res.swaps, vec![]);
+    assert_eq!(
+        res.skipped,
+        vec![(
+            token_asset_info(usdc_token_instance.clone()),
+            SkipReason::CollectLimit
+        )]
+    );
+
+    let res = router
+        .execute_contract(owner.clone(), maker_instance.clone(), &collect_msg, &[])
+        .unwrap();
+    assert!(res.events.iter().any(|event| event
+        .attributes
+        .contains(&attr("skipped_asset", usdc_token_instance.to_string()))));
+    check_balance(
+        &mut router,
+        maker_instance.clone(),
+        usdc_token_instance.clone(),
+        Uint128::new(2_000),
+    );
+
+    router.update_block(|block| {
+        block.height += 1;
+        block.time = block.time.plus_seconds(100);
+    });
+
+    // The pool holds 101000 USDC after the first swap
+    router
+        .execute_contract(owner.clone(), maker_instance.clone(), &collect_msg, &[])
+        .unwrap();
+    check_balance(
+        &mut router,
+        maker_instance.clone(),
+        usdc_token_instance.clone(),
+        Uint128::new(990),
+    );
+
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::UpdateCollectLimits {
+                add: None,
+                remove: Some(vec![token_asset_info(usdc_token_instance)]),
+            },
+            &[],
+        )
+        .unwrap();
+
+    let res: Vec<(String, CollectLimit)> = router
+        .wrap()
+        .query_wasm_smart(&maker_instance, &QueryMsg::CollectLimits {})
+        .unwrap();
+    assert_eq!(res, vec![]);
+}
+
+#[test]
+fn collect_limited_asset_without_pool() {
+    let owner = Addr::unchecked("owner");
+    let mut router = mock_app(owner.clone(), vec![]);
+    let staking = Addr::unchecked("staking");
+    let user = Addr::unchecked("user0000");
+
+    let (astro_token_instance, factory_instance, maker_instance, _) = instantiate_contracts(
+        &mut router,
+        owner.clone(),
+        staking.clone(),
+        Uint64::new(0),
+        None,
+        None,
+        None,
+    );
+
+    let usdc_token_instance = instantiate_token(
+        &mut router,
+        owner.clone(),
+        "Usdc token".to_string(),
+        "USDC".to_string(),
+    );
+    let foo_token_instance = instantiate_token(
+        &mut router,
+        owner.clone(),
+        "Foo token".to_string(),
+        "FOO".to_string(),
+    );
+
+    create_pair(
+        &mut router,
+        owner.clone(),
+        user,
+        &factory_instance,
+        vec![
+            token_asset(usdc_token_instance.clone(), Uint128::from(100_000_u128)),
+            token_asset(astro_token_instance.clone(), Uint128::from(100_000_u128)),
+        ],
+        None,
+    );
+
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::EnableRewards { blocks: 1 },
+            &[],
+        )
+        .unwrap();
+
+    let update_limits_msg = |cooldown: Option<u64>| ExecuteMsg::UpdateCollectLimits {
+        add: Some(vec![
+            (
+                token_asset_info(usdc_token_instance.clone()),
+                CollectLimit {
+                    max_pool_share: Some(Decimal::percent(1)),
+                    cooldown,
+                },
+            ),
+            (
+                token_asset_info(foo_token_instance.clone()),
+                CollectLimit {
+                    max_pool_share: Some(Decimal::percent(1)),
+                    cooldown: None,
+                },
+            ),
+        ]),
+        remove: None,
+    };
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &update_limits_msg(None),
+            &[],
+        )
+        .unwrap();
+
+    mint_some_token(
+        &mut router,
+        owner.clone(),
+        usdc_token_instance.clone(),
+        maker_instance.clone(),
+        Uint128::new(3_000),
+    );
+    mint_some_token(
+        &mut router,
+        owner.clone(),
+        foo_token_instance.clone(),
+        maker_instance.clone(),
+        Uint128::new(1_000),
+    );
+
+    // FOO has no pool thus it is skipped while USDC is still collected
+    let res: SimulateCollectResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &maker_instance,
+            &QueryMsg::SimulateCollect {
+                assets: vec![AssetWithLimit {
+                    info: token_asset_info(foo_token_instance.clone()),
+                    limit: None,
+                }],
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res.skipped,
+        vec![(
+            token_asset_info(foo_token_instance.clone()),
+            SkipReason::CollectLimit
+        )]
+    );
+
+    let res = router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::Collect {
+                assets: vec![
+                    AssetWithLimit {
+                        info: token_asset_info(usdc_token_instance.clone()),
+                        limit: None,
+                    },
+                    AssetWithLimit {
+                        info: token_asset_info(foo_token_instance.clone()),
+                        limit: None,
+                    },
+                ],
+            },
+            &[],
+        )
+        .unwrap();
+    assert!(res.events.iter().any(|event| event
+        .attributes
+        .contains(&attr("skipped_asset", foo_token_instance.to_string()))));
+    check_balance(
+        &mut router,
+        maker_instance.clone(),
+        foo_token_instance,
+        Uint128::new(1_000),
+    );
+    check_balance(
+        &mut router,
+        maker_instance.clone(),
+        usdc_token_instance.clone(),
+        Uint128::new(2_000),
+    );
+    check_balance(
+        &mut router,
+        staking,
+        astro_token_instance,
+        Uint128::new(990),
+    );
+
+    // Collections of tokens without a cooldown are not recorded thus a new cooldown starts with the next collection
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &update_limits_msg(Some(100)),
+            &[],
+        )
+        .unwrap();
+    router
+        .execute_contract(
+            owner,
+            maker_instance.clone(),
+            &ExecuteMsg::Collect {
+                assets: vec![AssetWithLimit {
+                    info: token_asset_info(usdc_token_instance.clone()),
+                    limit: None,
+                }],
+            },
+            &[],
+        )
+        .unwrap();
+    check_balance(
+        &mut router,
+        maker_instance,
+        usdc_token_instance,
+        Uint128::new(990),
+    );
+}
+
+#[test]
+fn collect_with_astro_burn() {
+    let owner = Addr::unchecked("owner");
+    let mut router = mock_app(owner.clone(), vec![]);
+    let staking = Addr::unchecked("staking");
+    let user = Addr::unchecked("user0000");
+    let burn_address = Addr::unchecked("burn_address");
+
+    let (astro_token_instance, factory_instance, maker_instance, governance_instance) =
+        instantiate_contracts(
+            &mut router,
+            owner.clone(),
+            staking.clone(),
+            Uint64::new(50),
+            None,
+            None,
+            None,
+        );
+
+    let usdc_token_instance = instantiate_token(
+        &mut router,
+        owner.clone(),
+        "Usdc token".to_string(),
+        "USDC".to_string(),
+    );
+
+    create_pair(
+        &mut router,
+        owner.clone(),
+        user,
+        &factory_instance,
+        vec![
+            token_asset(usdc_token_instance.clone(), Uint128::from(100_000_u128)),
+            token_asset(astro_token_instance.clone(), Uint128::from(100_000_u128)),
+        ],
+        None,
+    );
+
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::EnableRewards { blocks: 1 },
+            &[],
+        )
+        .unwrap();
+
+    let update_config_msg = |params: AstroBurnParams| ExecuteMsg::UpdateConfig {
+        factory_contract: None,
+        staking_contract: None,
+        governance_contract: None,
+        governance_percent: None,
+        basic_asset: None,
+        max_spread: None,
+        fee_receivers: None,
+        swap_protection: None,
+        keeper_bounty: None,
+        auto_bridge: None,
+        astro_streaming: None,
+        astro_burn: Some(UpdateAstroBurn::Set(params)),
+    };
+
+    for percent in [0, 101] {
+        let err = router
+            .execute_contract(
+                owner.clone(),
+                maker_instance.clone(),
+                &update_config_msg(AstroBurnParams {
+                    percent: Uint64::new(percent),
+                    burn_address: None,
+                }),
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            "Incorrect burn percent. It should be positive and not greater than 100"
+        );
+    }
+
+    // Cw20 ASTRO is burned directly
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &update_config_msg(AstroBurnParams {
+                percent: Uint64::new(40),
+                burn_address: None,
+            }),
+            &[],
+        )
+        .unwrap();
+
+    let collect_msg = ExecuteMsg::Collect {
+        assets: vec![AssetWithLimit {
+            info: token_asset_info(usdc_token_instance.clone()),
+            limit: None,
+        }],
+    };
+
+    let query_total_supply = |router: &App| {
+        router
+            .wrap()
+            .query_wasm_smart::<cw20::TokenInfoResponse>(
+                &astro_token_instance,
+                &Cw20QueryMsg::TokenInfo {},
+            )
+            .unwrap()
+            .total_supply
+    };
+    let total_supply = query_total_supply(&router);
+
+    mint_some_token(
+        &mut router,
+        owner.clone(),
+        usdc_token_instance.clone(),
+        maker_instance.clone(),
+        Uint128::new(1_000),
+    );
+
+    let res: SimulateCollectResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &maker_instance,
+            &QueryMsg::SimulateCollect {
+                assets: vec![AssetWithLimit {
+                    info: token_asset_info(usdc_token_instance.clone()),
+                    limit: None,
+                }],
+            },
+        )
+        .unwrap();
+    assert_eq!(res.astro_amount, Uint128::new(990));
+    assert_eq!(res.burn_amount, Uint128::new(396));
+    assert_eq!(res.governance_amount, Uint128::new(297));
+    assert_eq!(res.staking_amount, Uint128::new(297));
+
+    router
+        .execute_contract(owner.clone(), maker_instance.clone(), &collect_msg, &[])
+        .unwrap();
+
+    // 40% of 990 ASTRO is burned and the rest is split between governance and stakers
+    assert_eq!(
+        query_total_supply(&router),
+        total_supply - Uint128::new(396)
+    );
+    check_balance(
+        &mut router,
+        governance_instance,
+        astro_token_instance.clone(),
+        Uint128::new(297),
+    );
+    check_balance(
+        &mut router,
+        staking.clone(),
+        astro_token_instance.clone(),
+        Uint128::new(297),
+    );
+
+    let res: ConfigResponse = router
+        .wrap()
+        .query_wasm_smart(&maker_instance, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(
+        res.astro_burn,
+        Some(AstroBurn {
+            percent: Uint64::new(40),
+            burn_address: None,
+        })
+    );
+    assert_eq!(res.burned_amount, Uint128::new(396));
+
+    // All ASTRO is sent to the burn address
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &update_config_msg(AstroBurnParams {
+                percent: Uint64::new(100),
+                burn_address: Some(burn_address.to_string()),
+            }),
+            &[],
+        )
+        .unwrap();
+
+    mint_some_token(
+        &mut router,
+        owner.clone(),
+        usdc_token_instance,
+        maker_instance.clone(),
+        Uint128::new(1_000),
+    );
+    router
+        .execute_contract(owner, maker_instance.clone(), &collect_msg, &[])
+        .unwrap();
+
+    let res: ConfigResponse = router
+        .wrap()
+        .query_wasm_smart(&maker_instance, &QueryMsg::Config {})
+        .unwrap();
+    check_balance(
+        &mut router,
+        burn_address,
+        astro_token_instance.clone(),
+        res.burned_amount - Uint128::new(396),
+    );
+    check_balance(
+        &mut router,
+        staking,
+        astro_token_instance.clone(),
+        Uint128::new(297),
+    );
+    check_balance(
+        &mut router,
+        maker_instance,
+        astro_token_instance,
+        Uint128::zero(),
+    );
+}
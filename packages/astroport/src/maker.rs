@@ -32,8 +32,18 @@ pub struct Config {
     pub remainder_reward: Uint128,
     /// The amount of collected ASTRO before enabling rewards distribution
     pub pre_upgrade_astro_amount: Uint128,
-    /// Parameters that describe the second receiver of fees
-    pub second_receiver_cfg: Option<SecondReceiverConfig>,
+    /// Additional receivers of fees which are paid before governance and xASTRO stakers
+    pub fee_receivers: Vec<FeeReceiver>,
+    /// Parameters of the TWAP protection of fee swaps
+    pub swap_protection: Option<SwapProtection>,
+    /// Parameters of the bounty paid to keepers for collecting fees
+    pub keeper_bounty: Option<KeeperBounty>,
+    /// Parameters of the automatic bridge discovery
+    pub auto_bridge: Option<AutoBridge>,
+    /// Parameters of the streaming of ASTRO to the xASTRO staking contract
+    pub astro_streaming: Option<AstroStreaming>,
+    /// Parameters of burning ASTRO
+    pub astro_burn: Option<AstroBurn>,
 }
 
 /// This structure stores general parameters for the contract.
@@ -55,14 +65,25 @@ pub struct InstantiateMsg {
     pub governance_percent: Option<Uint64>,
     /// The maximum spread used when swapping fee tokens to ASTRO
     pub max_spread: Option<Decimal>,
-    /// The second receiver parameters of fees
-    pub second_receiver_params: Option<SecondReceiverParams>,
+    /// Additional receivers of fees
+    pub fee_receivers: Option<Vec<FeeReceiverParams>>,
+    /// Parameters of the TWAP protection of fee swaps
+    pub swap_protection: Option<SwapProtection>,
+    /// Parameters of the bounty paid to keepers for collecting fees
+    pub keeper_bounty: Option<KeeperBounty>,
+    /// Parameters of the automatic bridge discovery
+    pub auto_bridge: Option<AutoBridge>,
+    /// Parameters of the streaming of ASTRO to the xASTRO staking contract
+    pub astro_streaming: Option<AstroStreaming>,
+    /// Parameters of burning ASTRO
+    pub astro_burn: Option<AstroBurnParams>,
 }
 
 /// This structure describes the functions that can be executed in this contract.
 #[cw_serde]
 pub enum ExecuteMsg {
-    /// Collects and swaps fee tokens to ASTRO
+    /// Collects and swaps fee tokens to ASTRO. Astroport LP tokens are unwrapped
+    /// and their underlying assets are swapped to ASTRO as well
     Collect {
         /// The assets to swap to ASTRO
         assets: Vec<AssetWithLimit>,
@@ -81,18 +102,35 @@ pub enum ExecuteMsg {
         basic_asset: Option<AssetInfo>,
         /// The maximum spread used when swapping fee tokens to ASTRO
         max_spread: Option<Decimal>,
-        /// The second receiver parameters of fees
-        second_receiver_params: Option<SecondReceiverParams>,
+        /// Replaces the list of additional fee receivers. An empty list removes all of them
+        fee_receivers: Option<Vec<FeeReceiverParams>>,
+        /// Sets or removes the TWAP protection of fee swaps
+        swap_protection: Option<UpdateSwapProtection>,
+        /// Sets or removes the keeper bounty
+        keeper_bounty: Option<UpdateKeeperBounty>,
+        /// Enables or disables the automatic bridge discovery
+        auto_bridge: Option<UpdateAutoBridge>,
+        /// Enables or disables the streaming of ASTRO to the xASTRO staking contract
+        astro_streaming: Option<UpdateAstroStreaming>,
+        /// Sets or removes burning of ASTRO
+        astro_burn: Option<UpdateAstroBurn>,
     },
     /// Add bridge tokens used to swap specific fee tokens to ASTRO (effectively declaring a swap route)
     UpdateBridges {
         add: Option<Vec<(AssetInfo, AssetInfo)>>,
         remove: Option<Vec<AssetInfo>>,
     },
+    /// Add or remove limits applied to collections of specific fee tokens
+    UpdateCollectLimits {
+        add: Option<Vec<(AssetInfo, CollectLimit)>>,
+        remove: Option<Vec<AssetInfo>>,
+    },
     /// Swap fee tokens via bridge assets
     SwapBridgeAssets { assets: Vec<AssetInfo>, depth: u64 },
     /// Distribute ASTRO to stakers and to governance
     DistributeAstro {},
+    /// Releases the streamed ASTRO which is due to the xASTRO staking contract
+    ReleaseAstro {},
     /// Creates a request to change the contract's ownership
     ProposeNewOwner {
         /// The newly proposed owner
@@ -120,6 +158,22 @@ pub enum QueryMsg {
     Balances { assets: Vec<AssetInfo> },
     #[returns(Vec<(String, String)>)]
     Bridges {},
+    /// Simulates the collection of the specified assets and returns the swap plan
+    /// along with the resulting distribution of ASTRO in a [`SimulateCollectResponse`]
+    #[returns(SimulateCollectResponse)]
+    SimulateCollect { assets: Vec<AssetWithLimit> },
+    /// Returns the history of ASTRO distributions ordered by epoch. Every distribution is a separate epoch
+    #[returns(Vec<(u64, DistributionRecord)>)]
+    Distributions {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Returns the ASTRO which is being streamed to the xASTRO staking contract
+    #[returns(Option<AstroStream>)]
+    AstroStream {},
+    /// Returns the limits applied to collections of fee tokens
+    #[returns(Vec<(String, CollectLimit)>)]
+    CollectLimits {},
 }
 
 /// A custom struct that holds contract parameters and is used to retrieve them.
@@ -145,8 +199,20 @@ pub struct ConfigResponse {
     pub remainder_reward: Uint128,
     /// The amount of ASTRO tokens accrued before upgrading the Maker implementation and enabling reward distribution
     pub pre_upgrade_astro_amount: Uint128,
-    /// Parameters that describe the second receiver of fees
-    pub second_receiver_cfg: Option<SecondReceiverConfig>,
+    /// Additional receivers of fees which are paid before governance and xASTRO stakers
+    pub fee_receivers: Vec<FeeReceiver>,
+    /// Parameters of the TWAP protection of fee swaps
+    pub swap_protection: Option<SwapProtection>,
+    /// Parameters of the bounty paid to keepers for collecting fees
+    pub keeper_bounty: Option<KeeperBounty>,
+    /// Parameters of the automatic bridge discovery
+    pub auto_bridge: Option<AutoBridge>,
+    /// Parameters of the streaming of ASTRO to the xASTRO staking contract
+    pub astro_streaming: Option<AstroStreaming>,
+    /// Parameters of burning ASTRO
+    pub astro_burn: Option<AstroBurn>,
+    /// The total amount of ASTRO burned by the contract
+    pub burned_amount: Uint128,
 }
 
 /// A custom struct used to return multiple asset balances.
@@ -155,13 +221,97 @@ pub struct BalancesResponse {
     pub balances: Vec<Asset>,
 }
 
+/// This structure describes the simulated swap of a fee token to ASTRO.
+#[cw_serde]
+pub struct SwapSimulation {
+    /// The fee token and the amount which is swapped
+    pub offer_asset: Asset,
+    /// The assets which the fee token is swapped through, starting with the fee token and ending with ASTRO
+    pub route: Vec<AssetInfo>,
+    /// The expected amount of ASTRO
+    pub astro_amount: Uint128,
+    /// The price impact of the whole route
+    pub price_impact: Decimal,
+    /// Whether the swap is rejected by the TWAP protection
+    pub skipped: bool,
+}
+
+/// This enum describes why a fee token is not collected.
+#[cw_serde]
+pub enum SkipReason {
+    /// The fee token is on its collect cooldown or its pool is empty
+    CollectLimit,
+    /// The fee token has no route to ASTRO
+    NoRoute,
+}
+
+/// This structure describes the result of a simulated collection.
+/// Every fee token is simulated independently against the current pool reserves.
+#[cw_serde]
+pub struct SimulateCollectResponse {
+    /// The swaps of fee tokens to ASTRO
+    pub swaps: Vec<SwapSimulation>,
+    /// The amount of ASTRO which is distributed after the swaps
+    pub astro_amount: Uint128,
+    /// The amounts of ASTRO which go to additional fee receivers
+    pub fee_receivers: Vec<(Addr, Uint128)>,
+    /// The amount of ASTRO which goes to the governance contract
+    pub governance_amount: Uint128,
+    /// The amount of ASTRO which goes to the xASTRO staking contract
+    pub staking_amount: Uint128,
+    /// The amount of ASTRO which is paid to the keeper
+    pub keeper_bounty: Uint128,
+    /// The amount of ASTRO which is burned
+    pub burn_amount: Uint128,
+    /// Fee tokens which are not collected along with the reason
+    pub skipped: Vec<(AssetInfo, SkipReason)>,
+    /// Whether no keeper bounty is paid as the collection distributes less than the keeper bounty minimum
+    pub below_min_astro_amount: bool,
+}
+
+/// This structure describes a stored record of a single ASTRO distribution.
+#[cw_serde]
+pub struct DistributionRecord {
+    /// The timestamp of the distribution in seconds
+    pub timestamp: u64,
+    /// The block height of the distribution
+    pub height: u64,
+    /// The fee tokens which were collected and swapped to ASTRO since the previous distribution
+    pub collected: Vec<Asset>,
+    /// The amount of ASTRO which was distributed
+    pub astro_amount: Uint128,
+    /// The amount of ASTRO which was paid to the keeper
+    pub keeper_bounty: Uint128,
+    /// The amounts of ASTRO which went to additional fee receivers
+    pub fee_receivers: Vec<(Addr, Uint128)>,
+    /// The amount of ASTRO which was burned
+    pub burn_amount: Uint128,
+    /// The amount of ASTRO which went to the governance contract
+    pub governance_amount: Uint128,
+    /// The amount of ASTRO which went to the xASTRO staking contract.
+    /// It is added to the stream when the streaming is enabled
+    pub staking_amount: Uint128,
+}
+
 /// This structure describes a migration message.
 #[cw_serde]
 pub struct MigrateMsg {
     pub default_bridge: Option<AssetInfo>,
+    pub fee_receivers: Option<Vec<FeeReceiverParams>>,
+    /// Deprecated. Sets the only additional fee receiver which is paid in ASTRO.
+    /// Kept to accept migration messages of previous versions. Can't be used along with fee_receivers
     pub second_receiver_params: Option<SecondReceiverParams>,
 }
 
+/// This structure stores the parameters of the second receiver of fees used by previous versions.
+#[cw_serde]
+pub struct SecondReceiverParams {
+    /// The second fee receiver
+    pub second_fee_receiver: String,
+    /// The percentage of fees that go to the second fee receiver
+    pub second_receiver_cut: Uint64,
+}
+
 /// This struct holds parameters to help with swapping a specific amount of a fee token to ASTRO.
 #[cw_serde]
 pub struct AssetWithLimit {
@@ -171,23 +321,254 @@ pub struct AssetWithLimit {
     pub limit: Option<Uint128>,
 }
 
-/// This structure describes the parameters for updating the second receiver of fees.
+/// This structure describes the parameters for setting an additional receiver of fees.
 #[cw_serde]
-pub struct SecondReceiverParams {
-    /// The second fee receiver
-    pub second_fee_receiver: String,
-    /// The percentage of fees that go to the second fee receiver
-    pub second_receiver_cut: Uint64,
+pub struct FeeReceiverParams {
+    /// The fee receiver address
+    pub receiver: String,
+    /// The percentage of fees that go to the fee receiver
+    pub share: Uint64,
+    /// The asset which the receiver is paid in. ASTRO is used if not set
+    pub payout_asset: Option<AssetInfo>,
 }
 
-/// This structure stores the parameters for the second receiver of fees.
+/// This structure stores the parameters of an additional receiver of fees.
 #[cw_serde]
-pub struct SecondReceiverConfig {
-    /// The second fee receiver contract address
-    pub second_fee_receiver: Addr,
-    /// The percentage of fees that go to the second fee receiver
-    pub second_receiver_cut: Uint64,
+pub struct FeeReceiver {
+    /// The fee receiver address
+    pub receiver: Addr,
+    /// The percentage of fees that go to the fee receiver
+    pub share: Uint64,
+    /// The asset which the receiver is paid in. ASTRO is used if not set
+    pub payout_asset: Option<AssetInfo>,
+}
+
+/// The maximum allowed total share of all additional fee receivers (percents)
+pub const MAX_FEE_RECEIVERS_SHARE: Uint64 = Uint64::new(50);
+/// The maximum number of additional fee receivers
+pub const MAX_FEE_RECEIVERS: usize = 10;
+
+/// This enum describes where the Maker takes TWAP prices from to protect fee swaps.
+#[cw_serde]
+pub enum TwapSource {
+    /// The Astroport oracle contract. Every pool used for fee swaps must be registered in it
+    Oracle {
+        /// The oracle contract address
+        contract: String,
+        /// The maximum age of the oracle TWAP in seconds
+        max_age: Option<u64>,
+    },
+    /// The `Observe` query of pairs which store price observations (Injective concentrated pairs only).
+    /// Observations are sampled evenly over the window and averaged
+    PairObservation {
+        /// The length of the observation window in seconds
+        seconds_ago: u64,
+    },
+}
+
+/// This structure describes the TWAP protection of fee swaps.
+/// Assets whose spot price deviates from the TWAP more than `max_deviation` are not swapped,
+/// and every swap requires a return of at least the TWAP amount minus `max_deviation`.
+#[cw_serde]
+pub struct SwapProtection {
+    /// The source of TWAP prices
+    pub twap_source: TwapSource,
+    /// The maximum allowed deviation of the swap return from the TWAP
+    pub max_deviation: Decimal,
+}
+
+/// This enum describes the update of the fee swaps TWAP protection.
+#[cw_serde]
+pub enum UpdateSwapProtection {
+    /// Sets new protection parameters
+    Set(SwapProtection),
+    /// Disables the protection
+    Remove {},
+}
+
+/// The maximum allowed share of distributed ASTRO paid to keepers (10%)
+pub const MAX_KEEPER_BOUNTY_SHARE: Decimal = Decimal::raw(100000000000000000);
+
+/// This structure describes the bounty paid to whoever collects fees.
+#[cw_serde]
+pub struct KeeperBounty {
+    /// The share of distributed ASTRO which is paid to the keeper
+    pub share: Decimal,
+    /// The maximum amount of ASTRO paid to the keeper per collection
+    pub max_bounty: Uint128,
+    /// The minimum amount of ASTRO a collection must distribute to pay the bounty.
+    /// Collections which distribute less are executed without the bounty
+    pub min_astro_amount: Uint128,
+}
+
+/// This enum describes the update of the keeper bounty.
+#[cw_serde]
+pub enum UpdateKeeperBounty {
+    /// Sets new keeper bounty parameters
+    Set(KeeperBounty),
+    /// Disables the keeper bounty
+    Remove {},
+}
+
+/// This structure describes the automatic discovery of bridges for fee tokens
+/// which cannot be swapped to ASTRO with the configured bridges or pools.
+#[cw_serde]
+pub struct AutoBridge {
+    /// For how long discovered bridges are cached in seconds
+    pub cache_ttl: u64,
+}
+
+/// This enum describes the update of the automatic bridge discovery.
+#[cw_serde]
+pub enum UpdateAutoBridge {
+    /// Enables the discovery with new parameters
+    Set(AutoBridge),
+    /// Disables the discovery. Bridges which are already discovered are kept
+    Remove {},
+}
+
+/// This structure describes the streaming of ASTRO to the xASTRO staking contract.
+/// ASTRO allocated to stakers is released linearly instead of being sent at once.
+#[cw_serde]
+pub struct AstroStreaming {
+    /// For how long each collection's ASTRO is streamed in seconds
+    pub duration: u64,
+}
+
+/// This enum describes the update of the ASTRO streaming.
+#[cw_serde]
+pub enum UpdateAstroStreaming {
+    /// Enables the streaming with new parameters
+    Set(AstroStreaming),
+    /// Disables the streaming. ASTRO which is already streamed keeps being released
+    Remove {},
+}
+
+/// This structure describes the ASTRO which is being streamed to the xASTRO staking contract.
+/// ASTRO of every distribution is a separate tranche which is released linearly until its own end.
+/// Tranches are ordered by their ends.
+#[cw_serde]
+pub struct AstroStream {
+    /// The amount of ASTRO which is not released yet
+    pub amount: Uint128,
+    /// The timestamp of the last release in seconds
+    pub last_release: u64,
+    /// The timestamp when the stream is fully released in seconds
+    pub end: u64,
+    /// The tranches which are not fully released yet
+    pub tranches: Vec<AstroTranche>,
+}
+
+impl AstroStream {
+    /// Returns the amount of ASTRO which is released at the specified timestamp.
+    pub fn released(&self, time: u64) -> Uint128 {
+        self.tranches
+            .iter()
+            .map(|tranche| tranche.released(self.last_release, time))
+            .sum()
+    }
+
+    /// Releases the ASTRO which is due at the specified timestamp and returns its amount.
+    pub fn release(&mut self, time: u64) -> Uint128 {
+        let mut released = Uint128::zero();
+        for tranche in &mut self.tranches {
+            let amount = tranche.released(self.last_release, time);
+            tranche.amount -= amount;
+            released += amount;
+        }
+
+        self.tranches.retain(|tranche| !tranche.amount.is_zero());
+        self.amount -= released;
+        self.last_release = self.last_release.max(time);
+
+        released
+    }
+
+    /// Adds ASTRO which is released linearly until `end`. Tranches are kept ordered by their ends.
+    /// Once there are `max_tranches` tranches the two which end first are merged into one
+    /// which ends at the average of their ends weighted by their amounts.
+    pub fn add_tranche(&mut self, amount: Uint128, end: u64, max_tranches: usize) {
+        if self.tranches.len() >= max_tranches.max(2) {
+            let first = self.tranches.remove(0);
+            let second = &mut self.tranches[0];
+            let total = first.amount + second.amount;
+            if !total.is_zero() {
+                second.end = first.end
+                    + Uint128::from(second.end - first.end)
+                        .multiply_ratio(second.amount, total)
+                        .u128() as u64;
+            }
+            second.amount = total;
+        }
+
+        let index = self
+            .tranches
+            .iter()
+            .position(|tranche| tranche.end > end)
+            .unwrap_or(self.tranches.len());
+        self.tranches.insert(index, AstroTranche { amount, end });
+        self.amount += amount;
+        self.end = self.end.max(end);
+    }
+}
+
+/// This structure describes the ASTRO of a distribution which is being streamed.
+#[cw_serde]
+pub struct AstroTranche {
+    /// The amount of ASTRO which is not released yet
+    pub amount: Uint128,
+    /// The timestamp when the tranche is fully released in seconds
+    pub end: u64,
+}
+
+impl AstroTranche {
+    /// Returns the amount of ASTRO which is released at the specified timestamp
+    /// since the last release of the stream.
+    pub fn released(&self, last_release: u64, time: u64) -> Uint128 {
+        if time >= self.end {
+            self.amount
+        } else if time <= last_release {
+            Uint128::zero()
+        } else {
+            self.amount
+                .multiply_ratio(time - last_release, self.end - last_release)
+        }
+    }
 }
 
-/// The maximum allowed second receiver share (percents)
-pub const MAX_SECOND_RECEIVER_CUT: Uint64 = Uint64::new(50);
+/// This structure describes the limits applied to collections of a fee token.
+#[cw_serde]
+pub struct CollectLimit {
+    /// The maximum share of the token reserve in the first pool of its swap route which is swapped at once
+    pub max_pool_share: Option<Decimal>,
+    /// The minimum interval between collections of the token in seconds
+    pub cooldown: Option<u64>,
+}
+
+/// This structure describes the parameters used to set up burning of ASTRO.
+#[cw_serde]
+pub struct AstroBurnParams {
+    /// The percentage of ASTRO which is burned after fee receivers are paid
+    pub percent: Uint64,
+    /// The address which burned ASTRO is sent to. Cw20 ASTRO is burned directly if it is not set.
+    /// It is required for native ASTRO
+    pub burn_address: Option<String>,
+}
+
+/// This structure describes burning of ASTRO.
+#[cw_serde]
+pub struct AstroBurn {
+    /// The percentage of ASTRO which is burned after fee receivers are paid
+    pub percent: Uint64,
+    /// The address which burned ASTRO is sent to
+    pub burn_address: Option<Addr>,
+}
+
+/// This enum describes the update of burning of ASTRO.
+#[cw_serde]
+pub enum UpdateAstroBurn {
+    /// Enables burning with new parameters
+    Set(AstroBurnParams),
+    /// Disables burning
+    Remove {},
+}
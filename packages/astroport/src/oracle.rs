@@ -2,6 +2,13 @@ use crate::asset::AssetInfo;
 use cosmwasm_schema::{cw_serde, QueryResponses};
 use cosmwasm_std::{Uint128, Uint256};
 
+/// The default time between two consecutive TWAP updates
+pub const DEFAULT_PERIOD: u64 = 86400;
+/// The default amount of price snapshots kept by the oracle
+pub const DEFAULT_MAX_SNAPSHOTS: u32 = 48;
+/// The maximum amount of price snapshots which can be kept by the oracle
+pub const MAX_SNAPSHOTS_LIMIT: u32 = 1000;
+
 /// This structure stores general parameters for the contract.
 #[cw_serde]
 pub struct InstantiateMsg {
@@ -9,13 +16,26 @@ pub struct InstantiateMsg {
     pub factory_contract: String,
     /// The assets that have a pool for which this contract provides price feeds
     pub asset_infos: Vec<AssetInfo>,
+    /// Minimum time in seconds between two consecutive updates. Defaults to [`DEFAULT_PERIOD`]
+    pub period: Option<u64>,
+    /// The amount of price snapshots kept for windowed TWAPs. Defaults to [`DEFAULT_MAX_SNAPSHOTS`]
+    pub max_snapshots: Option<u32>,
 }
 
 /// This structure describes the execute functions available in the contract.
 #[cw_serde]
 pub enum ExecuteMsg {
     /// Update/accumulate prices
-    Update {},
+    Update {
+        /// The registered pair to update. The default oracle pair is updated if not specified
+        pair: Option<String>,
+    },
+    /// Starts tracking the prices of the factory pair with the specified assets.
+    /// Only the owner can execute this
+    RegisterPair {
+        /// The assets of the pair
+        asset_infos: Vec<AssetInfo>,
+    },
 }
 
 /// This structure describes the query messages available in the contract.
@@ -23,15 +43,55 @@ pub enum ExecuteMsg {
 #[derive(QueryResponses)]
 pub enum QueryMsg {
     /// Calculates a new TWAP with updated precision
-    #[returns(Vec<(AssetInfo, Uint256)>)]
+    #[returns(ConsultResponse)]
     Consult {
+        /// The registered pair to use. The default oracle pair is used if not specified
+        pair: Option<String>,
         /// The asset for which to compute a new TWAP value
         token: AssetInfo,
         /// The amount of tokens for which to compute the token price
         amount: Uint128,
+        /// The maximum age in seconds of the TWAP. If set, the query fails on stale data
+        /// and doesn't fall back to the spot price
+        max_age: Option<u64>,
+    },
+    /// Calculates a TWAP over the latest stored snapshot and the newest snapshot
+    /// which is at least `window` seconds older
+    #[returns(ConsultResponse)]
+    ConsultWindow {
+        /// The registered pair to use. The default oracle pair is used if not specified
+        pair: Option<String>,
+        /// The asset for which to compute a TWAP value
+        token: AssetInfo,
+        /// The amount of tokens for which to compute the token price
+        amount: Uint128,
+        /// The minimum time span in seconds covered by the TWAP
+        window: u64,
+        /// The maximum age in seconds of the latest snapshot. If set, the query fails on stale data
+        max_age: Option<u64>,
     },
 }
 
+/// The origin of a price returned by the oracle
+#[cw_serde]
+pub enum PriceSource {
+    /// Time weighted average price calculated from the pair cumulative prices
+    Twap,
+    /// Spot price simulated in the pair because no TWAP is available yet
+    Spot,
+}
+
+/// This structure describes the response of consult queries.
+#[cw_serde]
+pub struct ConsultResponse {
+    /// The amounts of the other pool assets which are worth the consulted amount
+    pub prices: Vec<(AssetInfo, Uint256)>,
+    /// The timestamp at which the end of the averaging window was recorded
+    pub block_timestamp_last: u64,
+    /// Whether the prices come from the TWAP or from a spot price fallback
+    pub source: PriceSource,
+}
+
 /// This structure describes a migration message.
 /// We currently take no arguments for migrations.
 #[cw_serde]
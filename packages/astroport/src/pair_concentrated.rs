@@ -56,15 +56,40 @@ pub struct PromoteParams {
     pub future_time: u64,
 }
 
+/// Pool parameters should be changed gradually. This structure holds target values of the parameters
+/// which are being ramped and the timestamp when they should be reached.
+/// Amp and gamma are ramped via [`PromoteParams`].
+#[cw_serde]
+pub struct ScheduleParams {
+    /// Target pool parameters. Omitted parameters are kept unchanged.
+    pub params: UpdatePoolParams,
+    /// Target price scale. If set then price scale is moved toward it on every swap
+    /// and the regular repegging is paused until the target is reached.
+    pub price_scale: Option<Decimal>,
+    /// Timestamp when the target values should be reached
+    pub future_time: u64,
+}
+
 /// This enum intended for parameters update.
 #[cw_serde]
 pub enum ConcentratedPoolUpdateParams {
     /// Allows to update fee parameters as well as repeg_profit_threshold, min_price_scale_delta and EMA interval.
+    /// Every parameter can't be changed by more than 50% at once.
     Update(UpdatePoolParams),
     /// Starts gradual (de/in)crease of Amp or Gamma parameters. Can handle an update of both of them.
     Promote(PromoteParams),
     /// Stops Amp and Gamma update and stores current values.
     StopChangingAmpGamma {},
+    /// Starts gradual (de/in)crease of the pool parameters.
+    ScheduleParams(ScheduleParams),
+    /// Stops pool parameters update and stores current values.
+    StopParamsSchedule {},
+    /// Moves price_scale toward target_price even if repeg_profit_threshold blocks a regular repeg.
+    /// The pool's xcp loss is bounded by max_loss thus price_scale may be moved only partially.
+    ForceRepeg {
+        target_price: Decimal,
+        max_loss: Decimal,
+    },
     /// Enable asset balances tracking
     EnableAssetBalancesTracking {},
 }
@@ -94,6 +119,25 @@ pub struct ConcentratedPoolConfig {
     pub ma_half_time: u64,
     /// Whether asset balances are tracked over blocks or not.
     pub track_asset_balances: bool,
+    /// Active pool parameters schedule if any
+    pub params_schedule: Option<ParamsScheduleResponse>,
+}
+
+/// This structure describes the pool parameters which are being changed gradually.
+#[cw_serde]
+pub struct ParamsScheduleResponse {
+    /// Pool parameters at the moment when the schedule was started
+    pub initial_params: UpdatePoolParams,
+    /// Target pool parameters
+    pub future_params: UpdatePoolParams,
+    /// Price scale at the moment when the schedule was started if price scale is being changed
+    pub initial_price_scale: Option<Decimal>,
+    /// Target price scale if price scale is being changed
+    pub future_price_scale: Option<Decimal>,
+    /// Timestamp when the schedule was started
+    pub initial_time: u64,
+    /// Timestamp when the pool parameters should become equal to future_params
+    pub future_time: u64,
 }
 
 /// This structure describes the query messages available in the contract.
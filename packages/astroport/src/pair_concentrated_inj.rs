@@ -15,6 +15,29 @@ pub struct OrderbookConfig {
     pub market_id: String,
     pub orders_number: u8,
     pub min_trades_to_avg: u32,
+    /// Defines how liquidity is spread over orders. Default is [`LiquidityDistribution::AvgTradeSize`].
+    pub liquidity_distribution: Option<LiquidityDistribution>,
+    /// Maximum share of pool liquidity which can be placed in the orderbook on each side.
+    /// If not set, the whole liquidity required by orders is deployed.
+    pub max_liquidity_share: Option<Decimal>,
+}
+
+/// This enum describes the shape of liquidity distribution over orderbook levels.
+/// Order size on each level is the average trade size multiplied by the level weight.
+#[cw_serde]
+pub enum LiquidityDistribution {
+    /// Every order has the size of an average trade.
+    AvgTradeSize {},
+    /// Order weight grows linearly: weight_i = 1 + step * (i - 1)
+    Linear { step: Decimal },
+    /// Order weight grows (ratio > 1) or decays (ratio < 1) geometrically: weight_i = ratio ^ (i - 1)
+    Geometric { ratio: Decimal },
+}
+
+impl Default for LiquidityDistribution {
+    fn default() -> Self {
+        Self::AvgTradeSize {}
+    }
 }
 
 /// This structure holds concentrated pool parameters along with orderbook params specific for Injective.
@@ -109,6 +132,9 @@ pub enum QueryMsg {
     Observe { seconds_ago: u64 },
     #[returns(OrderbookStateResponse)]
     OrderbookState {},
+    /// Returns the latest orderbook reconciliations starting from the most recent one
+    #[returns(Vec<OrderbookReconciliation>)]
+    OrderbookHistory { limit: Option<u32> },
 }
 
 #[cw_serde]
@@ -132,6 +158,36 @@ pub struct OrderbookStateResponse {
     pub min_trades_to_avg: u32,
     /// Whether the pool is ready to integrate with the orderbook (MIN_TRADES_TO_AVG is reached)
     pub ready: bool,
+    /// Shape of liquidity distribution over orderbook levels
+    pub liquidity_distribution: LiquidityDistribution,
+    /// Maximum share of pool liquidity which can be placed in the orderbook on each side
+    pub max_liquidity_share: Option<Decimal>,
+}
+
+/// This structure describes a cumulative trade filled in the orderbook between two reconciliations.
+#[cw_serde]
+pub struct OrderbookTrade {
+    /// Whether buy orders were filled i.e. the pool bought base asset for quote asset
+    pub is_buy: bool,
+    /// Base asset amount which was bought or sold
+    pub base_amount: Uint128,
+    /// Quote asset amount which was paid or received
+    pub quote_amount: Uint128,
+}
+
+/// This structure describes a single orderbook reconciliation performed in the begin blocker.
+#[cw_serde]
+pub struct OrderbookReconciliation {
+    /// Block timestamp when the reconciliation happened
+    pub timestamp: u64,
+    /// Subaccount balances saved on the previous reconciliation
+    pub last_balances: Vec<Asset>,
+    /// Subaccount balances before the current reconciliation
+    pub balances: Vec<Asset>,
+    /// Cumulative trade filled since the previous reconciliation
+    pub trade: Option<OrderbookTrade>,
+    /// Maker fee paid to the fee receiver
+    pub maker_fee: Option<Asset>,
 }
 
 #[cw_serde]
@@ -155,6 +211,19 @@ pub enum ConcentratedObPoolUpdateParams {
     Promote(PromoteParams),
     /// Stops Amp and Gamma update and stores current values.
     StopChangingAmpGamma {},
-    /// Update orderbook params.
-    UpdateOrderbookParams { orders_number: u8 },
+    /// Update orderbook params. Fields which are not set remain unchanged.
+    UpdateOrderbookParams {
+        orders_number: Option<u8>,
+        liquidity_distribution: Option<LiquidityDistribution>,
+        max_liquidity_share: Option<UpdateMaxLiquidityShare>,
+    },
+}
+
+/// This enum describes how the max share of pool liquidity placed in the orderbook is updated.
+#[cw_serde]
+pub enum UpdateMaxLiquidityShare {
+    /// Sets the new max liquidity share
+    Set(Decimal),
+    /// Removes the limit, i.e. the whole liquidity required by orders is deployed
+    Remove {},
 }
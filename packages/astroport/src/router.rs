@@ -1,17 +1,32 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
 
-use cosmwasm_std::{Decimal, Uint128};
+use cosmwasm_std::{Coin, Decimal, Uint128};
 use cw20::Cw20ReceiveMsg;
 
-use crate::asset::AssetInfo;
+use crate::asset::{Asset, AssetInfo};
+use crate::factory::UpdateAddr;
 
 pub const MAX_SWAP_OPERATIONS: usize = 50;
+/// Maximum amount of hops in a route discovered by the router
+pub const MAX_ROUTE_HOPS: u32 = 3;
+/// Maximum amount of parallel routes in a split swap
+pub const MAX_SPLIT_ROUTES: usize = 10;
+/// Default maximum referral commission (1%)
+pub const DEFAULT_MAX_REFERRAL_COMMISSION: Decimal = Decimal::raw(10000000000000000);
 
 /// This structure holds the parameters used for creating a contract.
 #[cw_serde]
 pub struct InstantiateMsg {
     /// The astroport factory contract address
     pub astroport_factory: String,
+    /// The address which is allowed to update the contract parameters
+    pub owner: String,
+    /// The contract which performs [`SwapOperation::NativeSwap`] operations.
+    /// Native swaps are disabled if not set
+    pub native_swap_handler: Option<String>,
+    /// The maximum commission a referrer can take from the offer amount.
+    /// Defaults to [`DEFAULT_MAX_REFERRAL_COMMISSION`]
+    pub max_referral_commission: Option<Decimal>,
 }
 
 /// This enum describes a swap operation.
@@ -30,10 +45,26 @@ pub enum SwapOperation {
         offer_asset_info: AssetInfo,
         /// Information about the asset we swap to
         ask_asset_info: AssetInfo,
+        /// Max spread of this hop. Overrides the route-level max spread.
+        /// The spread is asserted even if the hop is a part of a multi hop route
+        max_spread: Option<Decimal>,
+        /// Belief price of this hop. The spread is asserted even if the hop is a part of a multi hop route
+        belief_price: Option<Decimal>,
     },
 }
 
 impl SwapOperation {
+    pub fn get_offer_asset_info(&self) -> AssetInfo {
+        match self {
+            SwapOperation::NativeSwap { offer_denom, .. } => AssetInfo::NativeToken {
+                denom: offer_denom.clone(),
+            },
+            SwapOperation::AstroSwap {
+                offer_asset_info, ..
+            } => offer_asset_info.clone(),
+        }
+    }
+
     pub fn get_target_asset_info(&self) -> AssetInfo {
         match self {
             SwapOperation::NativeSwap { ask_denom, .. } => AssetInfo::NativeToken {
@@ -55,6 +86,62 @@ pub enum ExecuteMsg {
         minimum_receive: Option<Uint128>,
         to: Option<String>,
         max_spread: Option<Decimal>,
+        /// The referrer address and its commission which is deducted from the offer amount before the first hop
+        referral: Option<(String, Decimal)>,
+    },
+    /// ExecuteBestRoute discovers the route with the highest simulated output among factory pairs
+    /// and swaps native coins sent along with the message through it
+    ExecuteBestRoute {
+        /// The asset to swap to
+        ask_asset_info: AssetInfo,
+        /// The maximum amount of hops in a route. Defaults to [`MAX_ROUTE_HOPS`]
+        max_hops: Option<u32>,
+        minimum_receive: Option<Uint128>,
+        to: Option<String>,
+        max_spread: Option<Decimal>,
+    },
+    /// ExecuteSplitSwap splits native coins sent along with the message across parallel routes
+    /// according to route weights. Weights must sum up to 1
+    ExecuteSplitSwap {
+        routes: Vec<(Decimal, Vec<SwapOperation>)>,
+        minimum_receive: Option<Uint128>,
+        to: Option<String>,
+        max_spread: Option<Decimal>,
+    },
+    /// ExecuteSwapOperationsExactOut swaps native coins sent along with the message so that the recipient
+    /// receives at least `ask_amount` of the ask asset. Leftover offer coins are refunded to the sender
+    ExecuteSwapOperationsExactOut {
+        operations: Vec<SwapOperation>,
+        /// The amount of ask tokens to receive
+        ask_amount: Uint128,
+        /// The maximum amount of offer tokens to spend. Defaults to the amount sent
+        max_offer: Option<Uint128>,
+        to: Option<String>,
+        max_spread: Option<Decimal>,
+    },
+    /// ZapIn swaps native coins sent along with the message into the pair assets
+    /// and provides liquidity to the pair
+    ZapIn {
+        /// The pair to provide liquidity to. Only pairs with two assets are supported
+        pair_addr: String,
+        /// Swap operations which convert the offer asset into one of the pair assets.
+        /// Empty if the offer asset is already one of the pair assets
+        operations: Vec<SwapOperation>,
+        /// The slippage tolerance of the liquidity provision
+        slippage_tolerance: Option<Decimal>,
+        /// Determines whether LP tokens are staked in the Generator contract
+        auto_stake: Option<bool>,
+        /// The receiver of LP tokens. Defaults to the sender
+        receiver: Option<String>,
+        max_spread: Option<Decimal>,
+    },
+
+    /// UpdateConfig updates the contract parameters. Only the owner can execute this
+    UpdateConfig {
+        /// Sets or removes the contract which performs [`SwapOperation::NativeSwap`] operations
+        native_swap_handler: Option<UpdateAddr>,
+        /// Sets the maximum commission a referrer can take from the offer amount
+        max_referral_commission: Option<Decimal>,
     },
 
     /// Internal use
@@ -73,6 +160,31 @@ pub enum ExecuteMsg {
         minimum_receive: Uint128,
         receiver: String,
     },
+    /// Internal use
+    /// ZapInSwap swaps a part of the router balance of one pair asset into the other one
+    /// so that both assets have the proportions of the pool
+    ZapInSwap {
+        pair_addr: String,
+        offer_asset_info: AssetInfo,
+        max_spread: Option<Decimal>,
+    },
+    /// Internal use
+    /// ZapInProvide provides the whole router balance of the pair assets as liquidity
+    ZapInProvide {
+        pair_addr: String,
+        slippage_tolerance: Option<Decimal>,
+        auto_stake: Option<bool>,
+        receiver: String,
+    },
+    /// Internal use
+    /// ZapOutSwap sends the router balance of the ask asset to the recipient and swaps
+    /// the router balance of other pair assets into the ask asset
+    ZapOutSwap {
+        pair_addr: String,
+        ask_asset_info: AssetInfo,
+        to: String,
+        max_spread: Option<Decimal>,
+    },
 }
 
 #[cw_serde]
@@ -86,6 +198,68 @@ pub enum Cw20HookMsg {
         to: Option<String>,
         /// Max spread
         max_spread: Option<Decimal>,
+        /// The referrer address and its commission which is deducted from the offer amount before the first hop
+        referral: Option<(String, Decimal)>,
+    },
+    ExecuteBestRoute {
+        /// The asset to swap to
+        ask_asset_info: AssetInfo,
+        /// The maximum amount of hops in a route. Defaults to [`MAX_ROUTE_HOPS`]
+        max_hops: Option<u32>,
+        /// The minimum amount of tokens to get from a swap
+        minimum_receive: Option<Uint128>,
+        /// The recipient
+        to: Option<String>,
+        /// Max spread
+        max_spread: Option<Decimal>,
+    },
+    ExecuteSwapOperationsExactOut {
+        /// A vector of swap operations
+        operations: Vec<SwapOperation>,
+        /// The amount of ask tokens to receive
+        ask_amount: Uint128,
+        /// The maximum amount of offer tokens to spend. Defaults to the amount sent
+        max_offer: Option<Uint128>,
+        /// The recipient
+        to: Option<String>,
+        /// Max spread
+        max_spread: Option<Decimal>,
+    },
+    ExecuteSplitSwap {
+        /// Parallel routes along with their weights
+        routes: Vec<(Decimal, Vec<SwapOperation>)>,
+        /// The minimum amount of tokens to get from all routes
+        minimum_receive: Option<Uint128>,
+        /// The recipient
+        to: Option<String>,
+        /// Max spread
+        max_spread: Option<Decimal>,
+    },
+    ZapIn {
+        /// The pair to provide liquidity to. Only pairs with two assets are supported
+        pair_addr: String,
+        /// Swap operations which convert the offer token into one of the pair assets.
+        /// Empty if the offer token is already one of the pair assets
+        operations: Vec<SwapOperation>,
+        /// The slippage tolerance of the liquidity provision
+        slippage_tolerance: Option<Decimal>,
+        /// Determines whether LP tokens are staked in the Generator contract
+        auto_stake: Option<bool>,
+        /// The receiver of LP tokens. Defaults to the sender
+        receiver: Option<String>,
+        /// Max spread
+        max_spread: Option<Decimal>,
+    },
+    /// ZapOut withdraws liquidity with the LP tokens sent and swaps all withdrawn assets into one of the pair assets
+    ZapOut {
+        /// The pair asset to receive
+        ask_asset_info: AssetInfo,
+        /// The minimum amount of tokens to receive
+        minimum_receive: Option<Uint128>,
+        /// The recipient
+        to: Option<String>,
+        /// Max spread
+        max_spread: Option<Decimal>,
     },
 }
 
@@ -103,6 +277,45 @@ pub enum QueryMsg {
         offer_amount: Uint128,
         /// The swap operations to perform, each swap involving a specific pool
         operations: Vec<SwapOperation>,
+        /// The referral commission which is deducted from the offer amount before the first hop
+        referral_commission: Option<Decimal>,
+    },
+    /// SimulateSwapOperationsDetailed simulates multi-hop swap operations and returns
+    /// the result of every hop along with the cumulative price impact
+    #[returns(SimulateSwapOperationsDetailedResponse)]
+    SimulateSwapOperationsDetailed {
+        /// The amount of tokens to swap
+        offer_amount: Uint128,
+        /// The swap operations to perform, each swap involving a specific pool
+        operations: Vec<SwapOperation>,
+    },
+    /// ReverseSimulateSwapOperations walks multi-hop swap operations backwards and returns
+    /// the amount of offer tokens required to receive the specified amount of ask tokens
+    #[returns(ReverseSimulateSwapOperationsResponse)]
+    ReverseSimulateSwapOperations {
+        /// The amount of ask tokens to receive
+        ask_amount: Uint128,
+        /// The swap operations to perform, each swap involving a specific pool
+        operations: Vec<SwapOperation>,
+    },
+    /// SimulateSplitSwap simulates swaps split across parallel routes.
+    /// Every route is simulated independently against the current pools state
+    #[returns(SimulateSplitSwapResponse)]
+    SimulateSplitSwap {
+        /// The amount of tokens to swap
+        offer_amount: Uint128,
+        /// Parallel routes along with their weights
+        routes: Vec<(Decimal, Vec<SwapOperation>)>,
+    },
+    /// FindBestRoute explores factory pairs and returns the route with the highest simulated output
+    #[returns(BestRouteResponse)]
+    FindBestRoute {
+        /// The asset to swap
+        offer_asset: Asset,
+        /// The asset to swap to
+        ask_asset_info: AssetInfo,
+        /// The maximum amount of hops in a route. Defaults to [`MAX_ROUTE_HOPS`]
+        max_hops: Option<u32>,
     },
 }
 
@@ -111,6 +324,12 @@ pub enum QueryMsg {
 pub struct ConfigResponse {
     /// The Astroport factory contract address
     pub astroport_factory: String,
+    /// The address which is allowed to update the contract parameters
+    pub owner: String,
+    /// The contract which performs [`SwapOperation::NativeSwap`] operations
+    pub native_swap_handler: Option<String>,
+    /// The maximum commission a referrer can take from the offer amount
+    pub max_referral_commission: Decimal,
 }
 
 /// This structure describes a custom struct to return a query response containing the end amount of a swap simulation
@@ -120,7 +339,102 @@ pub struct SimulateSwapOperationsResponse {
     pub amount: Uint128,
 }
 
+/// This structure describes the simulation result of a single hop
+#[cw_serde]
+pub struct HopSimulation {
+    /// The asset swapped in this hop
+    pub offer_asset: Asset,
+    /// The asset received in this hop
+    pub return_asset: Asset,
+    /// The spread amount of this hop
+    pub spread_amount: Uint128,
+    /// The commission amount of this hop
+    pub commission_amount: Uint128,
+    /// The share of the ideal return amount lost to the spread
+    pub price_impact: Decimal,
+}
+
+/// This structure describes a custom struct to return a query response containing the result of every hop of a swap simulation
+#[cw_serde]
+pub struct SimulateSwapOperationsDetailedResponse {
+    /// The amount of tokens received in a swap simulation
+    pub amount: Uint128,
+    /// Simulation results of every hop
+    pub hops: Vec<HopSimulation>,
+    /// Cumulative price impact of all hops
+    pub price_impact: Decimal,
+}
+
+/// This structure describes a custom struct to return a query response containing the result of a reverse swap simulation
+#[cw_serde]
+pub struct ReverseSimulateSwapOperationsResponse {
+    /// The amount of offer tokens required to receive the ask amount
+    pub offer_amount: Uint128,
+}
+
+/// This structure describes a custom struct to return a query response containing the result of a split swap simulation
+#[cw_serde]
+pub struct SimulateSplitSwapResponse {
+    /// The total amount of tokens received in a swap simulation
+    pub amount: Uint128,
+    /// The amount of tokens received from each route
+    pub routes_amounts: Vec<Uint128>,
+}
+
+/// This structure describes a custom struct to return a query response containing the best route
+#[cw_serde]
+pub struct BestRouteResponse {
+    /// The swap operations of the best route
+    pub operations: Vec<SwapOperation>,
+    /// The amount of tokens received in a swap simulation
+    pub amount: Uint128,
+}
+
 /// This structure describes a migration message.
-/// We currently take no arguments for migrations.
 #[cw_serde]
-pub struct MigrateMsg {}
+pub struct MigrateMsg {
+    /// The address which is allowed to update the contract parameters
+    pub owner: String,
+    /// Sets the contract which performs [`SwapOperation::NativeSwap`] operations
+    pub native_swap_handler: Option<String>,
+    /// Sets the maximum commission a referrer can take from the offer amount
+    pub max_referral_commission: Option<Decimal>,
+}
+
+/// This structure describes the execute messages which a native swap handler contract must implement.
+#[cw_serde]
+pub enum NativeSwapHandlerExecuteMsg {
+    /// Swaps the native coin sent along with the message to `ask_denom`.
+    /// Ask coins are sent to `to` or to the message sender if `to` is not set
+    Swap {
+        ask_denom: String,
+        to: Option<String>,
+    },
+}
+
+/// This structure describes the queries which a native swap handler contract must implement.
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum NativeSwapHandlerQueryMsg {
+    /// Simulates a swap of `offer_coin` to `ask_denom`
+    #[returns(NativeSwapSimulationResponse)]
+    Simulate { offer_coin: Coin, ask_denom: String },
+    /// Returns the amount of `offer_denom` coins required to receive `ask_coin`.
+    /// The offer amount must be rounded up
+    #[returns(NativeSwapReverseSimulationResponse)]
+    ReverseSimulate { ask_coin: Coin, offer_denom: String },
+}
+
+/// This structure describes a native swap simulation result.
+#[cw_serde]
+pub struct NativeSwapSimulationResponse {
+    /// The amount of ask coins received in a swap simulation
+    pub return_amount: Uint128,
+}
+
+/// This structure describes a native swap reverse simulation result.
+#[cw_serde]
+pub struct NativeSwapReverseSimulationResponse {
+    /// The amount of offer coins required to receive the ask coins
+    pub offer_amount: Uint128,
+}